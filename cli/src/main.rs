@@ -0,0 +1,291 @@
+//! The `lmux` command, running commands side by side with one group per command.
+//!
+//! ```text
+//! lmux run --name build "cargo build" --name test "cargo test"
+//! ```
+//!
+//! The status of each group is derived from the exit code of its command, and the process exits
+//! with code 1 if any command failed.
+
+use anyhow::Result;
+use clap::Parser;
+use clap::Subcommand;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitCode;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// Interval of checking whether the commands exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Time the output of an exited command is read for. Processes started by the command may keep
+/// its output open after it exits.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+// ===========
+// === Cli ===
+// ===========
+
+#[derive(Debug, Parser)]
+#[command(name = "lmux", version, about = "Terminal log multiplexer")]
+struct Cli {
+    #[command(subcommand)]
+    command: CliCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum CliCommand {
+    /// Runs the commands in parallel, showing the output of each one in its own group.
+    Run(RunArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct RunArgs {
+    /// Name of the group and the shell command shown in it. Can be repeated.
+    #[arg(
+        long = "name",
+        num_args = 2,
+        value_names = ["NAME", "COMMAND"],
+        required = true,
+        action = clap::ArgAction::Append,
+    )]
+    commands: Vec<String>,
+    /// Collapse the groups of the commands which succeeded.
+    #[arg(long)]
+    collapse_on_success: bool,
+    /// Write the output of the commands to the file, one JSON object per line.
+    #[arg(long, value_name = "PATH")]
+    export: Option<PathBuf>,
+    /// Print the lines prefixed with the command names instead of showing the UI, and exit when
+    /// all commands finish.
+    #[arg(long)]
+    headless: bool,
+}
+
+// ==============
+// === Export ===
+// ==============
+
+/// JSON Lines transcript of the commands, see [`RunArgs::export`].
+#[derive(Clone, Debug, Default)]
+struct Export {
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl Export {
+    fn create(path: Option<&PathBuf>) -> Result<Self> {
+        let file = path.map(File::create).transpose()?.map(|file| Arc::new(Mutex::new(file)));
+        Ok(Self { file })
+    }
+
+    fn write(&self, record: serde_json::Value) {
+        let Some(file) = &self.file else { return };
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{record}").ok();
+    }
+
+    fn line(&self, name: &str, stream: &str, line: &str) {
+        let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        let time_ms = time.as_millis() as u64;
+        self.write(serde_json::json!({
+            "name": name, "stream": stream, "time_ms": time_ms, "line": line
+        }));
+    }
+
+    fn exit(&self, name: &str, code: Option<i32>) {
+        self.write(serde_json::json!({ "name": name, "exit_code": code }));
+    }
+}
+
+// ===============
+// === Process ===
+// ===============
+
+/// Command running in its own group. On Unix, it also runs in its own process group, so that the
+/// processes it starts are killed with it.
+#[derive(Debug)]
+struct Process {
+    id: lmux::group::Id,
+    name: String,
+    child: Child,
+    readers: Vec<JoinHandle<()>>,
+    /// Exit status of the command and the time it was noticed at, if it exited.
+    exited: Option<(ExitStatus, Instant)>,
+}
+
+impl Process {
+    fn spawn(id: lmux::group::Id, name: String, command: &str, export: &Export) -> Result<Self> {
+        let mut shell = shell(command);
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        let mut child = shell
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(forward(name.clone(), "stdout", stdout, export.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(forward(name.clone(), "stderr", stderr, export.clone()));
+        }
+        let exited = None;
+        Ok(Self { id, name, child, readers, exited })
+    }
+
+    /// Kills the command and the processes it started.
+    #[cfg(unix)]
+    fn kill(&self) {
+        // Once the command was waited for, its id may be reused.
+        if self.exited.is_some() { return }
+        let Ok(pid) = libc::pid_t::try_from(self.child.id()) else { return };
+        // SAFETY: Sending a signal has no memory safety requirements.
+        unsafe { libc::kill(-pid, libc::SIGKILL) };
+    }
+
+    #[cfg(not(unix))]
+    fn kill(&mut self) {
+        self.child.kill().ok();
+    }
+
+    /// Exit status of the command once it exited and its output was read, or [`DRAIN_TIMEOUT`]
+    /// passed. The readers are not waited for, as they may be blocked by the started processes.
+    fn poll(&mut self) -> Result<Option<ExitStatus>> {
+        if self.exited.is_none() {
+            self.exited = self.child.try_wait()?.map(|status| (status, Instant::now()));
+        }
+        let Some((status, exited_at)) = self.exited else { return Ok(None) };
+        let drained = self.readers.iter().all(|reader| reader.is_finished());
+        Ok((drained || exited_at.elapsed() >= DRAIN_TIMEOUT).then_some(status))
+    }
+
+    /// Pushes the final line of the group.
+    fn finish(self, status: ExitStatus, export: &Export) {
+        let code = status.code();
+        export.exit(&self.name, code);
+        let message = match code {
+            Some(code) => format!("Exited with code {code}."),
+            None => "Terminated by a signal.".to_owned(),
+        };
+        let status = if status.success() { lmux::Status::ok() } else { lmux::Status::error() };
+        lmux::log(&self.name, status.finished(), message);
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Logs the lines of the stream to the group on a dedicated thread.
+fn forward(
+    name: String,
+    stream: &'static str,
+    output: impl Read + Send + 'static,
+    export: Export,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(line) = line else { break };
+            export.line(&name, stream, &line);
+            lmux::log(&name, None, line);
+        }
+    })
+}
+
+// ===========
+// === Run ===
+// ===========
+
+fn run(args: RunArgs) -> Result<ExitCode> {
+    let export = Export::create(args.export.as_ref())?;
+    let interactive = !args.headless && lmux::is_interactive();
+    let mut builder = lmux::Builder::new().enabled(interactive).defer_first_draw_until_ready(true);
+    if args.collapse_on_success {
+        builder = builder.auto_collapse_default(lmux::group::AutoCollapse::collapse_on_success());
+    }
+    let tui_handle = builder.spawn()?;
+
+    // Groups killed from their context menus, handled on this thread which owns the processes.
+    let (kill_sender, kill_receiver) = mpsc::channel();
+    let mut running = Vec::new();
+    for (i, pair) in args.commands.chunks(2).enumerate() {
+        let [name, command] = pair else { continue };
+        let id = lmux::create_group_ordered(name, i as i64)?;
+        lmux::set_header(name, name.clone());
+        let kill_sender = kill_sender.clone();
+        lmux::set_kill_handler(id, move |id| _ = kill_sender.send(id))?;
+        running.push(Process::spawn(id, name.clone(), command, &export)?);
+    }
+    lmux::ready()?;
+
+    let mut failed = false;
+    while !running.is_empty() {
+        if interactive && !tui_handle.is_running() {
+            // The UI was quit, the remaining commands are not shown anymore.
+            for mut process in running.drain(..) {
+                process.kill();
+                process.child.wait().ok();
+                lmux::cancel_group(process.id)?;
+            }
+            failed = true;
+            break;
+        }
+        for id in kill_receiver.try_iter() {
+            for process in running.iter_mut().filter(|process| process.id == id) {
+                process.kill();
+            }
+        }
+        let mut still_running = Vec::new();
+        for mut process in running {
+            match process.poll()? {
+                Some(status) => {
+                    failed |= !status.success();
+                    process.finish(status, &export);
+                }
+                None => still_running.push(process),
+            }
+        }
+        running = still_running;
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    // The interactive UI stays open until it is quit, so that the output can be inspected.
+    tui_handle.join()?;
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+// ============
+// === Main ===
+// ============
+
+fn main() -> Result<ExitCode> {
+    match Cli::parse().command {
+        CliCommand::Run(args) => run(args),
+    }
+}
@@ -0,0 +1,91 @@
+//! Tests running the `lmux run` command headless, checking its exit code and its transcript.
+
+use anyhow::Result;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Transcript records without their times.
+type Records = Vec<serde_json::Value>;
+
+/// Runs `lmux run --headless` with the arguments, exporting the transcript to a temporary file.
+fn run(test: &str, args: &[&str]) -> Result<(Output, Records)> {
+    let path = export_path(test);
+    let output = Command::new(env!("CARGO_BIN_EXE_lmux"))
+        .args(["run", "--headless", "--export"])
+        .arg(&path)
+        .args(args)
+        .output()?;
+    let transcript = std::fs::read_to_string(&path)?;
+    std::fs::remove_file(&path)?;
+    let mut records = Vec::new();
+    for line in transcript.lines() {
+        let mut record: serde_json::Value = serde_json::from_str(line)?;
+        if let Some(record) = record.as_object_mut() {
+            record.remove("time_ms");
+        }
+        records.push(record);
+    }
+    Ok((output, records))
+}
+
+fn export_path(test: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("lmux-run-{test}-{}.jsonl", std::process::id()))
+}
+
+/// Records of one command, as the commands run in parallel.
+fn records_of(records: &Records, name: &str) -> Records {
+    records.iter().filter(|record| record["name"] == name).cloned().collect()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn succeeding_command_exits_with_zero() -> Result<()> {
+    let (output, records) = run("success", &["--name", "greet", "echo hello; echo warn >&2"])?;
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[greet] hello"));
+    assert_eq!(records, [
+        serde_json::json!({ "name": "greet", "stream": "stdout", "line": "hello" }),
+        serde_json::json!({ "name": "greet", "stream": "stderr", "line": "warn" }),
+        serde_json::json!({ "name": "greet", "exit_code": 0 }),
+    ]);
+    Ok(())
+}
+
+#[test]
+fn failing_command_exits_with_one() -> Result<()> {
+    let args = ["--name", "ok", "echo done", "--name", "bad", "echo oops; exit 3"];
+    let (output, records) = run("failure", &args)?;
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(records_of(&records, "ok"), [
+        serde_json::json!({ "name": "ok", "stream": "stdout", "line": "done" }),
+        serde_json::json!({ "name": "ok", "exit_code": 0 }),
+    ]);
+    assert_eq!(records_of(&records, "bad"), [
+        serde_json::json!({ "name": "bad", "stream": "stdout", "line": "oops" }),
+        serde_json::json!({ "name": "bad", "exit_code": 3 }),
+    ]);
+    Ok(())
+}
+
+#[test]
+fn collapse_on_success_keeps_the_transcript_and_exit_code() -> Result<()> {
+    let args = ["--collapse-on-success", "--name", "a", "echo a", "--name", "b", "exit 2"];
+    let (output, records) = run("collapse", &args)?;
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(records_of(&records, "a"), [
+        serde_json::json!({ "name": "a", "stream": "stdout", "line": "a" }),
+        serde_json::json!({ "name": "a", "exit_code": 0 }),
+    ]);
+    assert_eq!(records_of(&records, "b"), [serde_json::json!({ "name": "b", "exit_code": 2 })]);
+    let (output, _) = run("collapse-success", &["--collapse-on-success", "--name", "a", "true"])?;
+    assert_eq!(output.status.code(), Some(0));
+    Ok(())
+}
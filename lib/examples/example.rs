@@ -15,7 +15,6 @@ const START_DELAY: u64 = if DEBUG_SLOW { 1000 } else { 100 };
 // =================
 
 struct TaskConfig {
-    start_delay: u64,
     lines: usize,
     line_delay: u64,
     line_status: Box<dyn Fn(&TaskConfig, usize) -> lmux::Status + Send + Sync + 'static>,
@@ -24,7 +23,6 @@ struct TaskConfig {
 fn tasks() -> Vec<TaskConfig> {
     vec![
         TaskConfig {
-            start_delay: 0 * START_DELAY,
             lines: 100,
             line_delay: LINE_DELAY,
             line_status: Box::new(|cfg, line| {
@@ -33,7 +31,6 @@ fn tasks() -> Vec<TaskConfig> {
             }),
         },
         TaskConfig {
-            start_delay: 1 * START_DELAY,
             lines: 100,
             line_delay: LINE_DELAY,
             line_status: Box::new(|cfg, line| {
@@ -42,7 +39,6 @@ fn tasks() -> Vec<TaskConfig> {
             }),
         },
         TaskConfig {
-            start_delay: 2 * START_DELAY,
             lines: 100,
             line_delay: LINE_DELAY,
             line_status: Box::new(|cfg, line| {
@@ -51,7 +47,6 @@ fn tasks() -> Vec<TaskConfig> {
             }),
         },
         TaskConfig {
-            start_delay: 3 * START_DELAY,
             lines: 80,
             line_delay: LINE_DELAY,
             line_status: Box::new(|cfg, line| {
@@ -60,7 +55,6 @@ fn tasks() -> Vec<TaskConfig> {
             }),
         },
         TaskConfig {
-            start_delay: 4 * START_DELAY,
             lines: 100,
             line_delay: LINE_DELAY,
             line_status: Box::new(|cfg, line| {
@@ -77,19 +71,25 @@ fn tasks() -> Vec<TaskConfig> {
 // ============
 
 #[tokio::main]
-async fn main() {
-    let tui_handle = tokio::task::spawn_blocking(|| {
-        let out = lmux::main(true);
-        println!("Result: {out:?}")
-    });
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let theme = lmux::style::Theme { sparkline_len: 8, ..Default::default() };
+    let tui_handle = lmux::Builder::new()
+        .theme(theme)
+        .stale_after(Duration::from_secs(5))
+        .order(lmux::Order::ErrorsFirst)
+        .defer_first_draw_until_ready(true)
+        .spawn()?;
 
     let handles: Vec<_> = tasks().into_iter().enumerate()
         .map(|(i, cfg)| {
             let id = format!("task_{i}");
             let label = format!("TASK {i}");
+            // Explicit keys keep the layout identical across runs, regardless of start jitter.
+            lmux::create_group_ordered(&id, i as i64).ok();
             lmux::set_header(&id, label);
+            let start_delay = START_DELAY * i as u64;
             tokio::spawn(async move {
-                sleep(Duration::from_millis(cfg.start_delay)).await;
+                sleep(Duration::from_millis(start_delay)).await;
                 for line in 1..=cfg.lines {
                     let status = (cfg.line_status)(&cfg, line);
                     lmux::log(&id, Some(status), format!("Output line {line}"));
@@ -101,12 +101,15 @@ async fn main() {
             })
         })
         .collect();
+    lmux::ready()?;
 
     if WAIT_FOR_TASKS {
         for handle in handles {
-            handle.await.unwrap();
+            handle.await?;
         }
         lmux::debug("All tasks done.");
     }
-    tui_handle.await.unwrap();
+    let out = tui_handle.join();
+    println!("Result: {out:?}");
+    Ok(())
 }
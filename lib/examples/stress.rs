@@ -0,0 +1,51 @@
+//! Logs as fast as possible to one group, and slowly to another. The tail of the fast group is
+//! throttled, so the UI stays responsive, while all of its lines are still stored.
+
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+// =================
+// === Constants ===
+// =================
+
+const RUN_TIME: Duration = Duration::from_secs(5);
+const SLOW_LINE_DELAY: Duration = Duration::from_millis(250);
+
+// ============
+// === Main ===
+// ============
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let tui_handle = lmux::Builder::new().spawn()?;
+    lmux::set_header("fast", "FAST");
+    lmux::set_header("slow", "SLOW");
+
+    let fast = thread::spawn(|| {
+        let start = Instant::now();
+        let mut line = 0;
+        while start.elapsed() < RUN_TIME {
+            line += 1;
+            let progress = start.elapsed().as_secs_f32() / RUN_TIME.as_secs_f32();
+            lmux::log("fast", Some(lmux::Status::ok().progress(progress)), format!("Line {line}"));
+        }
+        lmux::log("fast", Some(lmux::Status::ok().finished()), format!("Done, {line} lines"));
+    });
+    let slow = thread::spawn(|| {
+        let start = Instant::now();
+        let mut line = 0;
+        while start.elapsed() < RUN_TIME {
+            line += 1;
+            lmux::log("slow", Some(lmux::Status::ok()), format!("Line {line}"));
+            thread::sleep(SLOW_LINE_DELAY);
+        }
+        lmux::log("slow", Some(lmux::Status::ok().finished()), format!("Done, {line} lines"));
+    });
+
+    fast.join().map_err(|_| "The fast task panicked.")?;
+    slow.join().map_err(|_| "The slow task panicked.")?;
+    lmux::debug("All tasks done.");
+    let out = tui_handle.join();
+    println!("Result: {out:?}");
+    Ok(())
+}
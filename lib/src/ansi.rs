@@ -0,0 +1,167 @@
+//! Parsing of the ANSI styling of composed lines back into styled spans, e.g. to export a frame
+//! in another format, see [`crate::html`].
+
+use crate::prelude::*;
+
+// =============
+// === Color ===
+// =============
+
+/// Color of an SGR sequence. Indexed colors use the 256-color palette, the first 16 of which are
+/// the standard and bright colors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// RGB values of the 16 standard and bright colors, as in xterm.
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+impl Color {
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Self::Rgb(r, g, b) => (r, g, b),
+            Self::Indexed(index @ 0..=15) => BASIC_COLORS[index as usize],
+            Self::Indexed(index @ 16..=231) => {
+                let level = |t: u8| if t == 0 { 0 } else { 55 + t * 40 };
+                let index = index - 16;
+                (level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            Self::Indexed(index) => {
+                let gray = 8 + (index - 232) * 10;
+                (gray, gray, gray)
+            }
+        }
+    }
+}
+
+// =============
+// === Style ===
+// =============
+
+/// Text attributes set by SGR sequences.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    /// Applies the parameters of an SGR sequence, e.g. `1;38;5;9` of `\x1b[1;38;5;9m`. Unknown
+    /// parameters are skipped.
+    pub fn apply(&mut self, params: &str) {
+        let mut params = params.split(';').map(|t| t.parse::<u8>().unwrap_or_default());
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 => self.fg = Some(Color::Indexed(param - 30)),
+                38 => self.fg = extended_color(&mut params),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(Color::Indexed(param - 40)),
+                48 => self.bg = extended_color(&mut params),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(Color::Indexed(param - 90 + 8)),
+                100..=107 => self.bg = Some(Color::Indexed(param - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Color given by the parameters following `38` or `48`: `5;index` or `2;r;g;b`.
+fn extended_color(params: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match params.next()? {
+        5 => Some(Color::Indexed(params.next()?)),
+        2 => Some(Color::Rgb(params.next()?, params.next()?, params.next()?)),
+        _ => None,
+    }
+}
+
+// =============
+// === Spans ===
+// =============
+
+/// Text drawn with one style.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Splits the text into runs of equally styled characters. Escape sequences other than SGR are
+/// dropped, and empty runs are skipped.
+pub fn spans(text: &str) -> Vec<Span> {
+    let mut spans = Vec::<Span>::new();
+    let mut style = Style::default();
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char == '\x1b' {
+            if chars.next_if_eq(&'[').is_some() {
+                let mut sequence = String::new();
+                for char in chars.by_ref() {
+                    if ('@'..='~').contains(&char) {
+                        if char == 'm' {
+                            style.apply(&sequence);
+                        }
+                        break;
+                    }
+                    sequence.push(char);
+                }
+            }
+        } else {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.text.push(char),
+                _ => spans.push(Span { text: char.to_string(), style }),
+            }
+        }
+    }
+    spans
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_follow_the_sgr_sequences() {
+        let text = "a\x1b[1;31mb\x1b[2Kc\x1b[22;38;2;1;2;3md\x1b[0m\x1b[7m\x1b[me";
+        let red = Style { fg: Some(Color::Indexed(1)), bold: true, ..default() };
+        let rgb = Style { fg: Some(Color::Rgb(1, 2, 3)), ..default() };
+        let spans = spans(text).into_iter().map(|s| (s.text, s.style)).collect::<Vec<_>>();
+        let expected = [("a", default()), ("bc", red), ("d", rgb), ("e", default())];
+        assert_eq!(spans, expected.map(|(text, style)| (text.to_string(), style)));
+    }
+
+    #[test]
+    fn indexed_colors_map_to_the_xterm_palette() {
+        let colors = [9, 16, 196, 231, 232, 255].map(|index| Color::Indexed(index).rgb());
+        let expected = [
+            (255, 0, 0), (0, 0, 0), (255, 0, 0), (255, 255, 255), (8, 8, 8), (238, 238, 238)
+        ];
+        assert_eq!(colors, expected);
+    }
+}
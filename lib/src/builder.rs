@@ -0,0 +1,331 @@
+use crate::prelude::*;
+
+use crate::ErrorHandler;
+use crate::Keymap;
+use crate::Level;
+use crate::Logger;
+use crate::Normalize;
+use crate::Notify;
+use crate::Order;
+use crate::QuitPolicy;
+use crate::Settings;
+use crate::SharedLogger;
+use crate::TuiHandle;
+use crate::group;
+use crate::logger;
+use crate::style;
+use std::time::Duration;
+use std::time::SystemTime;
+
+// ===============
+// === Builder ===
+// ===============
+
+/// Change of a logger made by a [`Builder`].
+type Change = Box<dyn FnOnce(&mut Logger) + Send>;
+
+/// Configuration of a logger, applied at once before the first frame is drawn. Only the options
+/// which were set are applied, so that configuring an existing logger keeps its other state. The
+/// settings of groups apply to the groups created later.
+pub struct Builder {
+    changes: Vec<Change>,
+    defer_first_draw: Option<bool>,
+    ready_timeout: Duration,
+    enabled: bool,
+    /// Warnings of reading the configuration file, shown in the debug panel.
+    config_warnings: Vec<String>,
+}
+
+impl Debug for Builder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builder")
+            .field("changes", &self.changes.len())
+            .field("defer_first_draw", &self.defer_first_draw)
+            .field("ready_timeout", &self.ready_timeout)
+            .field("enabled", &self.enabled)
+            .field("config_warnings", &self.config_warnings)
+            .finish()
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        let changes = default();
+        let defer_first_draw = None;
+        let ready_timeout = crate::DEFAULT_READY_TIMEOUT;
+        let enabled = true;
+        let config_warnings = default();
+        Self { changes, defer_first_draw, ready_timeout, enabled, config_warnings }
+    }
+}
+
+impl Builder {
+    /// Creates a builder with the defaults read from the configuration file, if the `config`
+    /// feature is enabled, see [`crate::config`].
+    pub fn new() -> Self {
+        #[cfg(feature = "config")]
+        {
+            let mut builder = Self::default().config(crate::config::loaded());
+            builder.config_warnings = crate::config::take_warnings();
+            builder
+        }
+        #[cfg(not(feature = "config"))]
+        default()
+    }
+
+    /// Overwrites the options set in the configuration. Options set earlier are overwritten, and
+    /// options set later take precedence.
+    #[cfg(feature = "config")]
+    pub fn config(self, config: &crate::config::Config) -> Self {
+        let config = config.clone();
+        self.with_settings(move |settings| config.apply(settings))
+    }
+
+    pub fn theme(self, theme: style::Theme) -> Self {
+        self.with_settings(move |settings| settings.theme = theme)
+    }
+
+    pub fn style(self, style: impl style::Style + 'static) -> Self {
+        self.with(move |logger| logger.style = style::Any::new(style))
+    }
+
+    pub fn stale_after(self, stale_after: impl Into<Option<Duration>>) -> Self {
+        let stale_after = stale_after.into();
+        self.with_settings(move |settings| settings.stale_after = stale_after)
+    }
+
+    /// Sets the stall timeout and action of new groups, see [`group::State::stall_timeout`].
+    pub fn stall_timeout
+    (self, timeout: impl Into<Option<Duration>>, action: group::StallAction) -> Self {
+        let timeout = timeout.into();
+        self.with_settings(move |settings| {
+            settings.stall_timeout = timeout;
+            settings.stall_action = action;
+        })
+    }
+
+    /// Sets the number of frames between full repaints of the screen, see
+    /// [`Settings::full_repaint_frames`].
+    pub fn full_repaint_frames(self, frames: impl Into<Option<u32>>) -> Self {
+        let frames = frames.into();
+        self.with_settings(move |settings| settings.full_repaint_frames = frames)
+    }
+
+    /// Repaints the screen if the cursor was moved by output printed around the UI, see
+    /// [`Settings::check_cursor`].
+    pub fn check_cursor(self, check: bool) -> Self {
+        self.with_settings(move |settings| settings.check_cursor = check)
+    }
+
+    pub fn strict_groups(self, strict: bool) -> Self {
+        self.with_settings(move |settings| settings.strict_groups = strict)
+    }
+
+    pub fn show_empty_groups(self, show: bool) -> Self {
+        self.with_settings(move |settings| settings.show_empty_groups = show)
+    }
+
+    /// Draws new groups without footers, see [`group::State::compact`].
+    pub fn compact(self, compact: bool) -> Self {
+        self.with_settings(move |settings| settings.compact = compact)
+    }
+
+    pub fn debug_rows(self, rows: usize) -> Self {
+        self.with_settings(move |settings| settings.debug_rows = rows)
+    }
+
+    pub fn min_level(self, min_level: Level) -> Self {
+        self.with_settings(move |settings| settings.min_level = min_level)
+    }
+
+    pub fn frame_rate(self, fps: u32) -> Self {
+        self.with_settings(move |settings| settings.frame_rate = fps)
+    }
+
+    pub fn adaptive_frame_rate(self, adaptive: bool) -> Self {
+        self.with_settings(move |settings| settings.adaptive_frame_rate = adaptive)
+    }
+
+    pub fn notifications(self, notifications: Notify) -> Self {
+        self.with_settings(move |settings| settings.notifications = notifications)
+    }
+
+    pub fn show_summary(self, show: bool) -> Self {
+        self.with_settings(move |settings| settings.show_summary = show)
+    }
+
+    pub fn show_history_times(self, show: bool) -> Self {
+        self.with_settings(move |settings| settings.show_history_times = show)
+    }
+
+    pub fn show_collapsed_errors(self, show: bool) -> Self {
+        self.with_settings(move |settings| settings.show_collapsed_errors = show)
+    }
+
+    pub fn resize_debounce(self, debounce: Duration) -> Self {
+        self.with_settings(move |settings| settings.resize_debounce = debounce)
+    }
+
+    pub fn start_timers(self, start: bool) -> Self {
+        self.with_settings(move |settings| settings.start_timers = start)
+    }
+
+    pub fn mouse_capture(self, capture: bool) -> Self {
+        self.with_settings(move |settings| settings.mouse_capture = capture)
+    }
+
+    pub fn fast_group_refresh_rate(self, rate: Option<u32>) -> Self {
+        self.with_settings(move |settings| settings.fast_group_refresh_rate = rate)
+    }
+
+    pub fn auto_collapse_default(self, auto_collapse: group::AutoCollapse) -> Self {
+        self.with_settings(move |settings| settings.auto_collapse = auto_collapse)
+    }
+
+    /// Remaps the keys pressed in the UI, see [`Keymap`].
+    pub fn keymap(self, keymap: Keymap) -> Self {
+        self.with_settings(move |settings| settings.keymap = keymap)
+    }
+
+    pub fn quit_policy(self, policy: QuitPolicy) -> Self {
+        self.with_settings(move |settings| settings.quit_policy = policy)
+    }
+
+    /// Limits the number of history entries, see [`Settings::history_limit`].
+    pub fn history_limit(self, limit: impl Into<Option<usize>>) -> Self {
+        let limit = limit.into();
+        self.with_settings(move |settings| settings.history_limit = limit)
+    }
+
+    /// Draws the UI on the main screen instead of the alternate one, see
+    /// [`Settings::inline_mode`].
+    pub fn inline_mode(self, inline: bool) -> Self {
+        self.with_settings(move |settings| settings.inline_mode = inline)
+    }
+
+    pub fn order(self, order: Order) -> Self {
+        self.with(move |logger| logger.groups.order = order)
+    }
+
+    pub fn hide_finished_after(self, duration: impl Into<Option<Duration>>) -> Self {
+        let duration = duration.into();
+        self.with(move |logger| logger.groups.hide_finished_after = duration)
+    }
+
+    pub fn error_handler(self, f: impl Fn(Error) + Send + Sync + 'static) -> Self {
+        let handler = ErrorHandler::new(f);
+        self.with(move |logger| logger.error_handler = Some(handler))
+    }
+
+    /// Does not draw the first frame until [`crate::ready`] is called or the
+    /// [`Self::ready_timeout`] elapses, so that the groups do not flash with their selector paths
+    /// as headers before the application sets them. Input is handled in the meantime.
+    pub fn defer_first_draw_until_ready(mut self, defer: bool) -> Self {
+        self.defer_first_draw = Some(defer);
+        self
+    }
+
+    /// Time after which the deferred first frame is drawn even if [`crate::ready`] was not
+    /// called. Defaults to [`crate::DEFAULT_READY_TIMEOUT`].
+    pub fn ready_timeout(mut self, timeout: Duration) -> Self {
+        self.ready_timeout = timeout;
+        self
+    }
+
+    /// Sets the normalization of the selector paths, see [`Logger::set_selector_normalizer`].
+    pub fn selector_normalizer(self, normalize: Normalize) -> Self {
+        self.with(move |logger| logger.set_selector_normalizer(normalize))
+    }
+
+    /// If disabled, [`Self::run`] and [`Self::spawn`] do not capture the terminal, see
+    /// [`crate::main`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    fn with(mut self, change: impl FnOnce(&mut Logger) + Send + 'static) -> Self {
+        self.changes.push(Box::new(change));
+        self
+    }
+
+    fn with_settings(self, change: impl FnOnce(&mut Settings) + Send + 'static) -> Self {
+        self.with(move |logger| change(&mut logger.settings))
+    }
+
+    fn apply(self, logger: &mut Logger) {
+        for change in self.changes {
+            change(logger);
+        }
+        if let Some(defer) = self.defer_first_draw {
+            logger.ready_deadline = defer.then(|| SystemTime::now() + self.ready_timeout);
+        }
+        for warning in self.config_warnings {
+            logger.push_debug_line(warning);
+        }
+    }
+
+    /// Applies the configuration to an existing logger instance.
+    pub fn configure(self, logger: &SharedLogger) -> Result {
+        logger.modify(|logger| self.apply(logger))
+    }
+
+    /// Creates a new, independent logger instance.
+    pub fn build(self) -> SharedLogger {
+        let mut logger = Logger::default();
+        self.apply(&mut logger);
+        SharedLogger::new(logger)
+    }
+
+    /// Configures the global logger and runs the UI, see [`crate::main`].
+    pub fn run(self) -> Result {
+        let enabled = self.enabled;
+        self.configure(logger())?;
+        logger().main(enabled)
+    }
+
+    /// Configures the global logger and runs the UI on a dedicated thread, see [`crate::spawn`].
+    pub fn spawn(self) -> Result<TuiHandle> {
+        let enabled = self.enabled;
+        self.configure(logger())?;
+        logger().spawn(enabled)
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configure_keeps_existing_state() -> Result {
+        let logger = SharedLogger::default();
+        logger.set_error_handler(|_| {})?;
+        logger.set_compact(true)?;
+        let group_id = logger.create_group(&["build"][..])?;
+        Builder::default().min_level(Level::Error).history_limit(8).configure(&logger)?;
+        logger.modify(|logger| {
+            assert!(logger.error_handler.is_some());
+            assert!(logger.settings.compact);
+            assert!(logger.groups[*group_id].compact);
+            assert_eq!(logger.groups[*group_id].min_level, Level::Trace);
+            assert_eq!(logger.settings.min_level, Level::Error);
+            assert_eq!(logger.settings.history_limit, Some(8));
+        })
+    }
+
+    #[test]
+    fn history_is_limited() -> Result {
+        let logger = Builder::default().history_limit(8).build();
+        for i in 0..20 {
+            logger.log(&["build"][..], None, format!("{i}"));
+        }
+        logger.modify(|logger| {
+            assert!(logger.history.len() <= 8);
+            assert_eq!(logger.history.end(), 20);
+        })
+    }
+}
@@ -0,0 +1,123 @@
+//! Recording of the drawn UI to files in the asciinema v2 format, see
+//! [`crate::SharedLogger::record`]. The file starts with a header line, followed by one line per
+//! event: the output written to the terminal, and the terminal resizes.
+
+use crate::prelude::*;
+
+use crate::terminal;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use std::time::SystemTime;
+
+// ==============
+// === Header ===
+// ==============
+
+/// Version of the asciinema file format.
+const VERSION: u32 = 2;
+
+#[derive(Debug, serde::Serialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+    /// Start of the recording, in seconds since the Unix epoch.
+    timestamp: u64,
+}
+
+// ================
+// === Recorder ===
+// ================
+
+/// Open cast file. The events are buffered, and written when the recorder is dropped or
+/// [`Self::finish`]ed.
+#[derive(Debug)]
+pub struct Recorder {
+    file: BufWriter<File>,
+    started_at: Instant,
+    size: terminal::Size,
+}
+
+impl Recorder {
+    /// Creates the file and writes the header with the terminal size.
+    pub fn create(path: &Path, size: terminal::Size) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create '{}'", path.display()))?;
+        let mut file = BufWriter::new(file);
+        let since_epoch = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        let timestamp = since_epoch.unwrap_or_default().as_secs();
+        let header = Header { version: VERSION, width: size.cols, height: size.rows, timestamp };
+        serde_json::to_writer(&mut file, &header)?;
+        writeln!(file)?;
+        let started_at = Instant::now();
+        Ok(Self { file, started_at, size })
+    }
+
+    /// Records the output of a frame. A resize event is recorded first if the terminal size
+    /// changed since the previous frame.
+    pub fn frame(&mut self, size: terminal::Size, output: &[u8]) -> Result {
+        if size != self.size {
+            self.size = size;
+            self.event("r", &format!("{}x{}", size.cols, size.rows))?;
+        }
+        if !output.is_empty() {
+            self.event("o", &String::from_utf8_lossy(output))?;
+        }
+        Ok(())
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> Result {
+        let time = self.started_at.elapsed().as_secs_f64();
+        serde_json::to_writer(&mut self.file, &(time, code, data))?;
+        writeln!(self.file)?;
+        Ok(())
+    }
+
+    /// Writes the buffered events and closes the file.
+    pub fn finish(mut self) -> Result {
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cast_has_a_header_and_events() -> Result {
+        let path = std::env::temp_dir().join(format!("lmux-cast-{}.cast", std::process::id()));
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let mut recorder = Recorder::create(&path, size)?;
+        recorder.frame(size, b"\x1b[1;1Hfirst")?;
+        recorder.frame(size, b"")?;
+        recorder.frame(terminal::Size { cols: 100, rows: 30 }, "second ✓".as_bytes())?;
+        recorder.finish()?;
+        let text = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let lines = text.lines()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+        let [header, events @ ..] = &lines[..] else { return Err(anyhow!("Empty cast")) };
+        assert_eq!(header["version"], 2);
+        assert_eq!((header["width"].as_u64(), header["height"].as_u64()), (Some(80), Some(24)));
+        let events = events.iter()
+            .map(|event| (event[1].as_str(), event[2].as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(events, [
+            (Some("o"), Some("\x1b[1;1Hfirst")),
+            (Some("r"), Some("100x30")),
+            (Some("o"), Some("second ✓")),
+        ]);
+        let times = lines[1..].iter().filter_map(|event| event[0].as_f64()).collect::<Vec<_>>();
+        assert!(times.len() == 3 && times.is_sorted());
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::prelude::*;
+
+use crate::group::Group;
+use crate::LineRange;
+use crate::format;
+use crate::group;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+// ============
+// === Text ===
+// ============
+
+/// Plain text of the lines of the group in the current view, without escape sequences. Runs are
+/// separated by their numbers.
+pub fn group_text(group: &LineRange<&Group>) -> String {
+    group.state().view_lines().iter().map(|line| match line.kind {
+        group::LineKind::RunStart(run) => format!("── run {run} ──\n"),
+        group::LineKind::Log => format!("{}\n", format::strip_ansi(&line.log.content)),
+    }).collect()
+}
+
+/// Writes the text to `lmux-<name>-<timestamp>.log` in the current directory, returning its path.
+pub fn write_file(name: &str, text: &str) -> Result<PathBuf> {
+    let name = name.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' });
+    let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let path = PathBuf::from(format!("lmux-{}-{timestamp}.log", name.collect::<String>()));
+    std::fs::write(&path, text).with_context(|| format!("Cannot write {}", path.display()))?;
+    Ok(path)
+}
+
+// ==============
+// === Base64 ===
+// ==============
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard Base64 encoding with padding.
+pub fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// ==============
+// === OSC 52 ===
+// ==============
+
+/// Maximum length of the encoded OSC 52 payload. Terminals ignore longer sequences, e.g. xterm
+/// and hterm accept about 100 kB.
+pub const MAX_OSC52_LEN: usize = 100_000;
+
+/// Length of the chunks of the sequence passed through GNU Screen, which limits the length of
+/// its control strings.
+const SCREEN_CHUNK_LEN: usize = 76;
+
+/// Escape sequence setting the clipboard of the terminal to the text. Inside tmux and GNU Screen,
+/// the sequence is wrapped so that it reaches the outer terminal, and for Screen it is split into
+/// chunks. Fails if the text is too long for terminals to accept.
+pub fn osc52(text: &str) -> Result<String> {
+    let payload = base64(text.as_bytes());
+    if payload.len() > MAX_OSC52_LEN {
+        return Err(anyhow!("Text is too long to copy ({} bytes encoded, at most {MAX_OSC52_LEN})",
+            payload.len()));
+    }
+    let sequence = format!("\x1b]52;c;{payload}\x07");
+    let tmux = std::env::var_os("TMUX").is_some();
+    let screen = std::env::var("TERM").unwrap_or_default().starts_with("screen");
+    Ok(pass_through(sequence, tmux, screen))
+}
+
+/// Wraps the sequence so that it passes through tmux or GNU Screen to the outer terminal.
+fn pass_through(sequence: String, tmux: bool, screen: bool) -> String {
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else if screen {
+        // The sequence is ASCII, so it can be split at any byte.
+        let chunks = sequence.as_bytes().chunks(SCREEN_CHUNK_LEN)
+            .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)));
+        chunks.collect()
+    } else {
+        sequence
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_the_reference_vectors() {
+        let vectors = [
+            ("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy"),
+        ];
+        for (text, encoded) in vectors {
+            assert_eq!(base64(text.as_bytes()), encoded);
+        }
+        assert_eq!(base64(&[0xff, 0xfe, 0x3f]), "//4/");
+    }
+
+    #[test]
+    fn osc52_sets_the_clipboard_through_multiplexers() {
+        let sequence = || "\x1b]52;c;aGk=\x07".to_owned();
+        assert_eq!(pass_through(sequence(), false, false), sequence());
+        let tmux = pass_through(sequence(), true, false);
+        assert_eq!(tmux, "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+        let long = format!("\x1b]52;c;{}\x07", "A".repeat(100));
+        let screen = pass_through(long.clone(), false, true);
+        assert_eq!(screen.matches("\x1bP").count(), long.len().div_ceil(SCREEN_CHUNK_LEN));
+        assert_eq!(screen.replace("\x1bP", "").replace("\x1b\\", ""), long);
+    }
+
+    #[test]
+    fn osc52_rejects_long_text() {
+        let max_text_len = MAX_OSC52_LEN / 4 * 3;
+        assert!(osc52(&"a".repeat(max_text_len)).is_ok());
+        assert!(osc52(&"a".repeat(max_text_len + 1)).is_err());
+    }
+
+    #[test]
+    fn group_text_strips_styles_and_separates_runs() {
+        let mut group = Group::new(group::Id(0));
+        let failed = "\x1b[31mfailed\x1b[0m";
+        let lines = [(group::LineKind::Log, failed), (group::LineKind::RunStart(2), "")];
+        for (id, (kind, content)) in lines.into_iter().enumerate() {
+            let log = group::Log { content: content.into(), status: default(), level: default() };
+            let (timestamp, time) = (crate::LineId(id), SystemTime::UNIX_EPOCH);
+            group.lines.push(group::Line { log, timestamp, time, kind, repeat: 1 });
+        }
+        let text = group_text(&LineRange { data: &group, next_line: None });
+        assert_eq!(text, "failed\n── run 2 ──\n");
+    }
+}
@@ -0,0 +1,297 @@
+//! Defaults of the logger read from a TOML file, see [`Config::path`]. Options set
+//! programmatically, e.g. with the [`crate::Builder`], take precedence over the file.
+
+use crate::prelude::*;
+
+use crate::Notify;
+use crate::Settings;
+use crate::format;
+use crate::group;
+use crate::widget;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// ====================
+// === AutoCollapse ===
+// ====================
+
+/// Named [`group::AutoCollapse`] policies.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoCollapse {
+    CollapseOnSuccess,
+    #[default]
+    ExpandOnError,
+    ExpandSelected,
+}
+
+impl AutoCollapse {
+    pub fn policy(self) -> group::AutoCollapse {
+        match self {
+            Self::CollapseOnSuccess => group::AutoCollapse::collapse_on_success(),
+            Self::ExpandOnError => group::AutoCollapse::expand_on_error(),
+            Self::ExpandSelected => group::AutoCollapse::expand_selected(),
+        }
+    }
+}
+
+// ===================
+// === ThemeConfig ===
+// ===================
+
+/// Options of the [`crate::style::Theme`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub show_eta: Option<bool>,
+    pub spinner: Option<widget::Spinner>,
+    pub progress_label: Option<bool>,
+    pub sparkline_len: Option<usize>,
+    pub unicode: Option<bool>,
+    pub dim_unfocused: Option<bool>,
+    pub duration_precision: Option<format::DurationPrecision>,
+    pub footer_refresh_ms: Option<u64>,
+    /// Zero hides the counts.
+    pub stats_window_secs: Option<u64>,
+}
+
+// ==============
+// === Config ===
+// ==============
+
+/// Contents of the configuration file. Options which are not set keep their default values.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub frame_rate: Option<u32>,
+    pub adaptive_frame_rate: Option<bool>,
+    pub auto_collapse: Option<AutoCollapse>,
+    pub stale_after_secs: Option<u64>,
+    pub debug_rows: Option<usize>,
+    pub show_summary: Option<bool>,
+    pub show_collapsed_errors: Option<bool>,
+    pub show_history_times: Option<bool>,
+    pub compact: Option<bool>,
+    pub resize_debounce_ms: Option<u64>,
+    pub notifications: Option<Notify>,
+    pub mouse_capture: Option<bool>,
+    /// Zero disables the periodic full repaints.
+    pub full_repaint_frames: Option<u32>,
+    pub check_cursor: Option<bool>,
+    /// Zero disables the throttling of groups with fast output.
+    pub fast_group_refresh_rate: Option<u32>,
+    pub theme: ThemeConfig,
+}
+
+/// Environment variable overriding the path of the configuration file.
+pub const PATH_VAR: &str = "LMUX_CONFIG";
+
+impl Config {
+    /// Path of the configuration file: [`PATH_VAR`] if set, or `lmux/config.toml` in
+    /// `$XDG_CONFIG_HOME` or `~/.config`.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(PATH_VAR) {
+            return Some(path.into());
+        }
+        let config_home = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("lmux").join("config.toml"))
+    }
+
+    /// Parses the configuration. Unknown keys are skipped and reported in the returned warnings.
+    pub fn parse(text: &str) -> Result<(Self, Vec<String>)> {
+        let table: toml::Table = toml::from_str(text)?;
+        let known = toml::Table::try_from(Self::defaults())?;
+        let mut warnings = Vec::new();
+        unknown_keys(&table, &known, "", &mut warnings);
+        Ok((table.try_into()?, warnings))
+    }
+
+    /// Reads the configuration file, see [`Self::path`]. A missing file is not an error. Errors
+    /// and unknown keys are returned as warnings, and the invalid file is ignored.
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = Self::path() else { return default() };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return default(),
+            Err(e) => return (default(), vec![format!("Config {}: {e}", path.display())]),
+        };
+        match Self::parse(&text) {
+            Ok((config, warnings)) => {
+                let warnings = warnings.into_iter()
+                    .map(|warning| format!("Config {}: {warning}", path.display()))
+                    .collect();
+                (config, warnings)
+            }
+            Err(e) => {
+                let message = e.to_string().lines().map(str::trim).collect::<Vec<_>>().join(" ");
+                (default(), vec![format!("Config {}: {message}", path.display())])
+            }
+        }
+    }
+
+    /// Configuration with every option set to its default value.
+    pub fn defaults() -> Self {
+        let settings = Settings::default();
+        let theme = settings.theme;
+        Self {
+            frame_rate: Some(settings.frame_rate),
+            adaptive_frame_rate: Some(settings.adaptive_frame_rate),
+            auto_collapse: Some(default()),
+            stale_after_secs: settings.stale_after.map(|t| t.as_secs()),
+            debug_rows: Some(settings.debug_rows),
+            show_summary: Some(settings.show_summary),
+            show_collapsed_errors: Some(settings.show_collapsed_errors),
+            show_history_times: Some(settings.show_history_times),
+            compact: Some(settings.compact),
+            resize_debounce_ms: Some(settings.resize_debounce.as_millis() as u64),
+            notifications: Some(settings.notifications),
+            mouse_capture: Some(settings.mouse_capture),
+            full_repaint_frames: Some(settings.full_repaint_frames.unwrap_or_default()),
+            check_cursor: Some(settings.check_cursor),
+            fast_group_refresh_rate: Some(settings.fast_group_refresh_rate.unwrap_or_default()),
+            theme: ThemeConfig {
+                show_eta: Some(theme.show_eta),
+                spinner: Some(theme.spinner),
+                progress_label: Some(theme.progress_label),
+                sparkline_len: Some(theme.sparkline_len),
+                unicode: Some(theme.unicode),
+                dim_unfocused: Some(theme.dim_unfocused),
+                duration_precision: Some(theme.duration_precision),
+                footer_refresh_ms: Some(theme.footer_refresh.as_millis() as u64),
+                stats_window_secs: Some(theme.stats_window.unwrap_or_default().as_secs()),
+            },
+        }
+    }
+
+    /// Writes a configuration file listing every option with its default value.
+    pub fn write_default(path: impl AsRef<Path>) -> Result {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(&Self::defaults())?)?;
+        Ok(())
+    }
+
+    /// Overwrites the settings with the options set in the configuration.
+    pub fn apply(&self, settings: &mut Settings) {
+        let theme = &mut settings.theme;
+        if let Some(t) = self.frame_rate { settings.frame_rate = t }
+        if let Some(t) = self.adaptive_frame_rate { settings.adaptive_frame_rate = t }
+        if let Some(t) = self.auto_collapse { settings.auto_collapse = t.policy() }
+        if let Some(t) = self.stale_after_secs {
+            settings.stale_after = Some(Duration::from_secs(t))
+        }
+        if let Some(t) = self.debug_rows { settings.debug_rows = t }
+        if let Some(t) = self.show_summary { settings.show_summary = t }
+        if let Some(t) = self.show_collapsed_errors { settings.show_collapsed_errors = t }
+        if let Some(t) = self.show_history_times { settings.show_history_times = t }
+        if let Some(t) = self.compact { settings.compact = t }
+        if let Some(t) = self.resize_debounce_ms {
+            settings.resize_debounce = Duration::from_millis(t)
+        }
+        if let Some(t) = self.notifications { settings.notifications = t }
+        if let Some(t) = self.mouse_capture { settings.mouse_capture = t }
+        if let Some(t) = self.full_repaint_frames {
+            settings.full_repaint_frames = (t > 0).then_some(t)
+        }
+        if let Some(t) = self.check_cursor { settings.check_cursor = t }
+        if let Some(t) = self.fast_group_refresh_rate {
+            settings.fast_group_refresh_rate = (t > 0).then_some(t)
+        }
+        if let Some(t) = self.theme.show_eta { theme.show_eta = t }
+        if let Some(t) = self.theme.spinner { theme.spinner = t }
+        if let Some(t) = self.theme.progress_label { theme.progress_label = t }
+        if let Some(t) = self.theme.sparkline_len { theme.sparkline_len = t }
+        if let Some(t) = self.theme.unicode { theme.unicode = t }
+        if let Some(t) = self.theme.dim_unfocused { theme.dim_unfocused = t }
+        if let Some(t) = self.theme.duration_precision { theme.duration_precision = t }
+        if let Some(t) = self.theme.footer_refresh_ms {
+            theme.footer_refresh = Duration::from_millis(t)
+        }
+        if let Some(t) = self.theme.stats_window_secs {
+            theme.stats_window = (t > 0).then(|| Duration::from_secs(t))
+        }
+    }
+}
+
+/// Reports the keys of the table which are not in the known one, recursing into nested tables.
+fn unknown_keys(table: &toml::Table, known: &toml::Table, prefix: &str, out: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = format!("{prefix}{key}");
+        match (value, known.get(key)) {
+            (_, None) => out.push(format!("Unknown key '{path}'")),
+            (toml::Value::Table(table), Some(toml::Value::Table(known))) =>
+                unknown_keys(table, known, &format!("{path}."), out),
+            _ => {}
+        }
+    }
+}
+
+// ==============
+// === Loaded ===
+// ==============
+
+static LOADED: OnceLock<Config> = OnceLock::new();
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Configuration file read once per process.
+pub fn loaded() -> &'static Config {
+    LOADED.get_or_init(|| {
+        let (config, warnings) = Config::load();
+        *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()) = warnings;
+        config
+    })
+}
+
+/// Warnings of reading the configuration file, returned once, so that they are reported by the
+/// first configured logger only.
+pub fn take_warnings() -> Vec<String> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_keys_are_reported_and_known_ones_applied() -> Result {
+        let text = "frame_rate = 30\nfull_repaint_frames = 0\ncolour = 1\n\
+            [theme]\nunicode = false\nsize = 2\n";
+        let (config, warnings) = Config::parse(text)?;
+        assert_eq!(warnings, ["Unknown key 'colour'", "Unknown key 'theme.size'"]);
+        let mut settings = Settings::default();
+        config.apply(&mut settings);
+        assert_eq!(settings.frame_rate, 30);
+        assert_eq!(settings.full_repaint_frames, None);
+        assert!(!settings.theme.unicode);
+        assert_eq!(settings.debug_rows, Settings::default().debug_rows);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        assert!(Config::parse("frame_rate = \"fast\"").is_err());
+        assert!(Config::parse("auto_collapse = \"never\"").is_err());
+        assert!(Config::parse("frame_rate = ").is_err());
+    }
+
+    #[test]
+    fn defaults_round_trip() -> Result {
+        let text = toml::to_string_pretty(&Config::defaults())?;
+        let (config, warnings) = Config::parse(&text)?;
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config, Config::defaults());
+        let (_, warnings) = Config::parse(include_str!("../examples/config.toml"))?;
+        assert!(warnings.is_empty(), "{warnings:?}");
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use crate::prelude::*;
+
+use crate::LineRange;
+use crate::framebuffer::LineIndex;
+use crate::group;
+use crate::group::Group;
+use crate::terminal;
+use crossterm::style::Stylize;
+use std::collections::HashMap;
+use std::ops::Range;
+
+// ==============
+// === Action ===
+// ==============
+
+/// Entry of the group context menu.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    ToggleCollapse,
+    TogglePin,
+    ToggleZoom,
+    /// Copies the lines of the group to the clipboard, see [`crate::Logger::copy_group`].
+    CopyLines,
+    Clear,
+    /// Calls the kill handler of the group, see [`group::State::kill_handler`].
+    Kill,
+    Close,
+}
+
+impl Action {
+    pub const ALL: &[Self] = &[
+        Self::ToggleCollapse, Self::TogglePin, Self::ToggleZoom, Self::CopyLines, Self::Clear,
+        Self::Kill, Self::Close,
+    ];
+
+    pub fn label(self, group: &LineRange<&Group>, zoomed: bool) -> &'static str {
+        match self {
+            Self::ToggleCollapse => if group.is_collapsed() { "Expand" } else { "Collapse" },
+            Self::TogglePin => if group.pinned { "Unpin" } else { "Pin" },
+            Self::ToggleZoom => if zoomed { "Unzoom" } else { "Zoom" },
+            Self::CopyLines => "Copy all lines",
+            Self::Clear => "Clear",
+            Self::Kill => "Kill",
+            Self::Close => "Close menu",
+        }
+    }
+}
+
+// ===================
+// === ContextMenu ===
+// ===================
+
+/// Width of the menu entries.
+const WIDTH: usize = 16;
+
+/// Menu of group actions opened with a right click, drawn over the rows below the click.
+#[derive(Clone, Copy, Debug)]
+pub struct ContextMenu {
+    pub group: group::Id,
+    /// Row and column of the click which opened the menu.
+    pub row: LineIndex,
+    pub column: usize,
+    /// Index of the highlighted action.
+    pub selected: usize,
+    /// Offer [`Action::Kill`], as the group has a kill handler.
+    pub killable: bool,
+}
+
+impl ContextMenu {
+    pub fn new(group: &Group, row: LineIndex, column: usize) -> Self {
+        let killable = group.kill_handler.is_some();
+        Self { group: group.id, row, column, selected: 0, killable }
+    }
+
+    /// Actions offered for the group.
+    pub fn actions(&self) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL.iter().copied().filter(|action| *action != Action::Kill || self.killable)
+    }
+
+    /// Rows taken by the menu. The menu opens below the click, or above it if it does not fit
+    /// the screen.
+    pub fn rows(&self, screen_rows: usize) -> Range<usize> {
+        let len = self.actions().count();
+        let start = if *self.row + 1 + len <= screen_rows { *self.row + 1 } else {
+            self.row.saturating_sub(len)
+        };
+        start..start + len
+    }
+
+    fn columns(&self, screen_cols: usize) -> Range<usize> {
+        let start = self.column.min(screen_cols.saturating_sub(WIDTH));
+        start..start + WIDTH
+    }
+
+    /// Action under the given screen position, if any.
+    pub fn action_at(&self, row: LineIndex, column: usize, size: terminal::Size) -> Option<Action> {
+        let rows = self.rows(size.rows);
+        let on_menu = rows.contains(&*row) && self.columns(size.cols).contains(&column);
+        on_menu.then(|| self.actions().nth(*row - rows.start)).flatten()
+    }
+
+    pub fn shift_selection(&mut self, shift: isize) {
+        let len = self.actions().count() as isize;
+        self.selected = (self.selected as isize + shift).rem_euclid(len) as usize;
+    }
+
+    pub fn selected_action(&self) -> Action {
+        self.actions().nth(self.selected).unwrap_or(Action::Close)
+    }
+
+    /// Entries of the menu by their screen rows, with the column they start at.
+    pub fn overlay(&self, group: &LineRange<&Group>, zoomed: bool, size: terminal::Size)
+    -> HashMap<LineIndex, (usize, String)> {
+        let column = self.columns(size.cols).start;
+        self.rows(size.rows).zip(self.actions()).enumerate().map(|(index, (row, action))| {
+            let label = format!(" {:WIDTH$}", action.label(group, zoomed), WIDTH = WIDTH - 1);
+            let entry = if index == self.selected {
+                label.black().on_green().to_string()
+            } else {
+                label.white().on_dark_grey().to_string()
+            };
+            (LineIndex(row), (column, entry))
+        }).collect()
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(menu: &ContextMenu, group: &Group) -> Vec<&'static str> {
+        let view = LineRange { data: group, next_line: None };
+        menu.actions().map(|action| action.label(&view, false)).collect()
+    }
+
+    #[test]
+    fn kill_is_offered_only_with_a_kill_handler() {
+        let mut group = Group::new(group::Id(0));
+        let menu = ContextMenu::new(&group, LineIndex(0), 0);
+        assert_eq!(labels(&menu, &group), [
+            "Expand", "Pin", "Zoom", "Copy all lines", "Clear", "Close menu",
+        ]);
+        assert_eq!(menu.rows(20), 1..7);
+        group.pinned = true;
+        group.kill_handler = Some(group::KillHandler::new(|_| {}));
+        let menu = ContextMenu::new(&group, LineIndex(19), 0);
+        assert_eq!(labels(&menu, &group), [
+            "Expand", "Unpin", "Zoom", "Copy all lines", "Clear", "Kill", "Close menu",
+        ]);
+        assert_eq!(menu.rows(20), 12..19);
+        let size = terminal::Size { cols: 80, rows: 20 };
+        assert_eq!(menu.action_at(LineIndex(17), 3, size), Some(Action::Kill));
+        assert_eq!(menu.action_at(LineIndex(17), 40, size), None);
+    }
+}
@@ -0,0 +1,518 @@
+use std::ops::Range;
+use std::time::Duration;
+use std::time::SystemTime;
+use unicode_width::UnicodeWidthChar;
+
+// =================
+// === Precision ===
+// =================
+
+/// Smallest unit shown by [`duration`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DurationPrecision {
+    /// Hide seconds, unless the duration is shorter than a minute, e.g. `1h 2m`.
+    Coarse,
+    /// Whole seconds, e.g. `1h 2m 3s`.
+    Seconds,
+    /// Seconds with three decimal places, e.g. `1.273s`.
+    Millis,
+    /// Whole seconds while the measured work is running, and milliseconds as a separate part once
+    /// it is finished, unless the duration is at least a day long, e.g. `1s 273ms`.
+    #[default]
+    Adaptive,
+}
+
+impl DurationPrecision {
+    /// Resolves [`Self::Adaptive`] to [`Self::Seconds`] for running work.
+    pub fn for_running(self, running: bool) -> Self {
+        if running && self == Self::Adaptive { Self::Seconds } else { self }
+    }
+}
+
+// ================
+// === Duration ===
+// ================
+
+/// Formats a duration as its non-zero day, hour, and minute parts followed by seconds, e.g.
+/// `1h 2m 3s`. The smallest unit depends on the precision, [`DurationPrecision::Adaptive`] is
+/// formatted as finished work.
+pub fn duration(duration: Duration, precision: DurationPrecision) -> String {
+    let total_ms = duration.as_millis();
+    let total_seconds = total_ms / 1000;
+    let ms = total_ms % 1000;
+    let s = total_seconds % 60;
+    let m = (total_seconds / 60) % 60;
+    let h = (total_seconds / 3600) % 24;
+    let d = total_seconds / 86400;
+
+    let mut parts = Vec::new();
+    if d > 0 { parts.push(format!("{d}d")) }
+    if h > 0 { parts.push(format!("{h}h")) }
+    if m > 0 { parts.push(format!("{m}m")) }
+    match precision {
+        DurationPrecision::Coarse if total_seconds >= 60 => {}
+        DurationPrecision::Coarse | DurationPrecision::Seconds => parts.push(format!("{s}s")),
+        DurationPrecision::Millis => parts.push(format!("{s}.{ms:03}s")),
+        DurationPrecision::Adaptive => {
+            parts.push(format!("{s}s"));
+            if ms > 0 && d == 0 { parts.push(format!("{ms}ms")) }
+        }
+    }
+    parts.join(" ")
+}
+
+// ================
+// === Relative ===
+// ================
+
+/// Formats the time elapsed since an event as a short label in its largest unit, e.g. `-5m`, or
+/// `now` if it is shorter than a second.
+pub fn relative(elapsed: Duration) -> String {
+    let s = elapsed.as_secs();
+    match s {
+        0 => "now".to_string(),
+        1..60 => format!("-{s}s"),
+        60..3600 => format!("-{}m", s / 60),
+        3600..86400 => format!("-{}h", s / 3600),
+        _ => format!("-{}d", s / 86400),
+    }
+}
+
+// =============
+// === Bytes ===
+// =============
+
+/// Formats a byte count using binary units with one decimal place, e.g. `1.5 KiB`.
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    // Values which round up to 1024 are shown in the next unit.
+    while value >= 1023.95 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+// =============
+// === Count ===
+// =============
+
+/// Formats a number with thin spaces separating the groups of thousands, e.g. `1 234 567`.
+pub fn count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push('\u{2009}');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+// ===================
+// === Time of Day ===
+// ===================
+
+/// Formats the UTC time of day as `HH:MM:SS`.
+pub fn time_of_day(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() % 86400;
+    let h = seconds / 3600;
+    let m = (seconds / 60) % 60;
+    let s = seconds % 60;
+    format!("{h:02}:{m:02}:{s:02}")
+}
+
+// =====================
+// === Visible Width ===
+// =====================
+
+const ESC: char = '\x1b';
+const SGR_RESET: &str = "\x1b[0m";
+
+/// Splits the text into visible characters with their terminal column widths, and ANSI escape
+/// sequences of zero width. Zero-width characters, e.g. combining marks, are joined with the
+/// preceding character.
+fn cells(text: &str) -> Cells<'_> {
+    Cells { text }
+}
+
+#[derive(Clone, Debug)]
+struct Cells<'t> {
+    text: &'t str,
+}
+
+impl<'t> Iterator for Cells<'t> {
+    type Item = (&'t str, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.text.chars();
+        let first = chars.next()?;
+        let (len, width) = if first == ESC {
+            let len = match chars.next() {
+                Some('[') => self.text[2..].find(|c| ('@'..='~').contains(&c))
+                    .map_or(self.text.len(), |end| end + 3),
+                _ => 1,
+            };
+            (len, 0)
+        } else {
+            let width = first.width().unwrap_or_default();
+            let joined = chars.take_while(|_| width > 0).take_while(|t| t.width() == Some(0));
+            (first.len_utf8() + joined.map(char::len_utf8).sum::<usize>(), width)
+        };
+        let (cell, rest) = self.text.split_at(len);
+        self.text = rest;
+        Some((cell, width))
+    }
+}
+
+/// Number of terminal columns taken by the text, ignoring ANSI escape sequences.
+pub fn visible_width(text: &str) -> usize {
+    cells(text).map(|t| t.1).sum()
+}
+
+/// SGR sequences in effect at the end of the text, the ones after its last reset.
+pub fn active_styles(text: &str) -> String {
+    let mut styles = String::new();
+    for (cell, _) in cells(text).filter(|t| t.1 == 0 && t.0.ends_with('m')) {
+        if cell == SGR_RESET || cell == "\x1b[m" {
+            styles.clear();
+        } else {
+            styles.push_str(cell);
+        }
+    }
+    styles
+}
+
+/// The text without ANSI escape sequences.
+pub fn strip_ansi(text: &str) -> String {
+    cells(text).filter(|t| t.1 > 0).map(|t| t.0).collect()
+}
+
+/// Cuts the text to at most `width` columns. Escape sequences are kept, and the styles are reset
+/// if the text was cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut out_width = 0;
+    let mut styled = false;
+    for (cell, cell_width) in cells(text) {
+        if out_width + cell_width > width {
+            if styled {
+                out.push_str(SGR_RESET);
+            }
+            return out;
+        }
+        styled |= cell_width == 0;
+        out_width += cell_width;
+        out.push_str(cell);
+    }
+    out
+}
+
+// ===============
+// === Columns ===
+// ===============
+
+/// Spaces between aligned columns.
+const COLUMN_GAP: usize = 2;
+
+/// Cells of a tabular line: its parts separated by tabs, or if it has no tabs, by runs of at least
+/// [`COLUMN_GAP`] spaces, as printed by tools aligning their columns themselves. The indentation
+/// of the line stays in its first cell.
+pub fn columns(line: &str) -> Vec<&str> {
+    if line.contains('\t') {
+        return line.split('\t').collect();
+    }
+    let line = line.trim_end_matches(' ');
+    let gap = " ".repeat(COLUMN_GAP);
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut search = line.len() - line.trim_start_matches(' ').len();
+    while let Some(offset) = line[search..].find(&gap) {
+        let gap_start = search + offset;
+        let gap_end = line[gap_start..].find(|c| c != ' ').map_or(line.len(), |t| gap_start + t);
+        cells.push(&line[start..gap_start]);
+        (start, search) = (gap_end, gap_end);
+    }
+    cells.push(&line[start..]);
+    cells
+}
+
+/// Widths of the columns of the lines, except the last column of each line, see [`columns`].
+pub fn column_widths<'t>(lines: impl IntoIterator<Item = &'t str>) -> Vec<usize> {
+    let mut column_widths = Vec::<usize>::new();
+    for line in lines {
+        let cells = columns(line);
+        let Some((_, cells)) = cells.split_last() else { continue };
+        for (column, cell) in cells.iter().enumerate() {
+            let width = visible_width(cell);
+            match column_widths.get_mut(column) {
+                Some(column_width) => *column_width = (*column_width).max(width),
+                None => column_widths.push(width),
+            }
+        }
+    }
+    column_widths
+}
+
+/// Joins the cells of a line, padding every cell but the last one to the width of its column,
+/// see [`columns`]. The result is cut to `width` columns, truncating the last columns.
+pub fn align_columns(cells: &[&str], column_widths: &[usize], width: usize) -> String {
+    let last = cells.len().saturating_sub(1);
+    let mut out = String::new();
+    for (column, cell) in cells.iter().enumerate() {
+        out.push_str(cell);
+        if column < last {
+            let column_width = column_widths.get(column).copied().unwrap_or_default();
+            let padding = column_width.saturating_sub(visible_width(cell)) + COLUMN_GAP;
+            out.push_str(&" ".repeat(padding));
+        }
+    }
+    truncate(&out, width)
+}
+
+// ============
+// === Wrap ===
+// ============
+
+/// Word-wraps the text to rows of at most `width` columns. Words longer than the width are split.
+/// ANSI escape sequences are never split and take no space, and the styles active at the end of a
+/// row are restored at the beginning of the next one.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut wrapper = Wrapper { width, ..Default::default() };
+    for (cell, cell_width) in cells(text) {
+        if cell == " " {
+            wrapper.flush_word();
+            wrapper.pending_spaces += 1;
+        } else {
+            wrapper.word.push((cell, cell_width));
+        }
+    }
+    wrapper.flush_word();
+    wrapper.finish()
+}
+
+#[derive(Debug, Default)]
+struct Wrapper<'t> {
+    width: usize,
+    rows: Vec<String>,
+    row: String,
+    row_width: usize,
+    /// Cells of the current word with their visible widths. Escape sequences are zero-width cells.
+    word: Vec<(&'t str, usize)>,
+    pending_spaces: usize,
+    /// SGR sequences applied to the row since the last reset.
+    active_styles: String,
+}
+
+impl<'t> Wrapper<'t> {
+    /// Appends the cell to the row, tracking the styles it applies.
+    fn push_cell(&mut self, cell: &str, cell_width: usize) {
+        if cell_width == 0 && cell.ends_with('m') {
+            if cell == SGR_RESET || cell == "\x1b[m" {
+                self.active_styles.clear();
+            } else {
+                self.active_styles.push_str(cell);
+            }
+        }
+        self.row.push_str(cell);
+        self.row_width += cell_width;
+    }
+
+    fn flush_word(&mut self) {
+        let word_width: usize = self.word.iter().map(|t| t.1).sum();
+        if self.word.is_empty() { return }
+        let fits = self.row_width + self.pending_spaces + word_width <= self.width;
+        if self.row_width > 0 && !fits && word_width > 0 {
+            self.new_row();
+        } else {
+            self.row.push_str(&" ".repeat(self.pending_spaces));
+            self.row_width += self.pending_spaces;
+        }
+        self.pending_spaces = 0;
+        for (cell, cell_width) in std::mem::take(&mut self.word) {
+            if self.row_width > 0 && self.row_width + cell_width > self.width {
+                self.new_row();
+            }
+            self.push_cell(cell, cell_width);
+        }
+    }
+
+    fn new_row(&mut self) {
+        let row = std::mem::take(&mut self.row);
+        let closed = if self.active_styles.is_empty() { row } else { format!("{row}{SGR_RESET}") };
+        self.rows.push(closed);
+        self.row.clone_from(&self.active_styles);
+        self.row_width = 0;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if self.row_width > 0 || self.rows.is_empty() {
+            self.rows.push(self.row);
+        }
+        self.rows
+    }
+}
+
+// ============
+// === Diff ===
+// ============
+
+/// Lines longer than this number of characters are not compared, see [`changed_range`].
+pub const MAX_DIFF_LEN: usize = 1000;
+
+/// Range of the characters of the line which differ from the previous line, found by trimming
+/// their common prefix and suffix. Returns `None` if nothing was inserted or replaced, if either
+/// line is too long or contains escape sequences, or if more than half of the line changed, as
+/// then the lines are not variants of one message.
+pub fn changed_range(previous: &str, line: &str) -> Option<Range<usize>> {
+    if previous == line || previous.contains(ESC) || line.contains(ESC) { return None }
+    let previous = previous.chars().collect::<Vec<_>>();
+    let line = line.chars().collect::<Vec<_>>();
+    if previous.len().max(line.len()) > MAX_DIFF_LEN { return None }
+    let prefix = previous.iter().zip(&line).take_while(|(a, b)| a == b).count();
+    let max_suffix = previous.len().min(line.len()) - prefix;
+    let suffix = previous.iter().rev().zip(line.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let range = prefix..line.len() - suffix;
+    (!range.is_empty() && range.len() * 2 <= line.len()).then_some(range)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_at_words() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let rows = ["The quick", "brown fox", "jumps over", "the lazy", "dog"];
+        assert_eq!(wrap(text, 10), rows);
+        assert_eq!(wrap("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap("", 3), [""]);
+    }
+
+    #[test]
+    fn wrap_keeps_styles_across_rows() {
+        let rows = wrap("\x1b[31mred words\x1b[0m plain", 5);
+        assert_eq!(rows, ["\x1b[31mred\x1b[0m", "\x1b[31mwords\x1b[0m", "plain"]);
+        assert!(rows.iter().all(|row| visible_width(row) <= 5));
+    }
+
+    #[test]
+    fn columns_are_split_at_tabs_or_aligned_spaces() {
+        assert_eq!(columns("name\tstatus\ttime"), ["name", "status", "time"]);
+        assert_eq!(columns("  name   status  time  "), ["  name", "status", "time"]);
+        assert_eq!(columns("test parse ... ok"), ["test parse ... ok"]);
+        assert_eq!(columns("a\t\tb"), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn columns_are_aligned_to_the_widest_cells() {
+        let lines = [
+            "test parse::header ... ok\t0.01s",
+            "test render ... FAILED\t1.20s",
+            "summary  2 tests",
+        ];
+        let widths = column_widths(lines);
+        assert_eq!(widths, [25]);
+        let aligned = lines.map(|line| align_columns(&columns(line), &widths, 40));
+        assert_eq!(aligned, [
+            "test parse::header ... ok  0.01s",
+            "test render ... FAILED     1.20s",
+            "summary                    2 tests",
+        ]);
+        assert_eq!(align_columns(&columns(lines[0]), &widths, 10), "test parse");
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        let status = "\x1b[32m✅ done\x1b[0m";
+        assert_eq!(visible_width(status), 7);
+        assert_eq!(visible_width("e\u{301}"), 1);
+        assert_eq!(strip_ansi(status), "✅ done");
+        assert_eq!(truncate("✅✅", 3), "✅");
+        assert_eq!(truncate("e\u{301}x", 1), "e\u{301}");
+        assert_eq!(wrap("🚀🚀🚀", 4), ["🚀🚀", "🚀"]);
+    }
+
+    #[test]
+    fn durations_are_formatted_with_the_precision() {
+        use DurationPrecision::*;
+        let short = Duration::from_millis(1_273);
+        let long = Duration::from_millis(3_723_045);
+        let day = Duration::from_millis(90_000_500);
+        assert_eq!(duration(short, Coarse), "1s");
+        assert_eq!(duration(long, Coarse), "1h 2m");
+        assert_eq!(duration(long, Seconds), "1h 2m 3s");
+        assert_eq!(duration(short, Millis), "1.273s");
+        assert_eq!(duration(long, Millis), "1h 2m 3.045s");
+        assert_eq!(duration(short, Adaptive), "1s 273ms");
+        assert_eq!(duration(day, Adaptive), "1d 1h 0s");
+        assert_eq!(duration(Duration::ZERO, Adaptive), "0s");
+        assert_eq!(Adaptive.for_running(true), Seconds);
+        assert_eq!(Adaptive.for_running(false), Adaptive);
+        assert_eq!(Millis.for_running(true), Millis);
+    }
+
+    #[test]
+    fn day_long_durations_keep_the_day_part() {
+        use DurationPrecision::*;
+        let day = Duration::from_secs(86_400);
+        assert_eq!(duration(day, Coarse), "1d");
+        assert_eq!(duration(day, Seconds), "1d 0s");
+        assert_eq!(duration(day, Millis), "1d 0.000s");
+        assert_eq!(duration(day, Adaptive), "1d 0s");
+        let days = Duration::from_millis(2 * 86_400_000 + 180_007);
+        assert_eq!(duration(days, Coarse), "2d 3m");
+        assert_eq!(duration(days, Adaptive), "2d 3m 0s");
+        assert_eq!(duration(Duration::from_millis(2 * 86_400_000 + 7), Millis), "2d 0.007s");
+        assert_eq!(relative(Duration::from_secs(86_399)), "-23h");
+        assert_eq!(relative(day), "-1d");
+    }
+
+    #[test]
+    fn bytes_and_counts_switch_units_at_exact_multiples() {
+        assert_eq!(bytes(0), "0 B");
+        assert_eq!(bytes(1023), "1023 B");
+        assert_eq!(bytes(1024), "1.0 KiB");
+        assert_eq!(bytes(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(bytes(1024 * 1023), "1023.0 KiB");
+        assert_eq!(bytes(1024 * 1024), "1.0 MiB");
+        assert_eq!(bytes(3 << 30), "3.0 GiB");
+        assert_eq!(bytes(1 << 60), "1.0 EiB");
+        assert_eq!(bytes(u64::MAX), "16.0 EiB");
+        assert_eq!(count(0), "0");
+        assert_eq!(count(999), "999");
+        assert_eq!(count(1024), "1\u{2009}024");
+        assert_eq!(count(1_048_576), "1\u{2009}048\u{2009}576");
+        assert_eq!(count(u64::MAX).split('\u{2009}').count(), 7);
+    }
+
+    #[test]
+    fn changed_range_covers_the_inserted_or_replaced_characters() {
+        assert_eq!(changed_range("progress 10%", "progress 20%"), Some(9..10));
+        assert_eq!(changed_range("état 1/9", "état 2/9"), Some(5..6));
+        assert_eq!(changed_range("aaa", "aaaa"), Some(3..4));
+        assert_eq!(changed_range("same", "same"), None);
+        assert_eq!(changed_range("abcdef", "abdef"), None);
+        assert_eq!(changed_range("abc", "xyz"), None);
+        assert_eq!(changed_range("step 1", "\x1b[1mstep 2"), None);
+        let long = "x".repeat(MAX_DIFF_LEN);
+        assert_eq!(changed_range(&long, &format!("{long}y")), None);
+    }
+}
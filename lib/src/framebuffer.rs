@@ -1,7 +1,9 @@
 use crate::prelude::*;
 
 use std::collections::HashMap;
+use crate::format;
 use crate::group;
+use crate::terminal;
 
 // =================
 // === LineIndex ===
@@ -18,6 +20,58 @@ impl LineIndex {
     pub fn inc(self) -> Self {
         Self(self.0 + 1)
     }
+
+    /// Number of rows between the indices, in either direction.
+    pub fn distance(self, to: Self) -> usize {
+        self.0.abs_diff(to.0)
+    }
+}
+
+// ===============
+// === RowKind ===
+// ===============
+
+/// Part of the UI drawn in a row of the terminal. Groups are referred to by their ids, not by
+/// their positions on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RowKind {
+    Summary,
+    Header(group::Id),
+    /// Last line of a collapsed group shown below its header, see
+    /// [`crate::Settings::show_collapsed_errors`].
+    CollapsedError(group::Id),
+    /// Row of the displayed line of the group. Separators and wrapped lines are content rows too.
+    Content(group::Id, group::LineIndex),
+    Footer(group::Id),
+    /// Number of groups above or below the drawn ones, see [`crate::GroupViewport`].
+    MoreGroups,
+    Blank,
+    HiddenGroups,
+    ScrollBar,
+    History,
+    /// Relative times of the history entries, see [`crate::Settings::show_history_times`].
+    HistoryTimes,
+    Menu,
+    Debug,
+}
+
+impl RowKind {
+    pub fn group(self) -> Option<group::Id> {
+        match self {
+            Self::Header(group)
+            | Self::CollapsedError(group)
+            | Self::Content(group, _)
+            | Self::Footer(group) => Some(group),
+            _ => None,
+        }
+    }
+
+    pub fn group_line(self) -> Option<group::LineIndex> {
+        match self {
+            Self::Content(_, line) => Some(line),
+            _ => None,
+        }
+    }
 }
 
 // ============
@@ -28,6 +82,8 @@ impl LineIndex {
 pub struct Line {
     pub changed: bool,
     pub content: String,
+    /// The content is wider than the terminal, so its end is cut off.
+    pub truncated: bool,
 }
 
 // ===================
@@ -36,28 +92,27 @@ pub struct Line {
 
 #[derive(Clone, Debug, Default)]
 pub struct Framebuffer {
+    /// Width of the terminal.
+    pub cols: usize,
+    /// Height of the terminal. Rows below it are written, but not drawn.
+    pub rows: usize,
     pub lines: Vec<Line>,
-    pub line_to_group: HashMap<LineIndex, Option<group::Id>>,
+    pub row_kind: HashMap<LineIndex, RowKind>,
     pub group_to_lines: HashMap<group::Id, (LineIndex, LineIndex)>,
     pub group_to_group_lines: HashMap<group::Id, (group::LineIndex, group::LineIndex)>
 }
 
 impl Framebuffer {
-    fn set_line(
-        &mut self,
-        line_ix: LineIndex,
-        group: Option<group::Id>,
-        group_line_ix: Option<group::LineIndex>,
-        content: String
-    ) {
-        self.line_to_group.insert(line_ix, group);
-        if let Some(group_ix) = group {
-            self.group_to_lines.entry(group_ix).or_insert((line_ix, line_ix)).1 = line_ix;
-            if let Some(group_line_ix) = group_line_ix {
+    fn set_line(&mut self, line_ix: LineIndex, kind: RowKind, content: String) {
+        self.row_kind.insert(line_ix, kind);
+        if let Some(group_ix) = kind.group() {
+            let lines = self.group_to_lines.entry(group_ix).or_insert((line_ix, line_ix));
+            *lines = (lines.0.min(line_ix), lines.1.max(line_ix));
+            if let Some(group_line_ix) = kind.group_line() {
                 let line_range = self.group_to_group_lines
                     .entry(group_ix)
                     .or_insert((group_line_ix, group_line_ix));
-                line_range.1 = group_line_ix;
+                *line_range = (line_range.0.min(group_line_ix), line_range.1.max(group_line_ix));
             }
         }
         if LineIndex(self.lines.len()) <= line_ix {
@@ -65,13 +120,23 @@ impl Framebuffer {
         }
         let line = &mut self.lines[line_ix.0];
         if line.content != content {
+            line.truncated = format::visible_width(&content) > self.cols;
             line.content = content;
             line.changed = true;
         }
     }
 
+    pub fn row_kind(&self, index: LineIndex) -> Option<RowKind> {
+        self.row_kind.get(&index).copied()
+    }
+
     pub fn line_to_group(&self, index: LineIndex) -> Option<group::Id> {
-        self.line_to_group.get(&index).copied().flatten()
+        self.row_kind(index).and_then(RowKind::group)
+    }
+
+    /// Size of the terminal the last frame was drawn for.
+    pub fn size(&self) -> terminal::Size {
+        terminal::Size { cols: self.cols, rows: self.rows }
     }
 
     pub fn group_to_lines(&self, group_index: group::Id) -> Option<(LineIndex, LineIndex)> {
@@ -81,7 +146,7 @@ impl Framebuffer {
     fn on_frame(&mut self) {
         self.group_to_lines.clear();
         self.group_to_group_lines.clear();
-        self.line_to_group.clear();
+        self.row_kind.clear();
     }
 
     pub fn clear(&mut self) {
@@ -102,22 +167,114 @@ pub struct Writer<'t> {
     #[deref_mut]
     pub framebuffer: &'t mut Framebuffer,
     pub line: LineIndex,
+    /// Row below the lowest row written in this frame.
+    pub end: LineIndex,
+    /// Content drawn over the written rows, starting at the given column.
+    pub overlay: HashMap<LineIndex, (usize, String)>,
 }
 
 impl<'t> Writer<'t> {
-    pub fn new(framebuffer: &'t mut Framebuffer) -> Self {
+    pub fn new(framebuffer: &'t mut Framebuffer, size: terminal::Size) -> Self {
         framebuffer.on_frame();
+        framebuffer.cols = size.cols;
+        framebuffer.rows = size.rows;
         let line = default();
-        Self { framebuffer, line }
-    }
-    
-    pub fn line(
-        &mut self,
-        group: Option<group::Id>,
-        group_line: Option<group::LineIndex>,
-        content: String
-    ) {
-        self.framebuffer.set_line(self.line, group, group_line, content);
+        let end = default();
+        let overlay = default();
+        Self { framebuffer, line, end, overlay }
+    }
+
+    /// Writes the row at the cursor and moves the cursor to the next row.
+    pub fn line(&mut self, kind: RowKind, content: String) {
+        self.line_at(self.line, kind, content);
         self.line.inc_mut();
     }
+
+    /// Writes the given row without moving the cursor. Rows are marked as changed only if their
+    /// content differs, so rows can be drawn over the ones written by [`Self::line`].
+    pub fn line_at(&mut self, line_ix: LineIndex, kind: RowKind, content: String) {
+        let content = match self.overlay.get(&line_ix) {
+            Some((column, overlay)) => {
+                let visible = format::truncate(&content, *column);
+                let padding = column.saturating_sub(format::visible_width(&visible));
+                format!("{visible}{}{overlay}", " ".repeat(padding))
+            }
+            None => content,
+        };
+        self.framebuffer.set_line(line_ix, kind, content);
+        self.end = self.end.max(line_ix.inc());
+    }
+
+    /// Moves the cursor used by [`Self::line`].
+    pub fn set_cursor(&mut self, line_ix: LineIndex) {
+        self.line = line_ix;
+    }
+
+    /// Drops the rows of the previous frame below the rows written in this one, returning whether
+    /// there were any. They have to be cleared from the screen.
+    pub fn truncate(&mut self) -> bool {
+        let len = self.end.0;
+        let had_stale_rows = self.framebuffer.lines.len() > len;
+        self.framebuffer.lines.truncate(len);
+        had_stale_rows
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: terminal::Size = terminal::Size { cols: 20, rows: 4 };
+
+    fn changed_rows(framebuffer: &mut Framebuffer) -> Vec<usize> {
+        let changed = framebuffer.lines.iter().enumerate().filter(|(_, line)| line.changed);
+        let rows = changed.map(|(row, _)| row).collect();
+        for line in &mut framebuffer.lines {
+            line.changed = false;
+        }
+        rows
+    }
+
+    fn write_frame(framebuffer: &mut Framebuffer, overlay: Option<&str>) {
+        let mut writer = Writer::new(framebuffer, SIZE);
+        if let Some(overlay) = overlay {
+            writer.overlay.insert(LineIndex(1), (2, overlay.to_owned()));
+        }
+        for content in ["first", "second", "third"] {
+            writer.line(RowKind::Blank, content.to_owned());
+        }
+    }
+
+    #[test]
+    fn overlay_changes_only_its_rows() {
+        let mut framebuffer = Framebuffer::default();
+        write_frame(&mut framebuffer, None);
+        assert_eq!(changed_rows(&mut framebuffer), [0, 1, 2]);
+        write_frame(&mut framebuffer, Some("menu"));
+        assert_eq!(changed_rows(&mut framebuffer), [1]);
+        assert_eq!(framebuffer.lines[1].content, "semenu");
+        write_frame(&mut framebuffer, Some("menu"));
+        assert_eq!(changed_rows(&mut framebuffer), Vec::<usize>::new());
+        write_frame(&mut framebuffer, None);
+        assert_eq!(changed_rows(&mut framebuffer), [1]);
+    }
+
+    #[test]
+    fn truncate_keeps_the_rows_written_below_the_cursor() {
+        let mut framebuffer = Framebuffer::default();
+        let mut writer = Writer::new(&mut framebuffer, SIZE);
+        for _ in 0..4 {
+            writer.line(RowKind::Blank, "row".to_owned());
+        }
+        assert!(!writer.truncate());
+        let mut writer = Writer::new(&mut framebuffer, SIZE);
+        writer.line(RowKind::Blank, "row".to_owned());
+        writer.line_at(LineIndex(2), RowKind::Menu, "menu".to_owned());
+        assert!(writer.truncate());
+        assert_eq!(framebuffer.lines.len(), 3);
+    }
 }
@@ -0,0 +1,86 @@
+//! Fuzzy matching of short queries, e.g. the group headers typed in the [`crate::palette`].
+
+// =================
+// === Constants ===
+// =================
+
+/// Score of every matched character.
+const MATCH_SCORE: i64 = 1;
+
+/// Bonus of a character matched right after the previous matched one.
+const CONSECUTIVE_BONUS: i64 = 5;
+
+/// Bonus of a character matched at the start of a word: the start of the text, after a character
+/// which is not alphanumeric, or an uppercase letter after a lowercase one.
+const WORD_START_BONUS: i64 = 8;
+
+/// Penalty of every character skipped between two matched ones.
+const GAP_PENALTY: i64 = 1;
+
+// =============
+// === score ===
+// =============
+
+/// Score of the query matched as a subsequence of the text, ignoring the case, or `None` if the
+/// text does not contain all characters of the query in order. Higher scores are better matches:
+/// consecutive characters and characters starting words score more, and gaps between the matched
+/// characters score less. An empty query matches every text with the score 0.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut prev: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for (index, char) in text.chars().enumerate() {
+        let Some(&expected) = query.peek() else { break };
+        let is_match = char.to_lowercase().eq(std::iter::once(expected));
+        if is_match {
+            query.next();
+            score += MATCH_SCORE;
+            if let Some(last_match) = last_match {
+                let gap = index - last_match - 1;
+                score += if gap == 0 { CONSECUTIVE_BONUS } else { -(gap as i64) * GAP_PENALTY };
+            }
+            if is_word_start(prev, char) {
+                score += WORD_START_BONUS;
+            }
+            last_match = Some(index);
+        }
+        prev = Some(char);
+    }
+    query.peek().is_none().then_some(score)
+}
+
+fn is_word_start(prev: Option<char>, char: char) -> bool {
+    match prev {
+        None => true,
+        Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && char.is_uppercase()),
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_match_subsequences_ignoring_case() {
+        assert_eq!(score("", "build"), Some(0));
+        assert_eq!(score("bld", "build"), Some(14));
+        assert_eq!(score("build", "BUILD"), score("build", "build"));
+        assert_eq!(score("BLD", "build"), Some(14));
+        assert_eq!(score("dlb", "build"), None);
+        assert_eq!(score("builds", "build"), None);
+    }
+
+    #[test]
+    fn consecutive_and_word_start_matches_score_more() {
+        let score = |query| move |text| score(query, text).unwrap_or(i64::MIN);
+        assert!(score("tr")("trace") > score("tr")("test-runner"));
+        assert!(score("tr")("test-runner") > score("tr")("tar"));
+        assert!(score("gs")("getStatus") > score("gs")("gears"));
+        assert!(score("ab")("ab") > score("ab")("a_______b"));
+    }
+}
@@ -1,24 +1,41 @@
 use crate::prelude::*;
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::time::Duration;
 use std::time::SystemTime;
 use crate::LineRange;
+use crate::format;
+use crossterm::style::Color;
 
 // ==============
 // === Status ===
 // ==============
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Status {
     pub progress: Option<f32>,
     pub finished: bool,
     pub tag: StatusTag,
+    /// Amount of work done by the line, e.g. bytes processed. If any line of a group reports it,
+    /// the group throughput is measured in amounts instead of lines.
+    pub amount: Option<u64>,
+    /// Number of steps done and the total number of steps. The progress is derived from it.
+    pub steps: Option<(u64, u64)>,
+    /// The work is in progress, but its remaining amount is unknown.
+    pub indeterminate: bool,
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum StatusTag {
     #[default]
     Success,
     Error,
+    /// The work was aborted, e.g. killed by the user or skipped after a failed dependency.
+    Cancelled,
 }
 
 impl Status {
@@ -26,20 +43,50 @@ impl Status {
         let progress = None;
         let finished = false;
         let tag = StatusTag::Success;
-        Self { progress, finished, tag }
+        let amount = None;
+        let steps = None;
+        let indeterminate = false;
+        Self { progress, finished, tag, amount, steps, indeterminate }
     }
 
     pub const fn error() -> Self {
         let progress = None;
         let finished = false;
         let tag = StatusTag::Error;
-        Self { progress, finished, tag }
+        let amount = None;
+        let steps = None;
+        let indeterminate = false;
+        Self { progress, finished, tag, amount, steps, indeterminate }
+    }
+
+    pub const fn cancelled() -> Self {
+        let progress = None;
+        let finished = false;
+        let tag = StatusTag::Cancelled;
+        let amount = None;
+        let steps = None;
+        let indeterminate = false;
+        Self { progress, finished, tag, amount, steps, indeterminate }
     }
 
     pub fn progress(self, progress: impl Into<Option<f32>>) -> Self {
         Self { progress: progress.into(), ..self }
     }
 
+    /// Sets the progress as `done` steps out of `total`.
+    pub fn steps(self, done: u64, total: u64) -> Self {
+        let progress = Some((done as f32 / total.max(1) as f32).min(1.0));
+        Self { progress, steps: Some((done, total)), ..self }
+    }
+
+    pub const fn indeterminate(self) -> Self {
+        Self { indeterminate: true, ..self }
+    }
+
+    pub const fn amount(self, amount: u64) -> Self {
+        Self { amount: Some(amount), ..self }
+    }
+
     pub const fn finished(self) -> Self {
         Self { finished: true, ..self }
     }
@@ -51,6 +98,39 @@ impl Status {
     pub fn is_error(&self) -> bool {
         self.tag == StatusTag::Error
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.tag == StatusTag::Cancelled
+    }
+}
+
+// =============
+// === Level ===
+// =============
+
+/// Severity of a log line, independent of its [`Status`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Level {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    /// The next level, wrapping around after the last one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Trace => Self::Debug,
+            Self::Debug => Self::Info,
+            Self::Info => Self::Warn,
+            Self::Warn => Self::Error,
+            Self::Error => Self::Trace,
+        }
+    }
 }
 
 // =============
@@ -60,6 +140,18 @@ impl Status {
 #[derive(Clone, Copy, Debug, Deref, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct LineIndex(pub usize);
 
+impl LineIndex {
+    /// Index shifted by the offset, if it is not negative.
+    pub fn offset(self, offset: isize) -> Option<Self> {
+        self.0.checked_add_signed(offset).map(Self)
+    }
+
+    /// Number of lines between the indices, in either direction.
+    pub fn distance(self, to: Self) -> usize {
+        self.0.abs_diff(to.0)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deref, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct Id(pub usize);
 
@@ -67,13 +159,52 @@ pub struct Id(pub usize);
 pub struct Line {
     pub log: Log,
     pub timestamp: crate::LineId,
+    /// Time of the line, or of its last repetition.
     pub time: SystemTime,
+    pub kind: LineKind,
+    /// Number of consecutive occurrences of the line merged into it, see [`State::dedup`].
+    pub repeat: u32,
+}
+
+// ==================
+// === Annotation ===
+// ==================
+
+/// Note attached to a stored line after it was logged, shown below the line, see
+/// [`crate::SharedLogger::annotate`].
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub text: String,
+    pub severity: Level,
+}
+
+impl Annotation {
+    pub fn new(text: impl Into<String>, severity: Level) -> Self {
+        let text = text.into();
+        Self { text, severity }
+    }
+}
+
+impl Line {
+    pub fn is_run_start(&self) -> bool {
+        matches!(self.kind, LineKind::RunStart(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineKind {
+    #[default]
+    Log,
+    /// Separator marking the start of the given run of the group.
+    RunStart(usize),
 }
 
 #[derive(Debug)]
 pub struct Log {
     pub content: String,
     pub status: Status,
+    pub level: Level,
 }
 
 #[derive(Debug, Deref, DerefMut)]
@@ -87,23 +218,178 @@ pub struct Group {
 #[derive(Debug)]
 pub struct State {
     pub id: Id,
+    /// Path of the group selector the group was created with.
+    pub path: Vec<String>,
+    /// Title of the header.
     pub header: String,
+    /// The group was created without a header, e.g. by its first line arriving before the
+    /// header was set. A placeholder is shown instead of the title shortly after the creation,
+    /// see [`crate::HEADER_GRACE`].
+    pub header_pending: bool,
+    pub created_at: SystemTime,
+    /// Text shown after the title, see [`HeaderSpec`].
+    pub subtitle: Option<String>,
+    /// Show the progress widgets in the header, see [`HeaderSpec`].
+    pub show_progress: bool,
+    pub badges: Vec<Badge>,
+    /// Icon shown in the header before the progress widget, a single grapheme cluster. Hidden if
+    /// the theme does not use Unicode.
+    pub icon: Option<String>,
     pub footer: String,
+    /// Key the groups are displayed by, before their creation order. Makes the layout independent
+    /// of which concurrent task creates its group first, see [`Order`].
+    pub order_key: i64,
+    /// Display the group before the unpinned ones, regardless of the [`Order`].
+    pub pinned: bool,
     pub lines: Vec<Line>,
+    /// Notes attached to the stored lines. They are dropped with their lines.
+    pub annotations: BTreeMap<crate::LineId, Vec<Annotation>>,
     pub collapsed: Option<bool>,
+    /// Whether the group is selected. Derived from the selection of the logger, which survives the
+    /// group being hidden, see [`crate::Logger::set_selection`].
     pub selected: bool,
-    pub scroll: Option<usize>,
+    /// Id of the top displayed line, if scrolled back. Anchoring to the line instead of its index
+    /// keeps the view in place when lines are evicted or filtered, see [`LineRange::scroll_index`].
+    pub scroll: Option<crate::LineId>,
+    /// Number of the current run, starting from 1.
+    pub run: usize,
+    /// Start of the measured duration of the current run, see [`crate::SharedLogger::start_timer`].
+    /// If not set, the duration is measured from the first line of the run.
+    pub started_at: Option<SystemTime>,
+    /// Precision of the duration shown in the footer. If not set, the one of the theme is used,
+    /// see [`crate::style::Theme::duration_precision`].
+    pub duration_precision: Option<format::DurationPrecision>,
+    /// Time without new lines after which an unfinished group is considered stale.
+    pub stale_after: Option<Duration>,
+    /// Expected duration of a run. Unfinished runs taking longer are highlighted, see
+    /// [`LineRange::overrun`].
+    pub time_budget: Option<Duration>,
+    /// Overrun of the budget last reported to the listeners, see
+    /// [`crate::SharedLogger::on_time_budget_overrun`].
+    pub reported_overrun: Option<Overrun>,
+    /// Time without new lines after which an unfinished group is stalled, and
+    /// [`Self::stall_action`] is taken, see [`crate::Logger::check_stalls`].
+    pub stall_timeout: Option<Duration>,
+    pub stall_action: StallAction,
+    /// Stops the work logging to the group, offered in its context menu if set.
+    pub kill_handler: Option<KillHandler>,
+    /// The group exceeded its [`Self::stall_timeout`]. Cleared when a line is pushed.
+    pub stalled: bool,
+    /// Unmet dependency of the unfinished group, evaluated every frame, see
+    /// [`crate::SharedLogger::set_group_dependency`].
+    pub blocked: Option<Blocked>,
+    pub throughput: Throughput,
+    pub activity: Activity,
+    pub line_counter: LineCounter,
+    /// Maximum number of stored lines. The oldest lines are dropped when exceeded, see
+    /// [`Self::evict_lines`].
+    pub max_lines: Option<usize>,
+    /// Render the group as a single pending header row while it has no lines, instead of hiding
+    /// it.
+    pub show_when_empty: bool,
+    /// Render the group without the footer row, showing the duration in the header instead, so
+    /// that more groups fit the terminal.
+    pub compact: bool,
+    /// Wrap long lines at word boundaries instead of truncating them.
+    pub wrap: bool,
+    /// Number of times per second the body is refreshed. If not set, the group is throttled only
+    /// while it has fast output, see [`crate::Settings::fast_group_refresh_rate`].
+    pub tail_refresh_rate: Option<u32>,
+    /// Merge a line equal to the previous one, including its status tag, into it, counting the
+    /// repetitions. Repetitions do not get their own history entries and [`crate::LineId`]s, so in
+    /// the history view a merged line shows its final repetition count.
+    pub dedup: bool,
+    /// Align the columns of the displayed lines, separated by tabs or multiple spaces, see
+    /// [`format::columns`].
+    pub tabular: bool,
+    /// Highlight the part of a line which changed since the previous displayed line, if the
+    /// lines differ by a small edit, see [`format::changed_range`].
+    pub diff_highlight: bool,
+    /// Lines below this level are not displayed. They are kept, and they still count for the
+    /// group status and durations.
+    pub min_level: Level,
+    /// Highlighting rules of the group lines, applied before the global ones.
+    #[cfg(feature = "regex")]
+    pub highlights: Vec<crate::highlight::Rule>,
+    /// Status rules of the group, applied before the global ones.
+    #[cfg(feature = "regex")]
+    pub status_rules: Vec<crate::status_rule::StatusRule>,
 }
 
 impl State {
     pub fn new(id: Id) -> Self {
+        let path = default();
         let header = default();
+        let header_pending = false;
+        let created_at = SystemTime::now();
+        let subtitle = None;
+        let show_progress = true;
+        let badges = default();
+        let icon = None;
         let footer = default();
+        let order_key = 0;
+        let pinned = false;
         let lines = default();
+        let annotations = default();
         let collapsed = None;
         let selected = false;
         let scroll = None;
-        Self { id, header, footer, lines, collapsed, selected, scroll }
+        let run = 1;
+        let started_at = None;
+        let duration_precision = None;
+        let stale_after = None;
+        let time_budget = None;
+        let reported_overrun = None;
+        let stall_timeout = None;
+        let stall_action = default();
+        let stalled = false;
+        let blocked = None;
+        let throughput = default();
+        let activity = default();
+        let line_counter = default();
+        let max_lines = None;
+        let show_when_empty = false;
+        let compact = false;
+        let wrap = false;
+        let tail_refresh_rate = None;
+        let dedup = false;
+        let tabular = false;
+        let diff_highlight = false;
+        let min_level = Level::Trace;
+        let kill_handler = None;
+        #[cfg(feature = "regex")]
+        let highlights = default();
+        #[cfg(feature = "regex")]
+        let status_rules = default();
+        Self {
+            id, path, header, header_pending, created_at, subtitle, show_progress, badges, icon,
+            footer, order_key, pinned, lines, annotations, collapsed, selected, scroll, run,
+            started_at, duration_precision, stale_after, time_budget, reported_overrun,
+            stall_timeout, stall_action, kill_handler, stalled, blocked, throughput, activity,
+            line_counter, max_lines, show_when_empty, compact, wrap, tail_refresh_rate, dedup,
+            tabular, diff_highlight, min_level,
+            #[cfg(feature = "regex")]
+            highlights,
+            #[cfg(feature = "regex")]
+            status_rules,
+        }
+    }
+}
+
+/// Part of [`State::max_lines`] dropped at once when the limit is exceeded.
+const EVICTION_DIVISOR: usize = 4;
+
+impl State {
+    /// Drops the oldest lines with their annotations if there are more than [`Self::max_lines`].
+    /// A quarter of the limit is dropped at once, so that the lines are not shifted on every
+    /// push.
+    pub fn evict_lines(&mut self) {
+        let Some(max_lines) = self.max_lines.filter(|max| self.lines.len() > *max) else { return };
+        let kept = max_lines - max_lines / EVICTION_DIVISOR;
+        self.lines.drain(..self.lines.len() - kept);
+        if let Some(first) = self.lines.first().map(|t| t.timestamp) {
+            self.annotations = self.annotations.split_off(&first);
+        }
     }
 }
 
@@ -115,6 +401,502 @@ impl Group {
     }
 }
 
+// ==================
+// === Throughput ===
+// ==================
+
+/// Width of a single throughput sample bucket.
+const THROUGHPUT_BUCKET: Duration = Duration::from_millis(250);
+
+/// Time window over which the current throughput is measured.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Sampler of the amount of work done by a group over time.
+#[derive(Clone, Debug, Default)]
+pub struct Throughput {
+    /// Bucket start times with the totals of lines and of amounts recorded before the bucket
+    /// started. Both are kept, as the unit changes when the first amount is recorded.
+    samples: VecDeque<(SystemTime, Totals)>,
+    total_lines: u64,
+    total_amount: u64,
+    uses_amount: bool,
+}
+
+impl Throughput {
+    pub fn record(&mut self, time: SystemTime, amount: Option<u64>) {
+        let total = Totals { lines: self.total_lines, amount: self.total_amount };
+        let bucket_end = self.samples.back().and_then(|t| t.0.checked_add(THROUGHPUT_BUCKET));
+        if bucket_end.is_none_or(|end| end <= time) {
+            self.samples.push_back((time, total));
+        }
+        while self.samples.front().is_some_and(|t|
+            time.duration_since(t.0).unwrap_or_default() > THROUGHPUT_WINDOW + THROUGHPUT_BUCKET
+        ) {
+            self.samples.pop_front();
+        }
+        self.total_lines += 1;
+        if let Some(amount) = amount {
+            self.uses_amount = true;
+            self.total_amount += amount;
+        }
+    }
+
+    /// Whether the throughput is measured in amounts reported in statuses, or in lines.
+    pub fn uses_amount(&self) -> bool {
+        self.uses_amount
+    }
+
+    pub fn total(&self) -> u64 {
+        if self.uses_amount { self.total_amount } else { self.total_lines }
+    }
+
+    /// Amount per second over the recent window. It decays to zero when the group goes quiet.
+    pub fn rate(&self, now: SystemTime) -> f64 {
+        let window_start = now.checked_sub(THROUGHPUT_WINDOW).unwrap_or(now);
+        let base = self.samples.iter().find(|t| t.0 >= window_start);
+        base.map_or(0.0, |(time, total)| {
+            let elapsed = now.duration_since(*time).unwrap_or_default().max(THROUGHPUT_BUCKET);
+            let total = if self.uses_amount { total.amount } else { total.lines };
+            self.total().saturating_sub(total) as f64 / elapsed.as_secs_f64()
+        })
+    }
+}
+
+/// Totals recorded by [`Throughput`] before a sample.
+#[derive(Clone, Copy, Debug, Default)]
+struct Totals {
+    lines: u64,
+    amount: u64,
+}
+
+// ================
+// === Activity ===
+// ================
+
+/// Number of seconds of line counts kept by [`Activity`].
+const ACTIVITY_SECONDS: usize = 60;
+
+/// Per-second line counts of a group over the recent time.
+#[derive(Clone, Debug, Default)]
+pub struct Activity {
+    /// Seconds since the Unix epoch with the number of lines logged within them.
+    buckets: VecDeque<(u64, u32)>,
+}
+
+impl Activity {
+    pub fn record(&mut self, time: SystemTime) {
+        let second = Self::second(time);
+        match self.buckets.back_mut() {
+            Some(bucket) if bucket.0 == second => bucket.1 += 1,
+            _ => self.buckets.push_back((second, 1)),
+        }
+        while self.buckets.front().is_some_and(|t| t.0 + ACTIVITY_SECONDS as u64 <= second) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Number of lines logged within the last complete second before `now`.
+    pub fn lines_per_second(&self, now: SystemTime) -> f32 {
+        self.counts(now, 2)[0]
+    }
+
+    /// Line counts of the last `count` seconds up to `now`, oldest first.
+    pub fn counts(&self, now: SystemTime, count: usize) -> Vec<f32> {
+        let now = Self::second(now);
+        let first = (now + 1).saturating_sub(count as u64);
+        let mut counts = vec![0.0; count];
+        for (second, lines) in &self.buckets {
+            if let Some(index) = second.checked_sub(first).filter(|t| *t < count as u64) {
+                counts[index as usize] = *lines as f32;
+            }
+        }
+        counts
+    }
+
+    fn second(time: SystemTime) -> u64 {
+        time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+}
+
+// ===================
+// === LineCounter ===
+// ===================
+
+/// Longest time window counted by [`LineCounter`].
+pub const MAX_STATS_WINDOW: Duration = Duration::from_secs(600);
+
+/// Number of lines and of error lines logged within a time window, see
+/// [`LineRange::window_stats`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WindowStats {
+    pub lines: usize,
+    pub errors: usize,
+}
+
+impl WindowStats {
+    fn record(&mut self, log: &Log, count: usize) {
+        self.lines += count;
+        if log.status.is_error() || log.level == Level::Error {
+            self.errors += count;
+        }
+    }
+}
+
+/// Per-second counts of the lines and of the error lines of a group, kept for
+/// [`MAX_STATS_WINDOW`], and their totals. Expired seconds are dropped as lines are recorded.
+#[derive(Clone, Debug, Default)]
+pub struct LineCounter {
+    /// Seconds since the Unix epoch with the counts of the lines logged within them.
+    buckets: VecDeque<(u64, WindowStats)>,
+    total: WindowStats,
+}
+
+impl LineCounter {
+    pub fn record(&mut self, time: SystemTime, log: &Log) {
+        self.total.record(log, 1);
+        let second = Activity::second(time);
+        match self.buckets.back_mut() {
+            Some(bucket) if bucket.0 == second => bucket.1.record(log, 1),
+            _ => {
+                let mut stats = WindowStats::default();
+                stats.record(log, 1);
+                self.buckets.push_back((second, stats));
+            }
+        }
+        let kept = MAX_STATS_WINDOW.as_secs();
+        while self.buckets.front().is_some_and(|t| t.0 + kept <= second) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Counts of all recorded lines, including the ones dropped or cleared since. They never
+    /// decrease.
+    pub fn total(&self) -> WindowStats {
+        self.total
+    }
+
+    /// Counts of the whole seconds of the window ending with the second of `now`. Windows longer
+    /// than [`MAX_STATS_WINDOW`] are cut to it.
+    pub fn stats(&self, now: SystemTime, window: Duration) -> WindowStats {
+        let now = Activity::second(now);
+        let seconds = window.min(MAX_STATS_WINDOW).as_secs().max(1);
+        let first = (now + 1).saturating_sub(seconds);
+        let buckets = self.buckets.iter().filter(|(second, _)| (first..=now).contains(second));
+        buckets.fold(default(), |total, (_, stats)| WindowStats {
+            lines: total.lines + stats.lines,
+            errors: total.errors + stats.errors,
+        })
+    }
+}
+
+// =============
+// === Order ===
+// =============
+
+/// Display order of groups. The order only affects how groups are presented, their [`Id`]s stay
+/// stable storage indexes. Pinned groups are displayed first, see [`State::pinned`]. Groups that
+/// compare equal are ordered by their [`State::order_key`], and then by their creation order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Order {
+    #[default]
+    Creation,
+    Name,
+    /// Groups with the most recent line first.
+    LastActivity,
+    /// Groups whose last line is an error first, then the active ones, then the finished ones.
+    ErrorsFirst,
+}
+
+impl Order {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Creation => Self::Name,
+            Self::Name => Self::LastActivity,
+            Self::LastActivity => Self::ErrorsFirst,
+            Self::ErrorsFirst => Self::Creation,
+        }
+    }
+
+    pub fn compare(self, a: &LineRange<&Group>, b: &LineRange<&Group>) -> Ordering {
+        let ordering = match self {
+            Self::Creation => Ordering::Equal,
+            Self::Name => a.header.cmp(&b.header),
+            Self::LastActivity => {
+                let last_timestamp = |g: &LineRange<&Group>|
+                    g.state().view_lines().last().map(|line| line.timestamp);
+                last_timestamp(b).cmp(&last_timestamp(a))
+            }
+            Self::ErrorsFirst => {
+                let rank = |g: &LineRange<&Group>| {
+                    let state = g.state();
+                    let status = state.view_lines().last().map(|line| line.log.status);
+                    match status {
+                        Some(status) if status.is_error() => 0,
+                        Some(status) if !status.is_finished() => 1,
+                        _ => 2,
+                    }
+                };
+                rank(a).cmp(&rank(b))
+            }
+        };
+        let pinned = b.pinned.cmp(&a.pinned);
+        pinned.then(ordering).then(a.order_key.cmp(&b.order_key)).then(a.id.cmp(&b.id))
+    }
+}
+
+// ===============
+// === Summary ===
+// ===============
+
+/// Number of groups in every status category.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    pub running: usize,
+    pub done: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    /// Groups without visible lines.
+    pub pending: usize,
+}
+
+impl Summary {
+    pub fn record(&mut self, group: &LineRange<&Group>) {
+        let last_status = group.state().view_lines().last().map(|line| line.log.status);
+        match last_status {
+            None => self.pending += 1,
+            Some(status) if status.tag == StatusTag::Error => self.failed += 1,
+            Some(status) if status.tag == StatusTag::Cancelled => self.cancelled += 1,
+            Some(status) if status.is_finished() => self.done += 1,
+            Some(_) => self.running += 1,
+        }
+    }
+}
+
+// ==================
+// === HeaderSpec ===
+// ==================
+
+/// Colored label shown at the end of the header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Badge {
+    pub text: String,
+    pub color: Color,
+}
+
+impl Badge {
+    pub fn new(text: impl Into<String>, color: Color) -> Self {
+        let text = text.into();
+        Self { text, color }
+    }
+}
+
+/// Structured header of a group, rendered by the style with consistent spacing, see
+/// [`crate::SharedLogger::set_group_header_spec`]. Plain string headers are title-only specs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderSpec {
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// Show the progress bar or spinner, and the related widgets, before the title.
+    pub show_progress: bool,
+    pub badges: Vec<Badge>,
+}
+
+impl HeaderSpec {
+    pub fn title_only(title: impl Into<String>) -> Self {
+        let title = title.into();
+        let subtitle = None;
+        let show_progress = true;
+        let badges = default();
+        Self { title, subtitle, show_progress, badges }
+    }
+}
+
+impl From<&str> for HeaderSpec {
+    fn from(title: &str) -> Self {
+        Self::title_only(title)
+    }
+}
+
+impl From<String> for HeaderSpec {
+    fn from(title: String) -> Self {
+        Self::title_only(title)
+    }
+}
+
+impl State {
+    /// Checks whether a placeholder is shown instead of the title, see [`State::header_pending`].
+    pub fn is_header_pending(&self, now: SystemTime) -> bool {
+        let age = now.duration_since(self.created_at).unwrap_or_default();
+        self.header_pending && age < crate::HEADER_GRACE
+    }
+
+    pub fn header_spec(&self) -> HeaderSpec {
+        let title = self.header.clone();
+        let subtitle = self.subtitle.clone();
+        let show_progress = self.show_progress;
+        let badges = self.badges.clone();
+        HeaderSpec { title, subtitle, show_progress, badges }
+    }
+
+    pub fn set_header_spec(&mut self, spec: HeaderSpec) {
+        let HeaderSpec { title, subtitle, show_progress, badges } = spec;
+        self.header = title;
+        self.header_pending = false;
+        self.subtitle = subtitle;
+        self.show_progress = show_progress;
+        self.badges = badges;
+    }
+}
+
+// ===============
+// === Overrun ===
+// ===============
+
+/// How much a run exceeded the [`State::time_budget`] of its group.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Overrun {
+    /// The run takes longer than the budget.
+    Exceeded,
+    /// The run takes longer than twice the budget.
+    Doubled,
+}
+
+impl Overrun {
+    /// Overrun of a run which took `elapsed` so far, if any.
+    pub fn new(elapsed: Duration, budget: Duration) -> Option<Self> {
+        if elapsed > budget.saturating_mul(2) {
+            Some(Self::Doubled)
+        } else if elapsed > budget {
+            Some(Self::Exceeded)
+        } else {
+            None
+        }
+    }
+}
+
+// ===================
+// === StallAction ===
+// ===================
+
+/// Action taken when a group stalls, see [`State::stall_timeout`]. Stalled groups are marked in
+/// their headers regardless of the action.
+#[derive(Clone, Default)]
+pub enum StallAction {
+    /// Only mark the group.
+    #[default]
+    MarkStalled,
+    /// Push an error line reporting the time without output.
+    PushErrorLine,
+    /// Call the function with the group outside of the logger lock, e.g. to kill the process
+    /// logging to it.
+    Callback(Arc<dyn Fn(Id) + Send + Sync>),
+}
+
+impl Debug for StallAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MarkStalled => write!(f, "MarkStalled"),
+            Self::PushErrorLine => write!(f, "PushErrorLine"),
+            Self::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+impl StallAction {
+    pub fn callback(f: impl Fn(Id) + Send + Sync + 'static) -> Self {
+        Self::Callback(Arc::new(f))
+    }
+}
+
+// ===================
+// === KillHandler ===
+// ===================
+
+/// Function stopping the work logging to a group, e.g. killing its process, see
+/// [`State::kill_handler`]. It is called outside of the logger lock.
+#[derive(Clone)]
+pub struct KillHandler {
+    f: Arc<dyn Fn(Id) + Send + Sync>,
+}
+
+impl Debug for KillHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KillHandler").finish()
+    }
+}
+
+impl KillHandler {
+    pub fn new(f: impl Fn(Id) + Send + Sync + 'static) -> Self {
+        Self { f: Arc::new(f) }
+    }
+
+    pub fn call(&self, group: Id) {
+        (self.f)(group)
+    }
+}
+
+// ===============
+// === Blocked ===
+// ===============
+
+/// Presentation of a group whose dependencies did not all finish successfully, see
+/// [`crate::SharedLogger::set_group_dependency`]. Dependencies are only displayed, nothing is
+/// scheduled by them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Blocked {
+    /// Headers of the dependencies which did not finish yet.
+    Waiting(Vec<String>),
+    /// Header of a dependency which finished with an error or was cancelled.
+    Cancelled(String),
+}
+
+impl Blocked {
+    /// Text shown after the title.
+    pub fn subtitle(&self) -> String {
+        match self {
+            Self::Waiting(headers) => format!("waiting on {}", headers.join(", ")),
+            Self::Cancelled(header) => format!("cancelled, {header} failed"),
+        }
+    }
+}
+
+// ================
+// === Template ===
+// ================
+
+/// Settings applied to groups at their creation. Fields which are not set are left untouched.
+#[derive(Clone, Debug, Default)]
+pub struct Template {
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    pub collapsed: Option<bool>,
+    pub auto_collapse: Option<AutoCollapse>,
+    pub stale_after: Option<Duration>,
+    /// See [`State::time_budget`].
+    pub time_budget: Option<Duration>,
+    pub max_lines: Option<usize>,
+    /// See [`State::order_key`].
+    pub order: Option<i64>,
+}
+
+impl Template {
+    pub fn apply(&self, group: &mut Group) {
+        if let Some(header) = &self.header {
+            group.header.clone_from(header);
+            group.header_pending = false;
+        }
+        if let Some(footer) = &self.footer { group.footer.clone_from(footer) }
+        if let Some(collapsed) = self.collapsed { group.collapsed = Some(collapsed) }
+        if let Some(auto_collapse) = &self.auto_collapse {
+            group.auto_collapse = auto_collapse.clone()
+        }
+        if let Some(stale_after) = self.stale_after { group.stale_after = Some(stale_after) }
+        if let Some(time_budget) = self.time_budget { group.time_budget = Some(time_budget) }
+        if let Some(max_lines) = self.max_lines { group.max_lines = Some(max_lines) }
+        if let Some(order) = self.order { group.order_key = order }
+    }
+}
+
 // ====================
 // === AutoCollapse ===
 // ====================
@@ -125,6 +907,7 @@ pub struct AutoCollapse {
 }
 
 impl AutoCollapse {
+    /// Collapses groups which finished successfully. Failed and cancelled groups stay expanded.
     pub fn collapse_on_success() -> Self {
         Self { 
             filter: Arc::new(|group: LineRange<&State>| {
@@ -164,6 +947,59 @@ impl Debug for AutoCollapse {
     }
 }
 
+// ======================
+// === DisplayedLines ===
+// ======================
+
+/// Lines displayed by a group, see [`LineRange::displayed_lines`]. If the level filter passes all
+/// lines, they are borrowed as they are, so that the unfiltered groups are not collected on every
+/// frame.
+#[derive(Clone, Debug)]
+pub enum DisplayedLines<'t> {
+    All(&'t [Line]),
+    Filtered(Vec<&'t Line>),
+}
+
+impl<'t> DisplayedLines<'t> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::All(lines) => lines.len(),
+            Self::Filtered(lines) => lines.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&'t Line> {
+        match self {
+            Self::All(lines) => lines.get(index),
+            Self::Filtered(lines) => lines.get(index).copied(),
+        }
+    }
+
+    /// Line at the index. Panics if the index is out of bounds, like indexing a slice.
+    pub fn at(&self, index: usize) -> &'t Line {
+        match self {
+            Self::All(lines) => &lines[index],
+            Self::Filtered(lines) => lines[index],
+        }
+    }
+
+    pub fn last(&self) -> Option<&'t Line> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Index of the first line for which the predicate is false, see [`slice::partition_point`].
+    pub fn partition_point(&self, mut pred: impl FnMut(&Line) -> bool) -> usize {
+        match self {
+            Self::All(lines) => lines.partition_point(pred),
+            Self::Filtered(lines) => lines.partition_point(|line| pred(line)),
+        }
+    }
+}
+
 // ============
 // === View ===
 // ============
@@ -178,17 +1014,305 @@ impl<'t> LineRange<&'t Group> {
     pub fn state(&self) -> LineRange<&'t State> {
         self.map(|t| &t.state)
     }
+
+    /// Checks whether the group has no visible lines yet.
+    pub fn is_pending(&self) -> bool {
+        self.state().view_lines().is_empty()
+    }
+
+    /// Checks whether the group is displayed as its header row only.
+    pub fn is_single_row(&self) -> bool {
+        self.is_collapsed() || self.is_pending()
+    }
+
+    /// Last visible line of the collapsed group, if it is an error.
+    pub fn collapsed_error(&self) -> Option<&Line> {
+        let last_line = self.state().view_lines().last();
+        last_line.filter(|line| self.is_collapsed() && line.log.status.tag == StatusTag::Error)
+    }
+
+    /// Checks whether the group finished successfully longer than `hide_after` ago. Groups ending
+    /// with an error are never hidden.
+    pub fn is_hidden(&self, hide_after: Option<Duration>) -> bool {
+        let state = self.state();
+        let last_line = state.view_lines().last();
+        hide_after.zip(last_line).is_some_and(|(hide_after, line)| {
+            let status = line.log.status;
+            let finished_ok = status.is_finished() && !status.is_error();
+            let elapsed = SystemTime::now().duration_since(line.time).unwrap_or_default();
+            finished_ok && elapsed >= hide_after
+        })
+    }
 }
 
-impl LineRange<&State> {
-    pub fn view_lines(&self) -> &[Line] {
+impl<'t> LineRange<&'t State> {
+    /// Number of lines and of error lines logged within the window ending at `now`, or at the
+    /// last visible line in the history view. The history view counts the stored lines, so the
+    /// dropped ones are not counted, see [`State::max_lines`].
+    pub fn window_stats(&self, now: SystemTime, window: Duration) -> WindowStats {
+        if self.next_line.is_none() {
+            return self.data.line_counter.stats(now, window);
+        }
+        let lines = self.view_lines();
+        let Some(end) = lines.last().map(|t| t.time) else { return default() };
+        let start = end.checked_sub(window).unwrap_or(std::time::UNIX_EPOCH);
+        let mut stats = WindowStats::default();
+        for line in lines.iter().rev().take_while(|t| t.time > start) {
+            if line.kind == LineKind::Log {
+                stats.record(&line.log, line.repeat as usize);
+            }
+        }
+        stats
+    }
+
+    pub fn view_lines(&self) -> &'t [Line] {
+        let lines = &self.data.lines;
         if let Some(view_range) = self.next_line {
-            let end = self.data.lines.iter().enumerate()
-                .find(|l| l.1.timestamp >= view_range)
-                .map_or_else(|| self.data.lines.len(), |t| t.0);
-            &self.data.lines[..end]
+            &lines[..lines.partition_point(|line| line.timestamp < view_range)]
         } else {
-            &self.data.lines
+            lines
+        }
+    }
+
+    /// Visible lines which pass the level filter of the group. Scroll offsets are indices into
+    /// them. Separators of runs are always displayed.
+    pub fn displayed_lines(&self) -> DisplayedLines<'t> {
+        let min_level = self.min_level;
+        let lines = self.view_lines();
+        if min_level == Level::Trace {
+            return DisplayedLines::All(lines)
+        }
+        DisplayedLines::Filtered(lines.iter()
+            .filter(|line| line.is_run_start() || line.log.level >= min_level)
+            .collect())
+    }
+
+    /// Index of the top line of a scrolled back group in [`Self::displayed_lines`]. If the anchor
+    /// line is not displayed anymore, the next displayed line is used.
+    pub fn scroll_index(&self) -> Option<usize> {
+        let lines = self.displayed_lines();
+        self.scroll.map(|anchor| lines.partition_point(|line| line.timestamp < anchor))
+    }
+
+    /// Checks whether the group is unfinished and did not output anything for longer than its
+    /// [`State::stale_after`] threshold. In the history view groups are never stale.
+    pub fn is_stale(&self) -> bool {
+        let last_line = self.view_lines().last();
+        let live_view = self.next_line.is_none();
+        live_view && self.stale_after.zip(last_line).is_some_and(|(stale_after, line)| {
+            let elapsed = SystemTime::now().duration_since(line.time).unwrap_or_default();
+            !line.log.status.is_finished() && elapsed >= stale_after
+        })
+    }
+
+    /// Overrun of the [`State::time_budget`] by the unfinished current run at `now`. In the history
+    /// view, the run is measured up to its last visible line, as shown in the footer.
+    pub fn overrun(&self, now: SystemTime) -> Option<Overrun> {
+        let budget = self.time_budget?;
+        let lines = self.run_lines();
+        let last = lines.last();
+        if last.is_some_and(|line| line.log.status.is_finished()) { return None }
+        let start = self.started_at.or_else(|| lines.first().map(|line| line.time))?;
+        let end = if self.next_line.is_some() { last?.time } else { now };
+        Overrun::new(end.duration_since(start).unwrap_or_default(), budget)
+    }
+
+    /// Visible lines of the current run, including its separator line.
+    pub fn run_lines(&self) -> &[Line] {
+        let lines = self.view_lines();
+        let start = lines.iter().rposition(|line| line.is_run_start()).unwrap_or_default();
+        &lines[start..]
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Group with one line of the given status per line id, or without lines.
+    fn group(id: usize, header: &str, lines: &[(usize, Status)]) -> Group {
+        let mut group = Group::new(Id(id));
+        group.header = header.into();
+        for (timestamp, status) in lines {
+            let log = Log { content: default(), status: *status, level: default() };
+            let (timestamp, time) = (crate::LineId(*timestamp), SystemTime::UNIX_EPOCH);
+            group.lines.push(Line { log, timestamp, time, kind: default(), repeat: 1 });
+        }
+        group
+    }
+
+    fn sorted(order: Order, groups: &[Group]) -> Vec<usize> {
+        let mut views = groups.iter().map(|data| LineRange { data, next_line: None })
+            .collect::<Vec<_>>();
+        views.sort_by(|a, b| order.compare(a, b));
+        views.iter().map(|view| view.id.0).collect()
+    }
+
+    #[test]
+    fn order_compares_groups() {
+        let groups = [
+            group(0, "c", &[(1, Status::ok())]),
+            group(1, "a", &[(3, Status::error())]),
+            group(2, "b", &[(2, Status::ok().finished())]),
+            group(3, "d", &[]),
+        ];
+        assert_eq!(sorted(Order::Creation, &groups), [0, 1, 2, 3]);
+        assert_eq!(sorted(Order::Name, &groups), [1, 2, 0, 3]);
+        assert_eq!(sorted(Order::LastActivity, &groups), [1, 2, 0, 3]);
+        assert_eq!(sorted(Order::ErrorsFirst, &groups), [1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn line_indices_do_not_underflow() {
+        assert_eq!(LineIndex(2).offset(-3), None);
+        assert_eq!(LineIndex(2).offset(-2), Some(LineIndex(0)));
+        assert_eq!(LineIndex(usize::MAX).offset(1), None);
+        assert_eq!(LineIndex(2).distance(LineIndex(7)), 5);
+        assert_eq!(LineIndex(7).distance(LineIndex(2)), 5);
+    }
+
+    #[test]
+    fn overruns_are_measured_against_the_budget() {
+        let secs = Duration::from_secs;
+        assert_eq!(Overrun::new(secs(10), secs(10)), None);
+        assert_eq!(Overrun::new(secs(11), secs(10)), Some(Overrun::Exceeded));
+        assert_eq!(Overrun::new(secs(21), secs(10)), Some(Overrun::Doubled));
+        let start = SystemTime::UNIX_EPOCH + secs(100);
+        let mut running = group(0, "a", &[(0, Status::ok()), (1, Status::ok())]);
+        running.lines[0].time = start;
+        running.lines[1].time = start + secs(5);
+        running.time_budget = Some(secs(10));
+        let overrun = |group: &Group, next_line, now| {
+            LineRange { data: group, next_line }.state().overrun(now)
+        };
+        assert_eq!(overrun(&running, None, start + secs(15)), Some(Overrun::Exceeded));
+        assert_eq!(overrun(&running, Some(crate::LineId(2)), start + secs(15)), None);
+        running.started_at = Some(start - secs(25));
+        assert_eq!(overrun(&running, None, start), Some(Overrun::Doubled));
+        running.lines[1].log.status = Status::ok().finished();
+        assert_eq!(overrun(&running, None, start + secs(60)), None);
+    }
+
+    #[test]
+    fn pinned_groups_come_first() {
+        let mut groups = [group(0, "a", &[]), group(1, "b", &[]), group(2, "c", &[])];
+        groups[2].pinned = true;
+        assert_eq!(sorted(Order::Creation, &groups), [2, 0, 1]);
+        assert_eq!(sorted(Order::Name, &groups), [2, 0, 1]);
+    }
+
+    #[test]
+    fn throughput_switches_from_lines_to_amounts() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut throughput = Throughput::default();
+        for i in 0..10 {
+            throughput.record(at(i * 300), None);
+        }
+        assert!(!throughput.uses_amount());
+        assert!(throughput.rate(at(3000)) > 0.0);
+        throughput.record(at(3000), Some(2));
+        throughput.record(at(3300), None);
+        throughput.record(at(3600), Some(4));
+        assert!(throughput.uses_amount());
+        assert_eq!(throughput.total(), 6);
+        let rate = throughput.rate(at(4000));
+        assert!(rate > 0.0 && rate <= 6.0, "{rate}");
+    }
+
+    #[test]
+    fn lines_are_evicted_in_batches() {
+        let ok = Status::ok();
+        let mut full = group(0, "a", &(0..8).map(|t| (t, ok)).collect::<Vec<_>>());
+        full.max_lines = Some(8);
+        full.evict_lines();
+        assert_eq!(full.lines.len(), 8);
+        let annotation = Annotation::new("note", Level::Info);
+        full.annotations.insert(crate::LineId(1), vec![annotation.clone()]);
+        full.annotations.insert(crate::LineId(7), vec![annotation]);
+        full.lines.append(&mut group(0, "a", &[(8, ok)]).state.lines);
+        full.evict_lines();
+        let timestamps = full.lines.iter().map(|t| t.timestamp.0).collect::<Vec<_>>();
+        assert_eq!(timestamps, [3, 4, 5, 6, 7, 8]);
+        assert_eq!(full.annotations.keys().copied().collect::<Vec<_>>(), [crate::LineId(7)]);
+    }
+
+    #[test]
+    fn line_counter_sums_the_seconds_of_the_window() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let at = |millis| start + Duration::from_millis(millis);
+        let log = |status, level| Log { content: default(), status, level };
+        let mut counter = LineCounter::default();
+        counter.record(at(0), &log(Status::ok(), Level::Info));
+        counter.record(at(500), &log(Status::ok(), Level::Error));
+        counter.record(at(5_000), &log(Status::error(), Level::Info));
+        counter.record(at(100_000), &log(Status::ok(), Level::Info));
+        let stats = |now, secs| {
+            let WindowStats { lines, errors } = counter.stats(at(now), Duration::from_secs(secs));
+            (lines, errors)
+        };
+        assert_eq!(stats(5_999, 1), (1, 1));
+        assert_eq!(stats(5_999, 0), (1, 1));
+        assert_eq!(stats(5_000, 10), (3, 2));
+        assert_eq!(stats(100_000, 3600), (4, 2));
+        counter.record(at(700_000), &log(Status::ok(), Level::Info));
+        assert_eq!(counter.buckets.len(), 1);
+        assert_eq!(counter.stats(at(700_000), MAX_STATS_WINDOW).lines, 1);
+    }
+
+    #[test]
+    fn scroll_anchor_stays_on_its_line_when_lines_are_evicted() {
+        let ok = Status::ok();
+        let mut data = group(0, "a", &(0..8).map(|t| (t, ok)).collect::<Vec<_>>());
+        for line in &mut data.lines {
+            line.log.content = format!("line {}", line.timestamp.0);
+        }
+        data.max_lines = Some(8);
+        data.scroll = Some(crate::LineId(5));
+        let top = |data: &Group| {
+            let view = LineRange { data: &data.state, next_line: None };
+            let index = view.scroll_index()?;
+            let lines = view.displayed_lines();
+            (index < lines.len()).then(|| (index, lines.at(index).log.content.clone()))
+        };
+        assert_eq!(top(&data), Some((5, "line 5".to_string())));
+        let mut more = group(0, "a", &[(8, ok)]);
+        more.lines[0].log.content = "line 8".into();
+        data.lines.append(&mut more.state.lines);
+        data.evict_lines();
+        assert_eq!(top(&data), Some((2, "line 5".to_string())));
+        data.scroll = Some(crate::LineId(1));
+        assert_eq!(top(&data), Some((0, "line 3".to_string())));
+    }
+
+    #[test]
+    fn order_cycles_through_all_orders() {
+        let mut order = Order::default();
+        let mut seen = vec![order];
+        while order.next() != Order::default() {
+            order = order.next();
+            seen.push(order);
+        }
+        assert_eq!(seen.len(), 4);
+    }
+
+    #[test]
+    fn activity_counts_the_lines_per_second() {
+        let at = |millis: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(1_000_000 + millis);
+        let mut activity = Activity::default();
+        for millis in [0, 0, 0, 500, 2000, 2999] {
+            activity.record(at(millis));
         }
+        assert_eq!(activity.counts(at(2900), 4), [0.0, 4.0, 0.0, 2.0]);
+        assert_eq!(activity.counts(at(0), 2), [0.0, 4.0]);
+        assert_eq!(activity.counts(at(0), 0), [0.0; 0]);
+        assert_eq!(activity.lines_per_second(at(3000)), 2.0);
+        assert_eq!(activity.lines_per_second(at(4000)), 0.0);
+        activity.record(at(60_000));
+        assert_eq!(activity.counts(at(60_000), 61)[..3], [0.0, 0.0, 2.0]);
     }
 }
@@ -0,0 +1,130 @@
+use crate::prelude::*;
+
+use crate::SharedLogger;
+use std::thread;
+use std::time::SystemTime;
+
+// =================
+// === TuiHandle ===
+// =================
+
+/// Handle of the UI running on a dedicated thread, see [`SharedLogger::spawn`].
+///
+/// Dropping the handle without joining requests the UI to quit and waits for its thread, so that
+/// the terminal is always restored before the handle is gone.
+#[derive(Debug)]
+pub struct TuiHandle {
+    logger: SharedLogger,
+    thread: Option<thread::JoinHandle<Result>>,
+}
+
+impl TuiHandle {
+    /// Requests the UI to quit before drawing its next frame.
+    pub fn request_quit(&self) {
+        self.logger.modify(|logger| logger.quit_requested = true).ok();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Waits for the UI to quit, which happens when the user quits it or after
+    /// [`Self::request_quit`], and returns its result.
+    pub fn join(mut self) -> Result {
+        self.join_thread()
+    }
+
+    fn join_thread(&mut self) -> Result {
+        let Some(thread) = self.thread.take() else { return Ok(()) };
+        thread.join().map_err(|_| anyhow!("The UI thread panicked."))?
+    }
+}
+
+impl Drop for TuiHandle {
+    fn drop(&mut self) {
+        if self.is_running() {
+            self.request_quit();
+        }
+        self.join_thread().ok();
+    }
+}
+
+impl SharedLogger {
+    /// Runs [`Self::main`] on a dedicated thread.
+    pub fn spawn(&self, enabled: bool) -> Result<TuiHandle> {
+        let logger = self.clone();
+        let thread = thread::Builder::new()
+            .name("lmux".into())
+            .spawn(move || logger.main(enabled))?;
+        let logger = self.clone();
+        let thread = Some(thread);
+        Ok(TuiHandle { logger, thread })
+    }
+}
+
+// ===================
+// === RenderGuard ===
+// ===================
+
+/// Suppresses drawing of the UI while alive, see [`SharedLogger::freeze_rendering`].
+#[derive(Debug)]
+#[must_use]
+pub struct RenderGuard {
+    logger: SharedLogger,
+}
+
+impl Drop for RenderGuard {
+    fn drop(&mut self) {
+        self.logger.modify(|logger| {
+            logger.frozen = logger.frozen.saturating_sub(1);
+            if logger.frozen == 0 {
+                logger.frozen_at = None;
+                logger.redraw = true;
+            }
+        }).ok();
+    }
+}
+
+impl SharedLogger {
+    /// Stops drawing the UI until the returned guard is dropped, so that bulk updates are not
+    /// shown half-done. Input is still handled. Guards can be nested, and the UI is fully redrawn
+    /// when the last one is dropped. Rendering resumes after [`crate::MAX_FREEZE_TIME`] even if a
+    /// guard is leaked.
+    pub fn freeze_rendering(&self) -> RenderGuard {
+        self.modify(|logger| {
+            let now = SystemTime::now();
+            // A guard created after the leaked ones timed out starts a new freeze.
+            if !logger.is_frozen(now) {
+                logger.frozen_at = Some(now);
+            }
+            logger.frozen += 1;
+        }).ok();
+        RenderGuard { logger: self.clone() }
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_guards_freeze_until_the_last_is_dropped() -> Result {
+        let logger = SharedLogger::default();
+        let is_frozen = |at: SystemTime| logger.modify(|l| l.is_frozen(at));
+        let outer = logger.freeze_rendering();
+        let inner = logger.freeze_rendering();
+        let now = SystemTime::now();
+        drop(outer);
+        assert!(is_frozen(now)?);
+        assert!(!is_frozen(now + crate::MAX_FREEZE_TIME)?);
+        logger.modify(|l| l.redraw = false)?;
+        drop(inner);
+        assert!(!is_frozen(now)?);
+        assert!(logger.modify(|l| l.redraw)?);
+        Ok(())
+    }
+}
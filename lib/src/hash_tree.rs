@@ -54,6 +54,132 @@ impl<K, V> HashTree<K, V> {
             child.get_or_insert_with(&path[1..], f)
         }
     }
+
+    /// Value of the longest prefix of the path having a value, including the path itself.
+    pub fn get_longest_prefix(&self, path: &[K]) -> Option<&V>
+    where K: Eq + Hash {
+        let child = path.first().and_then(|key| self.children.get(key));
+        child.and_then(|child| child.get_longest_prefix(&path[1..])).or(self.value.as_ref())
+    }
+
+    /// Takes the value at the exact path. The children of the node are left untouched, and so is
+    /// the node itself, even if it becomes empty. Use [`Self::prune`] to drop empty nodes.
+    pub fn remove(&mut self, path: &[K]) -> Option<V>
+    where K: Eq + Hash {
+        if path.is_empty() {
+            self.value.take()
+        } else {
+            let child_key = &path[0];
+            self.children.get_mut(child_key).and_then(|child| child.remove(&path[1..]))
+        }
+    }
+
+    /// Drops all child subtrees containing no values. The order of the remaining children is
+    /// preserved.
+    pub fn prune(&mut self) {
+        self.children.retain(|_, child| {
+            child.prune();
+            child.value.is_some() || !child.children.is_empty()
+        });
+    }
+}
+
+// =============
+// === Entry ===
+// =============
+
+/// View into a single path of a [`HashTree`], which may either be occupied or vacant.
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    path: Vec<K>,
+    value: &'a mut V,
+}
+
+/// Vacant path. The nodes on the path are created only when a value is inserted.
+pub struct VacantEntry<'a, K, V> {
+    path: Vec<K>,
+    /// Deepest existing node on the path.
+    node: &'a mut HashTree<K, V>,
+    /// Depth of [`Self::node`] on the path.
+    depth: usize,
+}
+
+impl<K, V> HashTree<K, V>
+where K: Clone + Eq + Hash {
+    pub fn entry(&mut self, path: &[K]) -> Entry<'_, K, V> {
+        self.entry_at(path, 0)
+    }
+
+    fn entry_at(&mut self, path: &[K], depth: usize) -> Entry<'_, K, V> {
+        if depth == path.len() {
+            let path = path.to_vec();
+            match self.value {
+                Some(ref mut value) => Entry::Occupied(OccupiedEntry { path, value }),
+                None => Entry::Vacant(VacantEntry { path, node: self, depth }),
+            }
+        } else if let Some(index) = self.children.get_index_of(&path[depth]) {
+            self.children[index].entry_at(path, depth + 1)
+        } else {
+            let path = path.to_vec();
+            Entry::Vacant(VacantEntry { path, node: self, depth })
+        }
+    }
+}
+
+impl<'a, K, V> Entry<'a, K, V>
+where K: Clone + Eq + Hash {
+    /// Full path of the entry.
+    pub fn key_path(&self) -> &[K] {
+        match self {
+            Self::Occupied(entry) => entry.key_path(),
+            Self::Vacant(entry) => entry.key_path(),
+        }
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        matches!(self, Self::Occupied(_))
+    }
+
+    pub fn or_insert_with(self, f: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(f()),
+        }
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key_path(&self) -> &[K] {
+        &self.path
+    }
+
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V>
+where K: Clone + Eq + Hash {
+    pub fn key_path(&self) -> &[K] {
+        &self.path
+    }
+
+    /// Inserts the value, creating all missing nodes on the path.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.node.get_or_insert_with(&self.path[self.depth..], || value)
+    }
 }
 
 // === Iterator for &HashTree ===
@@ -97,6 +223,38 @@ where K: Eq + Hash {
     pub fn iter(&'a self) -> Iter<'a, K, V> {
         self.into_iter()
     }
+
+    /// Node at the given path, if it exists.
+    pub fn subtree(&'a self, prefix: &[K]) -> Option<&'a HashTree<K, V>> {
+        if prefix.is_empty() {
+            Some(self)
+        } else {
+            self.children.get(&prefix[0]).and_then(|child| child.subtree(&prefix[1..]))
+        }
+    }
+
+    /// Iterates over all values at or under the prefix, yielding their full paths.
+    pub fn iter_prefix(&'a self, prefix: &'a [K]) -> Iter<'a, K, V> {
+        let stack = self.subtree(prefix)
+            .map(|subtree| (subtree, prefix.iter().collect()))
+            .into_iter()
+            .collect();
+        Iter { stack }
+    }
+
+    /// Iterates over all values at or under the prefix.
+    pub fn descendant_values(&'a self, prefix: &'a [K]) -> impl Iterator<Item = &'a V> {
+        self.iter_prefix(prefix).map(|(_, value)| value)
+    }
+
+    /// Number of stored values.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
 }
 
 // === Iterator for &mut HashTree ===
@@ -181,3 +339,114 @@ where K: Clone + Eq + Hash {
         }
     }
 }
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Tree = HashTree<&'static str, usize>;
+
+    fn tree(values: &[(&[&'static str], usize)]) -> Tree {
+        let mut tree = Tree::new();
+        for (path, value) in values {
+            *tree.get_or_insert_with(path, || 0) = *value;
+        }
+        tree
+    }
+
+    /// Values with their paths joined by dots, in the iteration order.
+    fn entries<'a>(iter: impl Iterator<Item = (Vec<&'a &'static str>, &'a usize)>)
+    -> Vec<(String, usize)> {
+        iter.map(|(path, value)| (path.into_iter().copied().collect::<Vec<_>>().join("."), *value))
+            .collect()
+    }
+
+    #[test]
+    fn iteration_skips_removed_values_and_pruned_nodes() {
+        let mut tree = tree(&[
+            (&["a"], 1), (&["a", "b"], 2), (&["a", "b", "c"], 3), (&["d", "e"], 4), (&["f"], 5)
+        ]);
+        assert_eq!(tree.remove(&["a", "b"]), Some(2));
+        assert_eq!(tree.remove(&["a", "b"]), None);
+        assert_eq!(tree.remove(&["d", "e"]), Some(4));
+        assert_eq!(tree.remove(&["x", "y"]), None);
+        let expected = [("a".into(), 1), ("a.b.c".into(), 3), ("f".into(), 5)];
+        assert_eq!(entries(tree.iter()), expected);
+        assert!(tree.subtree(&["d", "e"]).is_some());
+        tree.prune();
+        assert_eq!(entries(tree.iter()), expected);
+        assert!(tree.subtree(&["d"]).is_none());
+        assert!(tree.subtree(&["a", "b"]).is_some());
+        assert_eq!(tree.children.keys().copied().collect::<Vec<_>>(), ["a", "f"]);
+        let mutable = tree.iter_mut().map(|(path, value)| (path.len(), *value));
+        assert_eq!(mutable.collect::<Vec<_>>(), [(1, 1), (3, 3), (1, 5)]);
+        let owned = tree.clone().into_iter().map(|(path, value)| (path.join("."), value));
+        assert_eq!(owned.collect::<Vec<_>>(), expected);
+        assert_eq!(tree.len(), 3);
+        for path in [&["a"][..], &["a", "b", "c"], &["f"]] {
+            tree.remove(path);
+        }
+        tree.prune();
+        assert!(tree.is_empty() && tree.children.is_empty());
+    }
+
+    #[test]
+    fn prefixes_include_nodes_with_values_and_children() {
+        let tree = tree(&[
+            (&["build"], 1), (&["build", "lib"], 2), (&["build", "lib", "core"], 3),
+            (&["build", "cli"], 4), (&["test", "lib", "unit"], 5),
+        ]);
+        assert_eq!(tree.len(), 5);
+        let build = tree.subtree(&["build"]);
+        assert_eq!(build.map(|t| (t.value, t.len())), Some((Some(1), 4)));
+        let lib = tree.subtree(&["build", "lib"]);
+        assert_eq!(lib.map(|t| (t.value, t.len())), Some((Some(2), 2)));
+        let test = tree.subtree(&["test", "lib"]);
+        assert_eq!(test.map(|t| (t.value, t.len())), Some((None, 1)));
+        assert!(tree.subtree(&["build", "docs"]).is_none());
+        let expected = [("build.lib".into(), 2), ("build.lib.core".into(), 3)];
+        assert_eq!(entries(tree.iter_prefix(&["build", "lib"])), expected);
+        assert_eq!(tree.descendant_values(&["build"]).copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(tree.descendant_values(&["test"]).copied().collect::<Vec<_>>(), [5]);
+        assert_eq!(tree.descendant_values(&["docs"]).count(), 0);
+        assert_eq!(tree.descendant_values(&[]).count(), tree.len());
+        assert_eq!(tree.get_longest_prefix(&["build", "lib", "alloc"]), Some(&2));
+        assert_eq!(tree.get_longest_prefix(&["test", "lib"]), None);
+    }
+
+    #[test]
+    fn entries_at_leaves_and_intermediate_nodes() {
+        let mut tree = tree(&[(&["a", "b", "c"], 3), (&["a"], 1)]);
+        match tree.entry(&["a", "b"]) {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key_path(), ["a", "b"]);
+                assert_eq!(*entry.insert(2), 2);
+            }
+            Entry::Occupied(_) => unreachable!(),
+        }
+        match tree.entry(&["a"]) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!((entry.key_path(), *entry.get()), (&["a"][..], 1));
+                *entry.get_mut() += 10;
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        let leaf = tree.entry(&["a", "b", "c"]);
+        assert!(leaf.is_occupied());
+        assert_eq!(*leaf.or_insert_with(|| 0), 3);
+        let missing = tree.entry(&["a", "b", "c", "d", "e"]);
+        assert!(!missing.is_occupied());
+        assert_eq!(missing.key_path(), ["a", "b", "c", "d", "e"]);
+        assert!(tree.subtree(&["a", "b", "c", "d"]).is_none());
+        assert_eq!(*tree.entry(&["a", "b", "c", "d", "e"]).or_insert_with(|| 5), 5);
+        assert!(tree.subtree(&["a", "b", "c", "d"]).is_some_and(|t| t.value.is_none()));
+        let expected = [
+            ("a".into(), 11), ("a.b".into(), 2), ("a.b.c".into(), 3), ("a.b.c.d.e".into(), 5)
+        ];
+        assert_eq!(entries(tree.iter()), expected);
+    }
+}
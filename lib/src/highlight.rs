@@ -0,0 +1,124 @@
+use crate::prelude::*;
+
+use crate::format;
+use crossterm::style::ContentStyle;
+use regex::Regex;
+use std::borrow::Cow;
+
+pub use crossterm::style::ContentStyle as HighlightStyle;
+
+// ============
+// === Rule ===
+// ============
+
+/// Styling applied to the parts of log lines matching the regex.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub regex: Regex,
+    pub style: ContentStyle,
+}
+
+impl Rule {
+    pub fn new(pattern: &str, style: ContentStyle) -> Result<Self> {
+        let regex = Regex::new(pattern)?;
+        Ok(Self { regex, style })
+    }
+}
+
+// =============
+// === Apply ===
+// =============
+
+/// Styles the parts of the line matched by the rules. If matches of multiple rules overlap, the
+/// rule listed first wins. The rules match the text without its ANSI escape sequences, and the
+/// styles of the line are restored after every match.
+pub fn apply<'a, 't>(rules: impl IntoIterator<Item = &'a Rule>, line: &'t str) -> Cow<'t, str> {
+    let visible = Visible::new(line);
+    let mut spans: Vec<(usize, usize, &ContentStyle)> = Vec::new();
+    for rule in rules {
+        for found in rule.regex.find_iter(&visible.text).filter(|found| !found.is_empty()) {
+            let overlaps = spans.iter().any(|t| found.start() < t.1 && t.0 < found.end());
+            if !overlaps {
+                spans.push((found.start(), found.end(), &rule.style));
+            }
+        }
+    }
+    if spans.is_empty() {
+        return Cow::Borrowed(line);
+    }
+    spans.sort_by_key(|t| t.0);
+    let mut out = String::with_capacity(line.len());
+    let mut last_end = 0;
+    for (start, end, style) in spans {
+        let (start, end) = (visible.offset(start), visible.offset(end - 1) + 1);
+        out.push_str(&line[last_end..start]);
+        out.push_str(&style.apply(&line[start..end]).to_string());
+        out.push_str(&format::active_styles(&line[..end]));
+        last_end = end;
+    }
+    out.push_str(&line[last_end..]);
+    Cow::Owned(out)
+}
+
+// ===============
+// === Visible ===
+// ===============
+
+/// Text of a line without its ANSI escape sequences.
+#[derive(Debug)]
+struct Visible<'t> {
+    text: Cow<'t, str>,
+    /// Byte offsets in the line of the bytes of the text, if the line has escape sequences.
+    offsets: Option<Vec<usize>>,
+}
+
+impl<'t> Visible<'t> {
+    fn new(line: &'t str) -> Self {
+        if !line.contains('\x1b') {
+            return Self { text: Cow::Borrowed(line), offsets: None };
+        }
+        let mut text = String::with_capacity(line.len());
+        let mut offsets = Vec::with_capacity(line.len());
+        let mut chars = line.char_indices().peekable();
+        while let Some((offset, char)) = chars.next() {
+            if char == '\x1b' {
+                if chars.next_if(|t| t.1 == '[').is_some() {
+                    chars.by_ref().find(|t| ('@'..='~').contains(&t.1));
+                }
+            } else {
+                text.push(char);
+                offsets.extend(offset..offset + char.len_utf8());
+            }
+        }
+        Self { text: Cow::Owned(text), offsets: Some(offsets) }
+    }
+
+    /// Byte offset in the line of the byte of the text.
+    fn offset(&self, index: usize) -> usize {
+        self.offsets.as_ref().map_or(index, |offsets| offsets[index])
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::style::Stylize;
+
+    #[test]
+    fn matches_skip_escape_sequences_and_restore_styles() -> Result {
+        let rules = [Rule::new(r"\d+", ContentStyle::new().bold())?];
+        let line = "\x1b[31mfailed 12 of 40\x1b[0m tests";
+        let highlighted = apply(&rules, line);
+        let bold = |text: &str| ContentStyle::new().bold().apply(text).to_string();
+        let red = "\x1b[31m";
+        let (first, second) = (bold("12"), bold("40"));
+        let expected = format!("{red}failed {first}{red} of {second}{red}\x1b[0m tests");
+        assert_eq!(highlighted, expected);
+        assert_eq!(apply(&rules, "no digits"), "no digits");
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::prelude::*;
+
+use crate::group;
+use crate::group::StatusTag;
+use std::ops::Range;
+use std::time::Duration;
+use std::time::SystemTime;
+
+// ===========
+// === Run ===
+// ===========
+
+/// Consecutive history entries of one group with the same status tag, pushed within
+/// [`RUN_TIME_SPAN`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Run {
+    group: group::Id,
+    tag: StatusTag,
+    /// Index of the first entry of the run.
+    start: usize,
+    /// Time of the first entry of the run, if known. Entries restored from snapshots have no
+    /// times.
+    time: Option<SystemTime>,
+}
+
+/// Maximum time between the first and the last entry of a run, which bounds the error of
+/// [`History::time_at`].
+pub const RUN_TIME_SPAN: Duration = Duration::from_secs(1);
+
+// ===============
+// === History ===
+// ===============
+
+/// Group and status tag of every pushed line, indexed by [`crate::LineId`] minus
+/// [`History::start`]. Consecutive lines of one group are stored as a single run, so that pushing
+/// lines to a group with fast output does not grow the history.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    runs: Vec<Run>,
+    /// Line id of the first entry. Lines pushed before the logger was reset have no entries.
+    start: usize,
+    len: usize,
+}
+
+impl History {
+    /// Empty history whose first entry will be the line with the given id.
+    pub fn starting_at(start: usize) -> Self {
+        Self { start, ..default() }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Id of the line the next entry will belong to.
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = default();
+    }
+
+    /// Drops the oldest entries, keeping the last `len` ones.
+    pub fn keep_last(&mut self, len: usize) {
+        let count = self.len.saturating_sub(len);
+        if count == 0 { return }
+        let first_run = self.runs.partition_point(|run| run.start <= count).saturating_sub(1);
+        self.runs.drain(..first_run);
+        for run in &mut self.runs {
+            run.start = run.start.saturating_sub(count);
+        }
+        self.start += count;
+        self.len -= count;
+    }
+
+    /// Pushes an entry of unknown time.
+    pub fn push(&mut self, group: group::Id, tag: StatusTag) {
+        self.push_run(group, tag, None)
+    }
+
+    pub fn push_at(&mut self, group: group::Id, tag: StatusTag, time: SystemTime) {
+        self.push_run(group, tag, Some(time))
+    }
+
+    fn push_run(&mut self, group: group::Id, tag: StatusTag, time: Option<SystemTime>) {
+        let extends_last = self.runs.last().is_some_and(|run| {
+            let same_span = match (run.time, time) {
+                (Some(start), Some(time)) =>
+                    time.duration_since(start).unwrap_or_default() < RUN_TIME_SPAN,
+                (start, time) => start.is_none() && time.is_none(),
+            };
+            run.group == group && run.tag == tag && same_span
+        });
+        if !extends_last {
+            self.runs.push(Run { group, tag, start: self.len, time });
+        }
+        self.len += 1;
+    }
+
+    /// Approximate time of the entry, the time of the first entry of its run.
+    pub fn time_at(&self, index: usize) -> Option<SystemTime> {
+        if index >= self.len { return None }
+        let run = self.runs.partition_point(|run| run.start <= index).checked_sub(1)?;
+        self.runs[run].time
+    }
+
+    /// Entries in the range, clamped to the history length.
+    pub fn range(&self, range: Range<usize>) -> Vec<(group::Id, StatusTag)> {
+        let end = range.end.min(self.len);
+        let first_run = self.runs.partition_point(|run| run.start <= range.start).saturating_sub(1);
+        let mut out = Vec::with_capacity(end.saturating_sub(range.start));
+        for (i, run) in self.runs.iter().enumerate().skip(first_run) {
+            let run_end = self.runs.get(i + 1).map_or(self.len, |next| next.start);
+            let start = run.start.max(range.start);
+            if start >= end { break }
+            out.extend(std::iter::repeat_n((run.group, run.tag), run_end.min(end) - start));
+        }
+        out
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (group::Id, StatusTag)> + '_ {
+        self.runs.iter().enumerate().flat_map(|(i, run)| {
+            let run_end = self.runs.get(i + 1).map_or(self.len, |next| next.start);
+            std::iter::repeat_n((run.group, run.tag), run_end - run.start)
+        })
+    }
+}
+
+impl FromIterator<(group::Id, StatusTag)> for History {
+    fn from_iter<T: IntoIterator<Item = (group::Id, StatusTag)>>(iter: T) -> Self {
+        let mut history = Self::default();
+        for (group, tag) in iter {
+            history.push(group, tag);
+        }
+        history
+    }
+}
@@ -0,0 +1,111 @@
+//! Export of a drawn frame with its colors to a standalone HTML file, see
+//! [`crate::SharedLogger::export_html`].
+
+use crate::prelude::*;
+
+use crate::ansi;
+use crate::format;
+use crate::terminal;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Text color of the characters without a foreground color.
+const DEFAULT_FG: (u8, u8, u8) = (229, 229, 229);
+
+/// Background color of the page and of the characters without a background color.
+const DEFAULT_BG: (u8, u8, u8) = (0, 0, 0);
+
+// ================
+// === Document ===
+// ================
+
+/// HTML document showing the rows of a frame in a `pre` block sized to the terminal. The rows are
+/// cut to the terminal width, and the styles of their spans are inlined.
+pub fn document<'t>(rows: impl IntoIterator<Item = &'t str>, size: terminal::Size) -> String {
+    let mut body = String::new();
+    for row in rows.into_iter().take(size.rows) {
+        for span in ansi::spans(&format::truncate(row, size.cols)) {
+            let text = escape(&span.text);
+            match css(&span.style) {
+                Some(css) => write!(body, "<span style=\"{css}\">{text}</span>").ok(),
+                None => write!(body, "{text}").ok(),
+            };
+        }
+        body.push('\n');
+    }
+    let (cols, rows) = (size.cols, size.rows);
+    let fg = hex(DEFAULT_FG);
+    let bg = hex(DEFAULT_BG);
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>lmux {cols}x{rows}</title>\n\
+        </head>\n\
+        <body style=\"margin:0;background:{bg}\">\n\
+        <pre style=\"margin:0;padding:1ch;width:{cols}ch;min-height:{rows}lh;color:{fg};\
+        background:{bg};font-family:monospace;line-height:1.2\">\n\
+        {body}</pre>\n\
+        </body>\n\
+        </html>\n"
+    )
+}
+
+/// Inline style of the span, or `None` if it has no attributes.
+fn css(style: &ansi::Style) -> Option<String> {
+    if *style == default() {
+        return None;
+    }
+    let fg = style.fg.map(ansi::Color::rgb);
+    let bg = style.bg.map(ansi::Color::rgb);
+    let (fg, bg) = if style.reverse {
+        (Some(bg.unwrap_or(DEFAULT_BG)), Some(fg.unwrap_or(DEFAULT_FG)))
+    } else {
+        (fg, bg)
+    };
+    let mut css = String::new();
+    if let Some(fg) = fg { write!(css, "color:{};", hex(fg)).ok(); }
+    if let Some(bg) = bg { write!(css, "background:{};", hex(bg)).ok(); }
+    if style.bold { css.push_str("font-weight:bold;") }
+    if style.dim { css.push_str("opacity:0.6;") }
+    if style.italic { css.push_str("font-style:italic;") }
+    if style.underline { css.push_str("text-decoration:underline;") }
+    Some(css)
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Path of an exported frame: `lmux-frame-<timestamp>.html` in the current directory.
+pub fn default_path() -> PathBuf {
+    let since_epoch = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+    let timestamp = since_epoch.unwrap_or_default().as_secs();
+    PathBuf::from(format!("lmux-frame-{timestamp}.html"))
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_inlines_the_styles_and_escapes_the_text() {
+        let rows = ["a<b>&c", "\x1b[7mreversed\x1b[0m plain", "cut after ten", "dropped"];
+        let html = document(rows, terminal::Size { cols: 10, rows: 3 });
+        let reversed = "<span style=\"color:#000000;background:#e5e5e5;\">reversed</span>";
+        assert!(html.contains("<title>lmux 10x3</title>"));
+        assert!(html.contains("a&lt;b&gt;&amp;c\n"));
+        assert!(html.contains(&format!("{reversed} p")));
+        assert!(!html.contains("plain") && !html.contains("dropped"));
+    }
+}
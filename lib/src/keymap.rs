@@ -0,0 +1,87 @@
+use crate::prelude::*;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use std::collections::HashMap;
+
+// ==============
+// === Keymap ===
+// ==============
+
+/// Remapping of the keys pressed in the UI to the keys of the built-in bindings, e.g. binding `x`
+/// to `q` makes `x` quit. Unbound keys are handled as they are.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<char, char>,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Makes `key` act as `action`, the key of a built-in binding.
+    pub fn bind(mut self, key: char, action: char) -> Self {
+        self.bindings.insert(key, action);
+        self
+    }
+
+    pub fn map(&self, mut event: KeyEvent) -> KeyEvent {
+        if let KeyCode::Char(key) = event.code && let Some(action) = self.bindings.get(&key) {
+            event.code = KeyCode::Char(*action);
+        }
+        event
+    }
+}
+
+// ==================
+// === QuitPolicy ===
+// ==================
+
+/// Keys quitting the UI. The application can always quit it, see
+/// [`crate::TuiHandle::request_quit`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuitPolicy {
+    /// `q` and `Ctrl+C`.
+    #[default]
+    Keys,
+    /// `Ctrl+C` only, so that `q` is not pressed by accident.
+    Interrupt,
+    /// No key quits the UI.
+    Never,
+}
+
+impl QuitPolicy {
+    pub fn quits(self, event: KeyEvent) -> bool {
+        let interrupt = event.code == KeyCode::Char('c')
+            && event.modifiers.contains(KeyModifiers::CONTROL);
+        match self {
+            Self::Keys => interrupt || event.code == KeyCode::Char('q'),
+            Self::Interrupt => interrupt,
+            Self::Never => false,
+        }
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_keys_quit_according_to_policy() {
+        let keymap = Keymap::new().bind('x', 'q');
+        let x = keymap.map(KeyEvent::from(KeyCode::Char('x')));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(x.code, KeyCode::Char('q'));
+        assert_eq!(keymap.map(KeyEvent::from(KeyCode::Char('y'))).code, KeyCode::Char('y'));
+        assert!(QuitPolicy::Keys.quits(x));
+        assert!(!QuitPolicy::Interrupt.quits(x));
+        assert!(QuitPolicy::Interrupt.quits(ctrl_c));
+        assert!(!QuitPolicy::Never.quits(ctrl_c));
+    }
+}
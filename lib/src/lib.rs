@@ -1,9 +1,39 @@
+pub mod ansi;
+pub mod builder;
+#[cfg(feature = "serde")]
+pub mod cast;
+pub mod clipboard;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod context_menu;
+pub mod format;
 pub mod framebuffer;
+pub mod fuzzy;
 pub mod group;
+pub mod handle;
 pub mod hash_tree;
+pub mod history;
+pub mod html;
+pub mod keymap;
+pub mod location;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "regex")]
+pub mod highlight;
+pub mod pattern;
+pub mod palette;
 pub mod prelude;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "serde")]
+pub mod snapshot;
+#[cfg(feature = "regex")]
+pub mod status_rule;
 pub mod terminal;
+pub mod std_capture;
 pub mod style;
+pub mod subscription;
+pub mod tee;
 pub mod widget;
 
 use crate::prelude::*;
@@ -11,10 +41,39 @@ use crate::prelude::*;
 use crate::hash_tree::HashTree;
 use crossterm::style::Stylize;
 use group::Group;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::ptr;
+use std::sync::RwLock;
+use std::sync::atomic;
+use std::sync::atomic::AtomicUsize;
+use std::time::Duration;
+use std::time::Instant;
 use std::time::SystemTime;
+use unicode_segmentation::UnicodeSegmentation;
 
+pub use builder::Builder;
+#[cfg(feature = "config")]
+pub use config::Config;
+pub use framebuffer::RowKind;
+pub use group::Annotation;
 pub use group::Status;
+pub use group::Badge;
+pub use group::HeaderSpec;
+pub use handle::RenderGuard;
+pub use handle::TuiHandle;
+pub use keymap::Keymap;
+pub use keymap::QuitPolicy;
+pub use group::Level;
 pub use group::Log;
+pub use group::Order;
+pub use group::StallAction;
+pub use group::Template as GroupTemplate;
+pub use pattern::Pattern;
+pub use subscription::LineReceiver;
 
 // ==============
 // === LineId ===
@@ -29,6 +88,23 @@ impl LineId {
     pub fn inc(self) -> LineId {
         LineId(self.0 + 1)
     }
+
+    pub fn saturating_add(self, count: usize) -> LineId {
+        LineId(self.0.saturating_add(count))
+    }
+
+    pub fn saturating_sub(self, count: usize) -> LineId {
+        LineId(self.0.saturating_sub(count))
+    }
+
+    /// Id shifted by the offset, saturating at zero.
+    pub fn saturating_add_signed(self, offset: isize) -> LineId {
+        if offset >= 0 {
+            self.saturating_add(offset.unsigned_abs())
+        } else {
+            self.saturating_sub(offset.unsigned_abs())
+        }
+    }
 }
 
 // =================
@@ -43,8 +119,8 @@ pub struct LineRange<T> {
     pub next_line: Option<LineId>,
 }
 
-impl<'t, T> LineRange<&'t mut T> {
-    pub fn as_ref(&'t self) -> LineRange<&'t T> {
+impl<T> LineRange<&mut T> {
+    pub fn as_ref(&self) -> LineRange<&T> {
         LineRange { data: self.data, next_line: self.next_line }
     }
 }
@@ -59,742 +135,5723 @@ impl<T> LineRange<T> {
 // === Groups ===
 // ==============
 
-type Groups = LineRange<Vec<Group>>;
+/// All groups, stored by their [`group::Id`]. The display order is independent of the storage
+/// order and is applied only when querying the visible groups.
+#[derive(Debug, Default, Deref, DerefMut)]
+pub struct Groups {
+    #[deref]
+    #[deref_mut]
+    pub range: LineRange<Vec<Group>>,
+    pub order: group::Order,
+    /// Time after which successfully finished groups are hidden. Hiding is disabled if not set.
+    pub hide_finished_after: Option<Duration>,
+    /// Show the hidden groups anyway.
+    pub show_hidden: bool,
+    /// Group displayed alone, taking all the space of the groups.
+    pub zoomed: Option<group::Id>,
+}
+
+/// Checks whether the group is included in [`Groups::nonempty`]. In the history view, groups which
+/// did not start yet are shown too, so that groups do not move while scrubbing.
+fn is_shown(group: &LineRange<&Group>, zoomed: Option<group::Id>, hide_after: Option<Duration>)
+-> bool {
+    let starts_later = group.next_line.is_some() && !group.lines.is_empty();
+    zoomed.is_none_or(|id| group.id == id)
+        && (group.show_when_empty || !group.is_pending() || starts_later)
+        && !group.is_hidden(hide_after)
+}
 
 impl Groups {
+    /// Visible groups in the display order. Groups without lines are included only if they are
+    /// shown when empty, or if they get lines later than the time shown in the history view.
     pub fn nonempty_mut(&mut self) -> Vec<LineRange<&'_ mut Group>> {
-        self.data
+        let order = self.order;
+        let next_line = self.range.next_line;
+        let hide_after = self.hide_after();
+        let zoomed = self.zoomed;
+        let mut groups: Vec<_> = self.range.data
             .iter_mut()
-            .map(|data| LineRange { data, next_line: self.next_line })
-            .filter(|g| !g.as_ref().state().view_lines().is_empty())
-            .collect()
+            .map(|data| LineRange { data, next_line })
+            .filter(|g| is_shown(&g.as_ref(), zoomed, hide_after))
+            .collect();
+        groups.sort_by(|a, b| order.compare(&a.as_ref(), &b.as_ref()));
+        groups
     }
 
+    /// Visible groups in the display order. Groups without lines are included only if they are
+    /// shown when empty, or if they get lines later than the time shown in the history view.
     pub fn nonempty(&self) -> Vec<LineRange<&'_ Group>> {
-        self.data
-            .iter()
-            .map(|data| LineRange { data, next_line: self.next_line })
-            .filter(|g| !g.state().view_lines().is_empty())
-            .collect()
+        self.shown(self.zoomed)
     }
-}
-
-// ==============
-// === Logger ===
-// ==============
-
-#[derive(Debug, Default)]
-pub struct Logger {
-    groups: Groups,
-    path_to_group_id: HashTree<String, group::Id>,
-    style: style::Any,
-    next_line_id: LineId,
-    frame_buffer: framebuffer::Framebuffer,
-    debug_lines: Vec<String>,
-    history: Vec<(group::Id, group::StatusTag)>,
-    disabled: bool,
-}
 
-impl Logger {
-    fn next_line_id(&mut self) -> LineId {
-        let line_id = self.next_line_id;
-        self.next_line_id = line_id.inc();
-        line_id
+    /// Groups of [`Self::nonempty`] as if no group was zoomed, e.g. to switch to another group
+    /// from the palette.
+    pub fn nonempty_unzoomed(&self) -> Vec<LineRange<&'_ Group>> {
+        self.shown(None)
     }
-}
 
-impl Logger {
-    pub fn create_group(&mut self, selector: &[String]) -> group::Id {
-        *self.path_to_group_id.get_or_insert_with(selector, || {
-            let group_index = self.groups.len();
-            let group_id = group::Id(group_index);
-            let mut group = Group::new(group_id);
-            group.header = selector.join("::");
-            self.groups.push(group);
-            group_id
-        })
+    fn shown(&self, zoomed: Option<group::Id>) -> Vec<LineRange<&'_ Group>> {
+        let hide_after = self.hide_after();
+        let mut groups: Vec<_> = self.range.data
+            .iter()
+            .map(|data| LineRange { data, next_line: self.next_line })
+            .filter(|g| is_shown(g, zoomed, hide_after))
+            .collect();
+        groups.sort_by(|a, b| self.order.compare(a, b));
+        groups
     }
 
-    pub fn group_mut(&mut self, selector: impl GroupSelector) -> Result<LineRange<&'_ mut Group>> {
-        let next_line = self.groups.next_line;
-        GroupSelector::group_id(selector, self).map(|id|
-           LineRange { data: &mut self.groups[*id], next_line }
-        )
+    /// Number of nonempty groups hidden because they finished, regardless of [`Self::show_hidden`].
+    pub fn finished_hidden_count(&self) -> usize {
+        self.range.data
+            .iter()
+            .map(|data| LineRange { data, next_line: self.next_line })
+            .filter(|g| g.is_hidden(self.hide_finished_after))
+            .count()
     }
 
-    pub fn push_line(&mut self, selector: impl GroupSelector, log: Log) -> Result {
-        let group_id = GroupSelector::group_id(selector, self)?;
-        let time = SystemTime::now();
-        let timestamp = self.next_line_id();
-        let group = &mut self.groups[*group_id];
-        if self.disabled {
-            println!("[{}] {}", group.header, log.content)
+    /// Number of all groups, including the hidden ones, in every status category.
+    pub fn summary(&self) -> group::Summary {
+        let mut summary = group::Summary::default();
+        for data in &self.range.data {
+            summary.record(&LineRange { data, next_line: self.next_line });
         }
-        self.history.push((group_id, log.status.tag));
-        let line = group::Line { timestamp, time, log };
-        group.lines.push(line);
-        Ok(())
+        summary
     }
 
-    pub fn get_last_line(&mut self, selector: impl GroupSelector) -> Result<Option<&Log>> {
-        let group_id = GroupSelector::group_id(selector, self)?;
-        Ok(self.groups[*group_id].lines.last().map(|l| &l.log))
+    fn hide_after(&self) -> Option<Duration> {
+        if self.show_hidden { None } else { self.hide_finished_after }
     }
 
-    pub fn shift_selection(&mut self, shift: isize) {
-        let mut groups = self.groups.nonempty_mut();
-        if !groups.is_empty() {
-            let count = groups.len();
-            let border_ix = group::Id(if shift >= 0 { 0 } else { count.saturating_sub(1) });
-            let any_selected = groups.iter().any(|g| g.selected);
-            if !any_selected {
-                groups[*border_ix].selected = true;
-            } else {
-                let mut prev_selected = false;
-                if shift < 0 { groups.reverse() };
-                for group in &mut groups {
-                    swap(&mut prev_selected, &mut group.selected);
-                }
-                if prev_selected {
-                    groups[0].selected = true;
-                }
-            }
-        }
+    /// Zooms the group, or unzooms if any group is zoomed.
+    pub fn toggle_zoom(&mut self, group_id: group::Id) {
+        self.zoomed = if self.zoomed.is_some() { None } else { Some(group_id) };
     }
+}
 
-    pub fn shift_history(&mut self, shift: isize) {
-        let max = LineId(self.history.len());
-        let current = self.groups.next_line.unwrap_or(max);
-        let new = LineId(((*current as isize + shift).max(0) as usize).min(*max));
-        self.groups.next_line = if new == max { None } else { Some(new) };
+// ==============
+// === Notify ===
+// ==============
+
+/// Terminal notification emitted when a group becomes finished with an error.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Notify {
+    #[default]
+    Off,
+    /// Terminal bell, `BEL`.
+    Bell,
+    /// Desktop notification escape sequence, `OSC 9`, naming the failed group.
+    Osc9,
+    Both,
+}
+
+impl Notify {
+    pub fn bell(self) -> bool {
+        matches!(self, Self::Bell | Self::Both)
     }
 
-    pub fn scroll(&mut self, selector: impl GroupSelector, offset: isize) -> Result {
-        let group_id = selector.group_id(self)?;
-        let line_range = self.frame_buffer.group_to_group_lines.get(&group_id).copied();
-        let group = &mut self.groups[*group_id];
-        let line_count = line_range.map(|t| *t.1 - *t.0 + 1).unwrap_or_default();
-        let max = group.lines.len().saturating_sub(line_count);
-        let current_scroll = group.scroll.unwrap_or_else(|| *line_range.unwrap_or_default().0);
-        let new_scroll = if offset > 0 {
-            current_scroll.saturating_add(offset as usize).min(max)
-        } else {
-            current_scroll.saturating_sub((-offset) as usize)
-        };
-        group.scroll = (new_scroll != max).then_some(new_scroll);
-        Ok(())
+    pub fn osc9(self) -> bool {
+        matches!(self, Self::Osc9 | Self::Both)
     }
 }
 
-// ====================
-// === SharedLogger ===
-// ====================
+// ================
+// === Settings ===
+// ================
 
-#[derive(Clone, Debug, Default, Deref)]
-pub struct SharedLogger {
-    arc: Arc<Mutex<Logger>>,
-}
+/// Default number of frames drawn per second.
+pub const DEFAULT_FRAME_RATE: u32 = 60;
 
-static LOGGER: OnceLock<SharedLogger> = OnceLock::new();
+/// Default number of times per second the body of a group with fast output is refreshed.
+pub const DEFAULT_FAST_GROUP_REFRESH_RATE: u32 = 10;
 
-pub fn logger() -> &'static SharedLogger {
-    LOGGER.get_or_init(SharedLogger::default)
-}
+/// Default time the terminal size has to be stable for before the UI is redrawn.
+pub const DEFAULT_RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
 
-// =====================
-// === GroupSelector ===
-// =====================
+/// Time between frames while the terminal is not focused.
+pub const UNFOCUSED_FRAME_TIME: Duration = Duration::from_millis(500);
 
-pub trait GroupSelector {
-    fn group_id(self, logger: &mut Logger) -> Result<group::Id>;
+/// Time after which rendering resumes even if render guards are still alive, in case a guard was
+/// leaked, see [`SharedLogger::freeze_rendering`].
+pub const MAX_FREEZE_TIME: Duration = Duration::from_secs(10);
+
+/// Default number of frames between full repaints of the screen, see
+/// [`Settings::full_repaint_frames`].
+pub const DEFAULT_FULL_REPAINT_FRAMES: u32 = 60;
+
+/// Default time after which the first frame is drawn even if [`ready`] was not called, see
+/// [`Builder::defer_first_draw_until_ready`].
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Number of lines per second above which the tail of a group is refreshed at
+/// [`Settings::fast_group_refresh_rate`] instead of every frame.
+pub const FAST_GROUP_RATE: f32 = 1000.0;
+
+/// Time after the creation of a group without a header during which a placeholder is shown
+/// instead of its path, so that the header set right after its first line does not flicker.
+pub const HEADER_GRACE: Duration = Duration::from_millis(500);
+
+/// Time between frames when the adaptive frame rate is enabled and nothing happens.
+pub const IDLE_FRAME_TIME: Duration = Duration::from_millis(250);
+
+/// Time without new lines or input events after which the adaptive frame rate slows down.
+pub const IDLE_AFTER: Duration = Duration::from_secs(1);
+
+/// Maximum number of failed groups notified about in one frame. Failures past it are not
+/// notified, so that they do not pile up while no frames are drawn.
+const MAX_PENDING_NOTIFICATIONS: usize = 16;
+
+/// Fraction of [`Settings::history_limit`] dropped at once when the limit is exceeded.
+const HISTORY_EVICTION_DIVISOR: usize = 4;
+
+/// Logger-wide behavior settings.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// Staleness threshold applied to newly created groups. Groups are never stale if not set.
+    pub stale_after: Option<Duration>,
+    /// Stall timeout and action applied to newly created groups, see
+    /// [`group::State::stall_timeout`].
+    pub stall_timeout: Option<Duration>,
+    pub stall_action: group::StallAction,
+    /// If set, logging to an unknown group is an error instead of creating the group.
+    pub strict_groups: bool,
+    /// Value of [`group::State::show_when_empty`] for newly created groups.
+    pub show_empty_groups: bool,
+    /// Value of [`group::State::compact`] for newly created groups.
+    pub compact: bool,
+    /// Height of the debug panel.
+    pub debug_rows: usize,
+    /// Auto-collapse behavior of newly created groups.
+    pub auto_collapse: group::AutoCollapse,
+    /// Minimum displayed level of newly created groups.
+    pub min_level: Level,
+    /// Maximum number of frames drawn per second.
+    pub frame_rate: u32,
+    /// Slow down to [`IDLE_FRAME_TIME`] between frames if nothing happened for [`IDLE_AFTER`].
+    pub adaptive_frame_rate: bool,
+    /// Terminal notification emitted when a group becomes finished with an error.
+    pub notifications: Notify,
+    /// Show the number of groups by status above the groups.
+    pub show_summary: bool,
+    /// Show the last line of collapsed groups below their headers if it is an error.
+    pub show_collapsed_errors: bool,
+    /// Show the relative times of the history entries in a row below the history strip.
+    pub show_history_times: bool,
+    /// Time the terminal size has to be stable for before the UI is redrawn. Frames are not drawn
+    /// while resizing. Zero redraws on every resize.
+    pub resize_debounce: Duration,
+    /// Start measuring the duration of groups when they are created instead of at their first
+    /// line, see [`group::State::started_at`].
+    pub start_timers: bool,
+    /// Report mouse events to the UI instead of letting the terminal select text.
+    pub mouse_capture: bool,
+    /// Number of drawn frames after which the screen is cleared and fully repainted, so that the
+    /// output printed around the UI, e.g. by dependencies, does not corrupt it for long. Full
+    /// repaints happen only when requested if not set, see [`SharedLogger::force_redraw`].
+    pub full_repaint_frames: Option<u32>,
+    /// Query the cursor position before every frame, and repaint the screen if the cursor was
+    /// moved by output printed around the UI. Each query waits for the terminal to respond. The
+    /// cursor is not checked while the standard streams are captured, as the query is written to
+    /// the standard output, see [`SharedLogger::capture_std`].
+    pub check_cursor: bool,
+    /// Number of times per second the body of a group logging more than [`FAST_GROUP_RATE`]
+    /// lines per second is refreshed, so that its tail stays readable. Groups are never
+    /// throttled if not set, unless [`group::State::tail_refresh_rate`] is set.
+    pub fast_group_refresh_rate: Option<u32>,
+    pub theme: style::Theme,
+    /// Remapping of the keys pressed in the UI.
+    pub keymap: Keymap,
+    /// Keys quitting the UI.
+    pub quit_policy: QuitPolicy,
+    /// Maximum number of history entries. The oldest entries are dropped in batches once it is
+    /// exceeded. The history is unlimited if not set.
+    pub history_limit: Option<usize>,
+    /// Draw the UI on the main screen instead of the alternate one, so that its last frame stays
+    /// in the terminal after quitting.
+    pub inline_mode: bool,
 }
 
-impl GroupSelector for group::Id {
-    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
-        if self.0 >= logger.groups.len() {
-            return Err(anyhow!("Group index out of bounds: {}", self.0));
+impl Default for Settings {
+    fn default() -> Self {
+        let stale_after = None;
+        let stall_timeout = None;
+        let stall_action = default();
+        let strict_groups = false;
+        let show_empty_groups = false;
+        let compact = false;
+        let debug_rows = 5;
+        let auto_collapse = default();
+        let min_level = Level::Trace;
+        let frame_rate = DEFAULT_FRAME_RATE;
+        let adaptive_frame_rate = false;
+        let notifications = default();
+        let show_summary = false;
+        let show_collapsed_errors = false;
+        let show_history_times = false;
+        let resize_debounce = DEFAULT_RESIZE_DEBOUNCE;
+        let start_timers = false;
+        let mouse_capture = true;
+        let full_repaint_frames = Some(DEFAULT_FULL_REPAINT_FRAMES);
+        let check_cursor = false;
+        let fast_group_refresh_rate = Some(DEFAULT_FAST_GROUP_REFRESH_RATE);
+        let theme = default();
+        let keymap = default();
+        let quit_policy = default();
+        let history_limit = None;
+        let inline_mode = false;
+        Self {
+            stale_after, stall_timeout, stall_action, strict_groups, show_empty_groups, compact,
+            debug_rows, auto_collapse, min_level, frame_rate, adaptive_frame_rate, notifications,
+            show_summary, show_collapsed_errors, show_history_times, resize_debounce,
+            start_timers, mouse_capture, full_repaint_frames, check_cursor,
+            fast_group_refresh_rate, theme, keymap, quit_policy, history_limit, inline_mode
         }
-        Ok(self)
     }
 }
 
-impl GroupSelector for &[String] {
-    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
-        logger.path_to_group_id.get(self).copied()
-            .with_context(|| format!("Group not found: '{}'", self.join(".")))
-    }
+// ====================
+// === ErrorHandler ===
+// ====================
+
+/// Callback receiving errors of the simplified API. If not set, errors are displayed in the debug
+/// panel.
+#[derive(Clone)]
+pub struct ErrorHandler {
+    f: Arc<dyn Fn(Error) + Send + Sync>,
 }
 
-impl<const N: usize> GroupSelector for &[String; N] {
-    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
-        let slice: &[String] = self;
-        slice.group_id(logger)
+impl Debug for ErrorHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorHandler").finish()
     }
 }
 
+impl ErrorHandler {
+    pub fn new(f: impl Fn(Error) + Send + Sync + 'static) -> Self {
+        Self { f: Arc::new(f) }
+    }
 
-// ===========================
-// === GroupStringSelector ===
-// ===========================
+    pub fn call(&self, error: Error) {
+        (self.f)(error)
+    }
+}
 
-pub trait GroupStringSelector {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T;
+// =================
+// === ErrorHook ===
+// =================
+
+/// Callback observing errors of the simplified API, called in addition to the [`ErrorHandler`] or
+/// the debug panel. It is stored outside of the logger lock, so it is called even if the lock
+/// cannot be acquired.
+#[derive(Clone)]
+pub struct ErrorHook {
+    f: Arc<dyn Fn(&Error) + Send + Sync>,
 }
 
-impl GroupStringSelector for &[String] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(self)
+impl Debug for ErrorHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorHook").finish()
     }
 }
 
-impl GroupStringSelector for &[&str] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+impl ErrorHook {
+    pub fn new(f: impl Fn(&Error) + Send + Sync + 'static) -> Self {
+        Self { f: Arc::new(f) }
     }
-}
 
-impl GroupStringSelector for &[&String] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    pub fn call(&self, error: &Error) {
+        (self.f)(error)
     }
 }
 
-impl<const N: usize> GroupStringSelector for &[String; N] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(self)
-    }
+// ======================
+// === StatusListener ===
+// ======================
+
+/// Callback called with the group path and the new status when the status tag or the finished
+/// flag of a group changes.
+#[derive(Clone)]
+pub struct StatusListener {
+    /// Path prefix of the groups the listener is interested in.
+    prefix: Vec<String>,
+    finished_only: bool,
+    f: Arc<dyn Fn(&[String], Status) + Send + Sync>,
 }
 
-impl<const N: usize> GroupStringSelector for &[&str; N] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+impl Debug for StatusListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatusListener")
+            .field("prefix", &self.prefix)
+            .field("finished_only", &self.finished_only)
+            .finish()
     }
 }
 
-impl<const N: usize> GroupStringSelector for &[&String; N] {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+impl StatusListener {
+    pub fn new(
+        prefix: Vec<String>,
+        finished_only: bool,
+        f: impl Fn(&[String], Status) + Send + Sync + 'static
+    ) -> Self {
+        Self { prefix, finished_only, f: Arc::new(f) }
     }
-}
 
-impl GroupStringSelector for &str {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&[self.to_string()])
+    pub fn call(&self, path: &[String], status: Status) {
+        let matches = path.starts_with(&self.prefix) && (status.finished || !self.finished_only);
+        if matches {
+            (self.f)(path, status)
+        }
     }
 }
 
-impl GroupStringSelector for &String {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&[self.to_string()])
-    }
+// =======================
+// === OverrunListener ===
+// =======================
+
+/// Callback called with the group path when the unfinished run of a group exceeds its time
+/// budget, and again when it exceeds twice the budget, see [`group::State::time_budget`].
+#[derive(Clone)]
+pub struct OverrunListener {
+    /// Path prefix of the groups the listener is interested in.
+    prefix: Vec<String>,
+    f: Arc<dyn Fn(&[String], group::Overrun) + Send + Sync>,
 }
 
-impl GroupStringSelector for String {
-    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
-        f(&[self])
+impl Debug for OverrunListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverrunListener").field("prefix", &self.prefix).finish()
     }
 }
 
-// ===========
-// === API ===
-// ===========
+impl OverrunListener {
+    pub fn new(
+        prefix: Vec<String>,
+        f: impl Fn(&[String], group::Overrun) + Send + Sync + 'static
+    ) -> Self {
+        Self { prefix, f: Arc::new(f) }
+    }
 
-fn modify_logger<T>(f: impl FnOnce(&mut Logger) -> T) -> Result<T> {
-    let mut logger = logger().lock().map_err(|e| anyhow!("Failed to lock logger: {}", e))?;
-    Ok(f(&mut logger))
+    pub fn call(&self, path: &[String], overrun: group::Overrun) {
+        if path.starts_with(&self.prefix) {
+            (self.f)(path, overrun)
+        }
+    }
 }
 
-pub fn modify_all_groups(mut f: impl FnMut(LineRange<&'_ mut Group>)) -> Result {
-    modify_logger(|logger| for group in logger.groups.nonempty_mut() { f(group); })
-}
+// ===================
+// === GroupAction ===
+// ===================
 
-pub fn modify_group<T>(
-    selector: impl GroupSelector,
-    f: impl FnOnce(LineRange<&'_ mut Group>) -> T
-) -> Result<T> {
-    modify_logger(|l| l.group_mut(selector).map(f))?
-}
+/// Keys of the built-in actions, which group actions cannot be bound to. The group labels, see
+/// [`index_to_group_char`], are reserved too.
+const RESERVED_KEYS: &str = "q?0'/=OFDPRSTLNYWVH";
 
-pub fn push_line(selector: impl GroupSelector, log: Log) -> Result {
-    modify_logger(|l| l.push_line(selector, log))?
+/// Action of the groups matching a pattern, registered by the application. It is shown in the
+/// menu and triggered by its key while a single matching group is selected, see
+/// [`SharedLogger::register_group_action`].
+#[derive(Clone)]
+pub struct GroupAction {
+    pattern: Pattern,
+    pub key: char,
+    pub label: String,
+    f: Arc<dyn Fn(group::Id) + Send + Sync>,
 }
 
-pub fn set_group_header(selector: impl GroupSelector, s: impl Into<String>) -> Result {
-    modify_group_header(selector, |h| *h = s.into())
+impl Debug for GroupAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GroupAction")
+            .field("pattern", &self.pattern)
+            .field("key", &self.key)
+            .field("label", &self.label)
+            .finish()
+    }
 }
 
-pub fn modify_group_header<T>
-(selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
-    modify_group(selector, |mut g| f(&mut g.header))
-}
+impl GroupAction {
+    pub fn new(
+        pattern: Pattern,
+        key: char,
+        label: &str,
+        f: impl Fn(group::Id) + Send + Sync + 'static
+    ) -> Self {
+        Self { pattern, key, label: label.to_string(), f: Arc::new(f) }
+    }
 
-pub fn modify_group_footer<T>
-(selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
-    modify_group(selector, |mut g| f(&mut g.footer))
-}
+    pub fn matches(&self, group: &Group) -> bool {
+        self.pattern.matches(&group.path)
+    }
 
-pub fn set_group_footer(selector: impl GroupSelector, s: impl Into<String>) -> Result {
-    modify_group_footer(selector, |h| *h = s.into())
+    pub fn call(&self, group_id: group::Id) {
+        (self.f)(group_id)
+    }
 }
 
-pub fn modify_group_collapsed<T>
-(selector: impl GroupSelector, f: impl FnOnce(&mut Option<bool>) -> T) -> Result<T> {
-    modify_group(selector, |mut g| f(&mut g.collapsed))
-}
+// ====================
+// === FrameCounter ===
+// ====================
 
-pub fn collapse_group(selector: impl GroupSelector) -> Result {
-    modify_group_collapsed(selector, |b| *b = Some(true))
+/// Measures the effective number of frames drawn per second.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameCounter {
+    window_start: Option<SystemTime>,
+    frames: usize,
+    pub fps: f32,
 }
 
-pub fn expand_group(selector: impl GroupSelector) -> Result {
-    modify_group_collapsed(selector, |b| *b = Some(false))
+impl FrameCounter {
+    pub fn tick(&mut self, now: SystemTime) {
+        let window_start = *self.window_start.get_or_insert(now);
+        self.frames += 1;
+        let elapsed = now.duration_since(window_start).unwrap_or_default();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.frames = 0;
+            self.window_start = Some(now);
+        }
+    }
 }
 
-pub fn shift_selection(shift: isize) -> Result {
-    modify_logger(|l| l.shift_selection(shift))
-}
+// ==================
+// === FrameStats ===
+// ==================
 
-pub fn shift_history(shift: isize) -> Result {
-    modify_logger(|l| l.shift_history(shift))
+/// Cost of rendering the latest drawn frame, for debugging the performance of styles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Time of waiting for the logger lock before the frame.
+    pub lock_wait_time: Duration,
+    /// Time of rendering the rows of the frame.
+    pub compose_time: Duration,
+    /// Time of writing the changed rows to the terminal.
+    pub draw_time: Duration,
+    /// Number of rows redrawn because they changed.
+    pub changed_lines: usize,
+    pub bytes_written: usize,
 }
 
-pub fn scroll(group_index: group::Id, offset: isize) -> Result {
-    modify_logger(|l| l.scroll(group_index, offset))?
+impl std::fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |t: Duration| t.as_secs_f64() * 1000.0;
+        write!(f, "compose {:.1}ms, draw {:.1}ms, lock {:.1}ms, {} rows, {}",
+            ms(self.compose_time), ms(self.draw_time), ms(self.lock_wait_time),
+            self.changed_lines, format::bytes(self.bytes_written as u64))
+    }
 }
 
-pub fn line_to_group_id(line_ix: framebuffer::LineIndex) -> Result<Option<group::Id>> {
-    modify_logger(|logger| logger.frame_buffer.line_to_group(line_ix))
-}
+// =================
+// === DebugLine ===
+// =================
 
-pub fn group_to_lines
-(group_ix: group::Id) -> Result<Option<(framebuffer::LineIndex, framebuffer::LineIndex)>> {
-    modify_logger(|logger| logger.frame_buffer.group_to_lines(group_ix))
+/// Entry of the debug panel. Consecutive identical entries are merged and counted.
+#[derive(Clone, Debug)]
+pub struct DebugLine {
+    pub time: SystemTime,
+    pub content: String,
+    pub count: usize,
 }
 
-// =====================================
-// === Simplified API for common use ===
-// =====================================
-
-fn report_errors<T>(result: Result<T>) {
-    if let Err(error) = result {
-        modify_logger(|logger| {
-            logger.debug_lines.push(format!("Error: {error}"));
-        }).ok();
+impl DebugLine {
+    pub fn new(content: String) -> Self {
+        let time = SystemTime::now();
+        let count = 1;
+        Self { time, content, count }
     }
 }
 
-pub fn push_log_helper(selector: impl GroupStringSelector, log: Log) -> Result {
-    selector.with_selector(|sel|
-        modify_logger(|l| {
-            l.create_group(sel);
-            l.push_line(sel, log)
-        })?
-    )
+impl std::fmt::Display for DebugLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", format::time_of_day(self.time), self.content)?;
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+        Ok(())
+    }
 }
 
-pub fn log_helper(selector: &[String], status: Option<Status>, log: String) -> Result {
-    let last_log_status =
-        modify_logger(|l| {
-            l.create_group(selector);
-            l.get_last_line(selector).map(|t| t.map(|s| s.status))
-        })??;
-    let status = status.or_else(|| last_log_status).unwrap_or_default();
-    push_log(selector, Log { status, content: log.into() });
-    Ok(())
-}
+// ==============
+// === Logger ===
+// ==============
 
-pub fn set_header_helper(selector: impl GroupStringSelector, s: impl Into<String>) -> Result {
-    selector.with_selector(|sel| {
-        modify_logger(|l| l.create_group(sel))?;
-        modify_group_header(sel, |h| *h = s.into())
-    })
+#[derive(Debug, Default)]
+pub struct Logger {
+    groups: Groups,
+    /// Ids of the groups by their normalized selector paths, see [`Logger::normalize`].
+    path_to_group_id: HashTree<String, group::Id>,
+    /// Normalization of the selector paths, see [`Logger::set_selector_normalizer`].
+    normalize: Normalize,
+    templates: HashTree<String, group::Template>,
+    style: style::Any,
+    next_line_id: LineId,
+    frame_buffer: framebuffer::Framebuffer,
+    debug_lines: Vec<DebugLine>,
+    debug_hidden: bool,
+    /// Number of debug lines scrolled back from the newest one.
+    debug_scroll: usize,
+    history: history::History,
+    disabled: bool,
+    /// Set by [`TuiHandle::request_quit`], and checked by the render loop before every frame.
+    quit_requested: bool,
+    /// Time of the last pushed line or input event.
+    last_activity: Option<SystemTime>,
+    frame_counter: FrameCounter,
+    frame_stats: FrameStats,
+    /// Show the [`FrameStats`] in the menu row.
+    show_frame_stats: bool,
+    /// Record the status changes of groups for the status listeners.
+    track_status_changes: bool,
+    /// Group paths and new statuses not yet passed to the status listeners.
+    status_changes: Vec<(Vec<String>, Status)>,
+    /// Record the time budget overruns of groups for the overrun listeners.
+    track_overruns: bool,
+    /// Group paths and overruns not yet passed to the overrun listeners.
+    overruns: Vec<(Vec<String>, group::Overrun)>,
+    /// Groups which stalled with [`group::StallAction::Callback`], not yet passed to the callbacks.
+    stalls: Vec<(group::Id, group::StallAction)>,
+    /// Headers of the groups which became finished with an error since the last frame, if
+    /// notifications are enabled.
+    failed_groups: Vec<String>,
+    /// Status rules of all groups, applied after the rules of the group.
+    #[cfg(feature = "regex")]
+    status_rules: Vec<status_rule::StatusRule>,
+    #[cfg(feature = "regex")]
+    next_status_rule_id: status_rule::RuleId,
+    /// Label prefix typed so far in the goto mode, if the mode is active.
+    goto: Option<String>,
+    /// Terminal row under the mouse cursor.
+    hover: Option<framebuffer::LineIndex>,
+    /// Receivers of the lines pushed to groups.
+    subscribers: Vec<subscription::Subscriber>,
+    tees: Vec<tee::Tee>,
+    next_tee_id: tee::TeeId,
+    /// Group and line focused in the line mode, if the mode is active, see
+    /// [`Logger::toggle_line_mode`].
+    line_cursor: Option<(group::Id, LineId)>,
+    opener: Option<location::Opener>,
+    /// Actions of the groups registered by the application, see
+    /// [`SharedLogger::register_group_action`].
+    group_actions: Vec<GroupAction>,
+    /// Groups paired with the groups they depend on, see [`Logger::set_group_dependency`].
+    dependencies: Vec<(group::Id, group::Id)>,
+    /// Escape sequences printed with the next frame, e.g. setting the clipboard.
+    pending_output: String,
+    /// Groups drawn in the last frame. Its offset is the scroll position of the group list.
+    group_viewport: GroupViewport,
+    /// Rendered bodies of the throttled groups, see [`Settings::fast_group_refresh_rate`].
+    tail_cache: HashMap<group::Id, TailCache>,
+    /// Regex extracting locations from log lines. If not set, [`location::find`] is used.
+    #[cfg(feature = "regex")]
+    location_regex: Option<regex::Regex>,
+    /// Group context menu, if open.
+    context_menu: Option<context_menu::ContextMenu>,
+    /// Group quick-switch palette, if open.
+    palette: Option<palette::Palette>,
+    /// Recording of the drawn frames, see [`SharedLogger::record`].
+    #[cfg(feature = "serde")]
+    recorder: Option<cast::Recorder>,
+    /// The reset of all groups waits for a confirmation, see [`Logger::reset`].
+    confirm_reset: bool,
+    /// The logger mirrors a served logger, which local changes would diverge from, see
+    /// `remote::attach`.
+    attached: bool,
+    /// Time and row of the last mouse click, used to detect double clicks.
+    last_click: Option<(SystemTime, framebuffer::LineIndex)>,
+    /// The terminal reported losing focus.
+    unfocused: bool,
+    /// Number of alive render guards, see [`SharedLogger::freeze_rendering`].
+    frozen: usize,
+    /// Time the outermost render guard was created at.
+    frozen_at: Option<SystemTime>,
+    /// The first frame is not drawn before this time, unless [`Logger::ready`] is called, see
+    /// [`Builder::defer_first_draw_until_ready`].
+    ready_deadline: Option<SystemTime>,
+    /// Clear the screen and redraw everything in the next frame.
+    redraw: bool,
+    /// Frames drawn since the last periodic repaint, see [`Settings::full_repaint_frames`].
+    frames_since_repaint: u32,
+    /// Position the cursor was left at after the last frame, if it is checked, see
+    /// [`Settings::check_cursor`].
+    parked_cursor: Option<(u16, u16)>,
+    /// Selected groups, including the ones not displayed at the moment. The
+    /// [`group::State::selected`] flags are derived from it.
+    selected: BTreeSet<group::Id>,
+    settings: Settings,
+    error_handler: Option<ErrorHandler>,
 }
 
-pub fn debug(log: impl Into<String>) {
-    report_errors(modify_logger(|logger| logger.debug_lines.push(log.into())))
+impl Logger {
+    fn next_line_id(&mut self) -> LineId {
+        let line_id = self.next_line_id;
+        self.next_line_id = line_id.inc();
+        line_id
+    }
 }
 
-pub fn log(selector: impl GroupStringSelector, status: impl Into<Option<Status>>, log: impl Into<String>) {
-    selector.with_selector(|sel| report_errors(log_helper(sel, status.into(), log.into())))
-}
+impl Logger {
+    /// Creates the group if it does not exist yet, and returns its id. The group keeps the
+    /// selector path it was created with, and it is found by the normalized path.
+    pub fn create_group(&mut self, selector: &[String]) -> group::Id {
+        match self.path_to_group_id.entry(&self.normalize.path(selector)) {
+            hash_tree::Entry::Occupied(entry) => *entry.get(),
+            hash_tree::Entry::Vacant(entry) => {
+                let group_id = group::Id(self.groups.len());
+                entry.insert(group_id);
+                self.push_group(group_id, selector);
+                group_id
+            }
+        }
+    }
 
-pub fn push_log(selector: impl GroupStringSelector, log: Log) {
-    report_errors(push_log_helper(selector, log))
-}
+    /// Creates the group if it does not exist yet, and sets its display order key, see
+    /// [`group::State::order_key`].
+    pub fn create_group_ordered(&mut self, selector: &[String], order_key: i64) -> group::Id {
+        let group_id = self.create_group(selector);
+        self.groups[*group_id].order_key = order_key;
+        group_id
+    }
 
-pub fn set_header(selector: impl GroupStringSelector, s: impl Into<String>) {
-    report_errors(set_header_helper(selector, s))
-}
+    /// Creates the group, failing if a group with the same selector already exists.
+    pub fn create_group_strict(&mut self, selector: &[String]) -> Result<group::Id> {
+        match self.path_to_group_id.entry(&self.normalize.path(selector)) {
+            hash_tree::Entry::Occupied(entry) =>
+                Err(anyhow!("Group already exists: '{}'", entry.key_path().join("."))),
+            hash_tree::Entry::Vacant(_) => Ok(self.create_group(selector)),
+        }
+    }
 
-#[macro_export]
-macro_rules! log {
-    ($sel:expr, $msg:literal $($ts:tt)*) => {
+    /// Returns the id of the group, creating it first unless strict groups are enabled.
+    pub fn group_or_create(&mut self, selector: &[String]) -> Result<group::Id> {
+        if self.settings.strict_groups {
+            selector.group_id(self)
+        } else {
+            Ok(self.create_group(selector))
+        }
+    }
+
+    fn push_group(&mut self, group_id: group::Id, selector: &[String]) {
+        let mut group = Group::new(group_id);
+        group.path = selector.to_vec();
+        group.header = selector.join("::");
+        group.header_pending = true;
+        group.stale_after = self.settings.stale_after;
+        group.stall_timeout = self.settings.stall_timeout;
+        group.stall_action = self.settings.stall_action.clone();
+        group.show_when_empty = self.settings.show_empty_groups;
+        group.compact = self.settings.compact;
+        if self.settings.start_timers {
+            group.started_at = Some(SystemTime::now());
+        }
+        group.auto_collapse = self.settings.auto_collapse.clone();
+        group.min_level = self.settings.min_level;
+        if let Some(template) = self.templates.get_longest_prefix(selector) {
+            template.apply(&mut group);
+        }
+        self.groups.push(group);
+    }
+
+    /// Registers the template applied to groups created under the prefix. If multiple templates
+    /// match, the one with the longest prefix is applied. If `back_apply` is set, the template is
+    /// also applied to the existing groups for which it is the longest matching one.
+    pub fn register_group_template
+    (&mut self, prefix: &[String], template: group::Template, back_apply: bool) {
+        *self.templates.get_or_insert_with(prefix, default) = template;
+        if back_apply {
+            let paths = self.path_to_group_id.iter_prefix(prefix)
+                .map(|(path, id)| (path.into_iter().cloned().collect::<Vec<_>>(), *id))
+                .collect::<Vec<_>>();
+            let registered = self.templates.get(prefix);
+            let is_registered = |t: &&group::Template| registered.is_some_and(|r| ptr::eq(*t, r));
+            for (path, group_id) in paths {
+                let longest = self.templates.get_longest_prefix(&path);
+                if let Some(template) = longest.filter(is_registered) {
+                    template.apply(&mut self.groups[*group_id]);
+                }
+            }
+        }
+    }
+
+    /// Ids of all groups at or under the selector path.
+    pub fn group_ids_under(&self, prefix: &[String]) -> Vec<group::Id> {
+        self.path_to_group_id.descendant_values(&self.normalize.path(prefix)).copied().collect()
+    }
+
+    /// Sets the normalization of the selector paths, so that e.g. `Build` and `build ` select the
+    /// same group. The existing groups are indexed by their normalized paths. If the paths of
+    /// multiple groups become equal, the first created group is selected by it, and a warning is
+    /// shown in the debug panel. The other groups stay reachable by their ids.
+    pub fn set_selector_normalizer(&mut self, normalize: Normalize) {
+        self.normalize = normalize;
+        self.path_to_group_id = default();
+        let mut warnings = Vec::new();
+        for group in self.groups.iter() {
+            let path = self.normalize.path(&group.path);
+            match self.path_to_group_id.entry(&path) {
+                hash_tree::Entry::Occupied(entry) => {
+                    let first = &self.groups[**entry.get()].path;
+                    warnings.push(format!("Groups '{}' and '{}' have the same normalized selector",
+                        first.join("."), group.path.join(".")));
+                }
+                hash_tree::Entry::Vacant(entry) => { entry.insert(group.id); }
+            }
+        }
+        for warning in warnings {
+            self.push_debug_line(warning);
+        }
+    }
+
+    /// Selector paths and ids of all groups, in their creation order within each tree level.
+    pub fn list_groups(&self) -> Vec<(Vec<String>, group::Id)> {
+        self.path_to_group_id.iter()
+            .map(|(path, id)| (path.into_iter().cloned().collect(), *id))
+            .collect()
+    }
+
+    pub fn group_mut(&mut self, selector: impl GroupSelector) -> Result<LineRange<&'_ mut Group>> {
+        let next_line = self.groups.next_line;
+        GroupSelector::group_id(selector, self).map(|id|
+           LineRange { data: &mut self.groups[*id], next_line }
+        )
+    }
+
+    #[cfg_attr(not(feature = "regex"), allow(unused_mut))]
+    pub fn push_line(&mut self, selector: impl GroupSelector, mut log: Log) -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        #[cfg(feature = "regex")]
+        {
+            let group_rules = &self.groups[*group_id].status_rules;
+            let rules = group_rules.iter().chain(&self.status_rules);
+            if let Some(status) = status_rule::find(rules, &log.content) {
+                log.status = status;
+            }
+        }
+        self.push_log(group_id, log, SystemTime::now());
+        Ok(())
+    }
+
+    /// Pushes the line to the group and mirrors it to the targets of the matching tees.
+    fn push_log(&mut self, group_id: group::Id, log: Log, time: SystemTime) {
+        let group = &self.groups[*group_id];
+        let mirrored = self.tees.iter().filter(|tee| tee.accepts(&group.path, &log))
+            .map(|tee| (tee.target, tee::mirror(&log, &group.header)))
+            .collect::<Vec<_>>();
+        self.push_line_of_kind_at(group_id, group::LineKind::Log, log, time);
+        self.notify_subscribers(group_id);
+        // Tees cannot form cycles, so mirroring the copies further terminates.
+        for (target, log) in mirrored {
+            self.push_log(target, log, time);
+        }
+    }
+
+    /// Mirrors the lines pushed to the groups matching the pattern into the target group, if they
+    /// pass the filter. The target group is created if needed, and it is skipped if it matches the
+    /// pattern. Fails if the tee would mirror lines back into their group, directly or through
+    /// other tees.
+    pub fn add_tee(
+        &mut self,
+        from: &[String],
+        to: &[String],
+        filter: impl Fn(&Log) -> bool + Send + Sync + 'static
+    ) -> Result<tee::TeeId> {
+        let pattern = Pattern::parse(from);
+        if from == to {
+            return Err(anyhow!("Cannot tee group '{}' into itself", to.join(".")));
+        }
+        if tee::creates_cycle(&self.tees, &pattern, to) {
+            return Err(anyhow!("Tee from '{}' to '{}' would form a cycle",
+                from.join("."), to.join(".")));
+        }
+        let target = self.group_or_create(to)?;
+        let id = self.next_tee_id;
+        self.tees.push(tee::Tee::new(id, pattern, to.to_vec(), target, filter));
+        self.next_tee_id = tee::TeeId(*id + 1);
+        Ok(id)
+    }
+
+    pub fn remove_tee(&mut self, id: tee::TeeId) {
+        self.tees.retain(|tee| tee.id != id);
+    }
+
+    /// Sends the last line of the group to its subscribers, dropping the disconnected ones.
+    fn notify_subscribers(&mut self, group_id: group::Id) {
+        if self.subscribers.is_empty() { return }
+        self.subscribers.retain(|subscriber| subscriber.sender.is_connected());
+        let group = &self.groups[*group_id];
+        let Some(line) = group.lines.last() else { return };
+        for subscriber in &self.subscribers {
+            if group.path.starts_with(&subscriber.prefix) {
+                let status = line.log.status;
+                let event = (line.timestamp, line.time, status, line.log.content.clone());
+                subscriber.sender.send(event);
+            }
+        }
+    }
+
+    /// State of the groups and the history, see [`snapshot::Snapshot`].
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> snapshot::Snapshot {
+        let groups = self.groups.iter().map(snapshot::GroupSnapshot::new).collect();
+        let history = self.history.iter().map(|(group_id, tag)| (*group_id, tag)).collect();
+        let next_line_id = *self.next_line_id;
+        snapshot::Snapshot { groups, history, next_line_id }
+    }
+
+    /// Restores the state saved with [`Self::snapshot`]. The groups are created with the current
+    /// settings and templates. Fails if any group exists already.
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, snapshot: snapshot::Snapshot) -> Result {
+        if !self.groups.is_empty() {
+            return Err(anyhow!("Cannot restore a snapshot into a logger with groups"));
+        }
+        for group in snapshot.groups {
+            let group_id = self.create_group_strict(&group.path)?;
+            group.restore(&mut self.groups[*group_id]);
+            if self.groups[*group_id].selected {
+                self.selected.insert(group_id);
+            }
+        }
+        // The history of a reset logger does not start at the first line.
+        let start = snapshot.next_line_id.saturating_sub(snapshot.history.len());
+        self.history = history::History::starting_at(start);
+        for (group_id, tag) in snapshot.history {
+            self.history.push(group::Id(group_id), tag);
+        }
+        self.next_line_id = LineId(snapshot.next_line_id);
+        Ok(())
+    }
+
+    /// Subscribes to the lines pushed to the groups at or under the path.
+    pub fn subscribe(&mut self, prefix: &[String], capacity: usize) -> LineReceiver {
+        let (sender, receiver) = subscription::channel(capacity);
+        let prefix = prefix.to_vec();
+        self.subscribers.push(subscription::Subscriber { prefix, sender });
+        receiver
+    }
+
+    /// Registers a status rule of the groups, or of all groups if no selector is given. Rules of
+    /// a group are checked in their registration order, before the rules of all groups.
+    #[cfg(feature = "regex")]
+    pub fn add_status_rule(
+        &mut self,
+        selector: Option<impl GroupsSelector>,
+        regex: &str,
+        status: Status
+    ) -> Result<status_rule::RuleId> {
+        let id = self.next_status_rule_id;
+        let rule = status_rule::StatusRule::new(id, regex, status)?;
+        if let Some(selector) = selector {
+            for group_id in selector.group_ids(self)? {
+                self.groups[*group_id].status_rules.push(rule.clone());
+            }
+        } else {
+            self.status_rules.push(rule);
+        }
+        self.next_status_rule_id = status_rule::RuleId(*id + 1);
+        Ok(id)
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn remove_status_rule(&mut self, id: status_rule::RuleId) {
+        self.status_rules.retain(|rule| rule.id != id);
+        for group in self.groups.iter_mut() {
+            group.status_rules.retain(|rule| rule.id != id);
+        }
+    }
+
+    fn push_line_of_kind(&mut self, group_id: group::Id, kind: group::LineKind, log: Log) {
+        self.push_line_of_kind_at(group_id, kind, log, SystemTime::now());
+    }
+
+    fn push_line_of_kind_at(
+        &mut self,
+        group_id: group::Id,
+        kind: group::LineKind,
+        log: Log,
+        time: SystemTime
+    ) {
+        let group = &mut self.groups[*group_id];
+        if self.disabled {
+            let mut output = if log.status.is_error() {
+                terminal::Output::current_err()
+            } else {
+                terminal::Output::current()
+            };
+            let line = format!("[{}] {}\n", group.header, log.content);
+            std::io::Write::write_all(&mut output, line.as_bytes()).ok();
+        }
+        self.last_activity = Some(time);
+        if kind == group::LineKind::Log {
+            group.throughput.record(time, log.status.amount);
+            group.activity.record(time);
+            group.line_counter.record(time, &log);
+            group.stalled = false;
+        }
+        if kind == group::LineKind::Log {
+            let previous = group.lines.last().map(|line| line.log.status).unwrap_or_default();
+            let changed =
+                previous.tag != log.status.tag || previous.finished != log.status.finished;
+            if self.track_status_changes && changed {
+                self.status_changes.push((group.path.clone(), log.status));
+            }
+            let failed = |status: &Status| status.finished && status.tag == group::StatusTag::Error;
+            let notify = self.settings.notifications != Notify::Off && !self.disabled;
+            let pending = self.failed_groups.len() < MAX_PENDING_NOTIFICATIONS;
+            if notify && pending && failed(&log.status) && !failed(&previous) {
+                self.failed_groups.push(group.header.clone());
+            }
+        }
+        let repeated = group.dedup.then(|| group.lines.last_mut()).flatten().filter(|last| {
+            last.kind == kind && last.log.content == log.content
+                && last.log.status.tag == log.status.tag
+        });
+        if let Some(last) = repeated {
+            last.repeat = last.repeat.saturating_add(1);
+            last.time = time;
+            last.log.status = log.status;
+            return;
+        }
+        let timestamp = self.next_line_id();
+        self.history.push_at(group_id, log.status.tag, time);
+        self.limit_history();
+        let group = &mut self.groups[*group_id];
+        let repeat = 1;
+        let line = group::Line { timestamp, time, log, kind, repeat };
+        group.lines.push(line);
+        group.evict_lines();
+    }
+
+    /// Drops the oldest history entries in batches once [`Settings::history_limit`] is exceeded.
+    fn limit_history(&mut self) {
+        if let Some(limit) = self.settings.history_limit && self.history.len() > limit {
+            self.history.keep_last(limit - limit / HISTORY_EVICTION_DIVISOR);
+        }
+    }
+
+    /// Escape sequences notifying about the groups which failed since the last call. At most one
+    /// bell is emitted per call.
+    fn take_notifications(&mut self) -> String {
+        let notify = self.settings.notifications;
+        let failed_groups = std::mem::take(&mut self.failed_groups);
+        let mut out = String::new();
+        if notify.bell() && !failed_groups.is_empty() {
+            out.push('\x07');
+        }
+        if notify.osc9() {
+            for header in failed_groups {
+                let header = header.replace(|c: char| c.is_control(), "");
+                out.push_str(&format!("\x1b]9;{header} failed\x07"));
+            }
+        }
+        out
+    }
+
+    /// Starts a new run of the group. A separator line is pushed between runs, unless the current
+    /// run has no lines yet. The footer duration and the header progress are measured from the
+    /// start of the current run.
+    pub fn begin_run(&mut self, selector: impl GroupSelector) -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let group = &mut self.groups[*group_id];
+        let current_run_started = group.lines.last().is_some_and(|line| !line.is_run_start());
+        if current_run_started {
+            group.run += 1;
+            group.started_at = None;
+            let run = group.run;
+            let content = format!("run {run}");
+            let log = Log { content, status: Status::ok(), level: default() };
+            self.push_line_of_kind(group_id, group::LineKind::RunStart(run), log);
+        }
+        Ok(())
+    }
+
+    /// Sets the staleness threshold of all groups, including the ones created later.
+    pub fn set_stale_after(&mut self, stale_after: Option<Duration>) {
+        self.settings.stale_after = stale_after;
+        for group in self.groups.iter_mut() {
+            group.stale_after = stale_after;
+        }
+    }
+
+    /// Sets the stall timeout and action of all groups, including the ones created later.
+    pub fn set_stall_timeout(&mut self, timeout: Option<Duration>, action: group::StallAction) {
+        for group in self.groups.iter_mut() {
+            group.stall_timeout = timeout;
+            group.stall_action = action.clone();
+        }
+        self.settings.stall_timeout = timeout;
+        self.settings.stall_action = action;
+    }
+
+    pub fn set_show_empty_groups(&mut self, show: bool) {
+        self.settings.show_empty_groups = show;
+        for group in self.groups.iter_mut() {
+            group.show_when_empty = show;
+        }
+    }
+
+    /// Sets the compact mode of all groups, including the ones created later, see
+    /// [`group::State::compact`].
+    pub fn set_compact(&mut self, compact: bool) {
+        self.settings.compact = compact;
+        for group in self.groups.iter_mut() {
+            group.compact = compact;
+        }
+    }
+
+    /// Sets the auto-collapse behavior of all groups, including the ones created later.
+    pub fn set_auto_collapse(&mut self, auto_collapse: group::AutoCollapse) {
+        for group in self.groups.iter_mut() {
+            group.auto_collapse = auto_collapse.clone();
+        }
+        self.settings.auto_collapse = auto_collapse;
+    }
+
+    /// Checks whether drawing is suppressed by render guards. Guards older than
+    /// [`MAX_FREEZE_TIME`] are ignored.
+    pub fn is_frozen(&self, now: SystemTime) -> bool {
+        self.frozen_at.is_some_and(|t| now.duration_since(t).unwrap_or_default() < MAX_FREEZE_TIME)
+    }
+
+    /// Draws the first frame deferred by [`Builder::defer_first_draw_until_ready`].
+    pub fn ready(&mut self) {
+        if self.ready_deadline.take().is_some() {
+            self.redraw = true;
+        }
+    }
+
+    /// Checks whether the first frame can be drawn, see [`Self::ready`]. The screen is fully
+    /// redrawn once the deferral ends.
+    fn check_ready(&mut self, now: SystemTime) -> bool {
+        if self.ready_deadline.is_some_and(|deadline| now >= deadline) {
+            self.ready();
+        }
+        self.ready_deadline.is_none()
+    }
+
+    /// Clears the screen and redraws everything in the next frame, e.g. after running code which
+    /// prints around the UI.
+    pub fn force_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    /// Queues the output written after the lines of a frame: the notifications, the pending
+    /// output, and the cursor move to the parked position, see [`Settings::check_cursor`].
+    fn queue_frame_end(&mut self, out: &mut Vec<u8>) -> Result {
+        crossterm::queue!(out, crossterm::style::Print(self.take_notifications()))?;
+        let pending_output = std::mem::take(&mut self.pending_output);
+        crossterm::queue!(out, crossterm::style::Print(pending_output))?;
+        let check_cursor = self.settings.check_cursor && !std_capture::is_active();
+        self.parked_cursor = check_cursor.then_some((0, 0));
+        if let Some((col, row)) = self.parked_cursor {
+            crossterm::queue!(out, crossterm::cursor::MoveTo(col, row))?;
+        }
+        Ok(())
+    }
+
+    /// Counts the drawn frames, checking whether the periodic full repaint is due, see
+    /// [`Settings::full_repaint_frames`].
+    fn is_repaint_due(&mut self) -> bool {
+        let Some(interval) = self.settings.full_repaint_frames else { return false };
+        self.frames_since_repaint += 1;
+        if self.frames_since_repaint < interval { return false }
+        self.frames_since_repaint = 0;
+        true
+    }
+
+    /// Time to wait for input events between frames.
+    pub fn frame_time(&self) -> Duration {
+        let frame_time = Duration::from_secs(1) / self.settings.frame_rate.max(1);
+        let idle = self.last_activity.is_none_or(|time| {
+            SystemTime::now().duration_since(time).unwrap_or_default() >= IDLE_AFTER
+        });
+        if self.unfocused {
+            frame_time.max(UNFOCUSED_FRAME_TIME)
+        } else if self.settings.adaptive_frame_rate && idle {
+            frame_time.max(IDLE_FRAME_TIME)
+        } else {
+            frame_time
+        }
+    }
+
+    /// Sets the minimum displayed level of all groups, including the ones created later.
+    pub fn set_min_level(&mut self, min_level: Level) {
+        self.settings.min_level = min_level;
+        for group in self.groups.iter_mut() {
+            group.min_level = min_level;
+            group.scroll = None;
+        }
+    }
+
+    /// Pushes a line to the debug panel, merging it with the last one if they are identical.
+    /// Whether the user can change the logger state, reporting the refused action in the debug
+    /// panel if the logger is attached, see [`Logger::attached`].
+    fn allows_local_changes(&mut self, action: &str) -> bool {
+        if self.attached {
+            self.push_debug_line(format!("{action} is not available in an attached view."));
+        }
+        !self.attached
+    }
+
+    pub fn push_debug_line(&mut self, content: String) {
+        match self.debug_lines.last_mut() {
+            Some(last) if last.content == content => {
+                last.count += 1;
+                last.time = SystemTime::now();
+            }
+            _ => {
+                self.debug_lines.push(DebugLine::new(content));
+                // Keep the scrolled back panel in place.
+                if self.debug_scroll > 0 {
+                    self.debug_scroll += 1;
+                }
+            }
+        }
+    }
+
+    /// Scrolls the group list by the number of pages, if it does not fit the terminal. The
+    /// position is clamped to the groups when the next frame is drawn.
+    pub fn page_groups(&mut self, pages: isize) {
+        let viewport = &mut self.group_viewport;
+        let page = viewport.count.max(1) as isize;
+        viewport.offset = viewport.offset.saturating_add_signed(pages * page);
+    }
+
+    /// Scrolls the debug panel back by the offset, or forward if it is negative.
+    pub fn scroll_debug(&mut self, offset: isize) {
+        let max_scroll = self.debug_lines.len().saturating_sub(self.settings.debug_rows);
+        self.debug_scroll = self.debug_scroll.saturating_add_signed(offset).min(max_scroll);
+    }
+
+    /// Removes all lines of the group. History entries of the removed lines are kept, so the
+    /// history strip stays truthful, and the history view simply shows no lines for the group
+    /// before its first new line.
+    pub fn clear_group(&mut self, selector: impl GroupSelector) -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let group = &mut self.groups[*group_id];
+        group.lines.clear();
+        group.annotations.clear();
+        group.scroll = None;
+        group.started_at = None;
+        group.throughput = default();
+        group.activity = default();
+        self.tail_cache.remove(&group_id);
+        Ok(())
+    }
+
+    /// Attaches the note to the line of the group, shown below the line. Fails if the line is not
+    /// stored in the group, e.g. because it was evicted.
+    pub fn annotate
+    (&mut self, selector: impl GroupSelector, line: LineId, annotation: group::Annotation)
+    -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let group = &mut self.groups[*group_id];
+        if group.lines.binary_search_by_key(&line, |t| t.timestamp).is_err() {
+            return Err(anyhow!("Line {} is not stored in the group.", line.0));
+        }
+        group.annotations.entry(line).or_default().push(annotation);
+        self.tail_cache.remove(&group_id);
+        Ok(())
+    }
+
+    /// Scrolls the selected group so that its next annotated line after the top displayed one is
+    /// at the top, wrapping around to the first one. Requires exactly one selected group.
+    pub fn scroll_to_next_annotation(&mut self) {
+        let [group_id] = self.selected.iter().copied().collect::<Vec<_>>()[..] else { return };
+        let Some(group) = self.groups.get_mut(*group_id) else { return };
+        let after = group.scroll.and_then(|top| {
+            group.annotations.range(top.inc()..).next().map(|(line, _)| *line)
+        });
+        let next = after.or_else(|| group.annotations.keys().next().copied());
+        if next.is_some() {
+            group.scroll = next;
+        }
+    }
+
+    /// Registers an action of the groups matching the pattern. While a single matching group is
+    /// selected, the action is shown in the menu, and pressing its key calls it with the group.
+    /// Keys of the built-in actions and the group labels are rejected. If multiple actions of a
+    /// group share the key, the first registered one is called.
+    pub fn register_group_action(
+        &mut self,
+        pattern: &[String],
+        key: char,
+        label: &str,
+        f: impl Fn(group::Id) + Send + Sync + 'static
+    ) -> Result {
+        if RESERVED_KEYS.contains(key) {
+            return Err(anyhow!("Key '{key}' is used by a built-in action"));
+        }
+        if group_char_to_index(key).is_some() {
+            return Err(anyhow!("Key '{key}' is used by a group label"));
+        }
+        self.group_actions.push(GroupAction::new(Pattern::parse(pattern), key, label, f));
+        Ok(())
+    }
+
+    /// Action bound to the key of the single selected group, if any.
+    fn selected_group_action(&self, key: char) -> Option<(GroupAction, group::Id)> {
+        let [group_id] = self.selected.iter().copied().collect::<Vec<_>>()[..] else { return None };
+        let group = self.groups.get(*group_id)?;
+        let action = self.group_actions.iter()
+            .find(|action| action.key == key && action.matches(group))?;
+        Some((action.clone(), group.id))
+    }
+
+    /// Removes all groups, their lines, the history, and the debug lines, as if the logger was
+    /// just created. Settings, templates, rules, tees, subscribers, and group actions are kept,
+    /// and the target groups of tees are created again. Line ids keep growing, so that they stay
+    /// unique for subscribers and exports. Ids of the removed groups become invalid, and selectors
+    /// create the groups again. Dependencies between the groups are dropped with them.
+    pub fn reset(&mut self) {
+        // Dropping the groups releases the memory of their lines.
+        self.groups.range = default();
+        self.groups.zoomed = None;
+        self.path_to_group_id = default();
+        self.dependencies.clear();
+        self.history = history::History::starting_at(*self.next_line_id);
+        self.selected.clear();
+        self.line_cursor = None;
+        self.context_menu = None;
+        self.palette = None;
+        self.goto = None;
+        self.confirm_reset = false;
+        self.tail_cache = default();
+        self.failed_groups.clear();
+        self.debug_lines = default();
+        self.debug_scroll = 0;
+        self.frame_buffer = default();
+        self.redraw = true;
+        let targets = self.tees.iter().map(|tee| tee.to.clone()).collect::<Vec<_>>();
+        for (tee_ix, target) in targets.iter().enumerate() {
+            self.tees[tee_ix].target = self.create_group(target);
+        }
+    }
+
+    /// Marks the work of the group as cancelled by pushing a finished line with the
+    /// [`group::StatusTag::Cancelled`] tag, keeping the progress of the last line. Groups which
+    /// already finished are left untouched.
+    pub fn cancel_group(&mut self, selector: impl GroupSelector) -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let last = self.groups[*group_id].lines.last().map(|line| line.log.status);
+        if last.is_some_and(|status| status.is_finished()) {
+            return Ok(());
+        }
+        let progress = last.and_then(|status| status.progress);
+        let status = Status::cancelled().progress(progress).finished();
+        self.push_line(group_id, Log { status, content: "cancelled".into(), level: default() })
+    }
+
+    pub fn get_last_line(&mut self, selector: impl GroupSelector) -> Result<Option<&Log>> {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        Ok(self.groups[*group_id].lines.last().map(|l| &l.log))
+    }
+
+    /// Moves the selection of the displayed groups to the next or previous displayed group. If no
+    /// displayed group is selected, the first or last one is selected. Groups which are not
+    /// displayed keep their selection.
+    pub fn shift_selection(&mut self, shift: isize) {
+        let mut ids = self.displayed_ids();
+        if shift < 0 { ids.reverse() };
+        let selected = ids.iter().map(|id| self.selected.contains(id)).collect::<Vec<_>>();
+        if !selected.contains(&true) {
+            self.selected.extend(ids.first());
+        } else {
+            let count = ids.len();
+            for (index, id) in ids.into_iter().enumerate() {
+                self.select(id, selected[(index + count - 1) % count]);
+            }
+        }
+        self.sync_selection();
+    }
+
+    /// Ids of the displayed groups in the display order.
+    fn displayed_ids(&self) -> Vec<group::Id> {
+        self.groups.nonempty().iter().map(|g| g.id).collect()
+    }
+
+    fn select(&mut self, group_id: group::Id, selected: bool) {
+        if selected {
+            self.selected.insert(group_id);
+        } else {
+            self.selected.remove(&group_id);
+        }
+    }
+
+    /// Updates the [`group::State::selected`] flags to match the selection.
+    fn sync_selection(&mut self) {
+        for group in self.groups.iter_mut() {
+            group.selected = self.selected.contains(&group.id);
+        }
+    }
+
+    /// Selects the group, or deselects it if it is selected.
+    pub fn toggle_selection(&mut self, group_id: group::Id) {
+        let selected = self.selected.contains(&group_id);
+        self.select(group_id, !selected);
+        self.sync_selection();
+    }
+
+    /// Inverts the selection of the displayed groups.
+    pub fn invert_selection(&mut self) {
+        for group_id in self.displayed_ids() {
+            let selected = self.selected.contains(&group_id);
+            self.select(group_id, !selected);
+        }
+        self.sync_selection();
+    }
+
+    /// Replaces the selection with the given groups.
+    pub fn set_selection(&mut self, group_ids: impl IntoIterator<Item = group::Id>) {
+        self.selected = group_ids.into_iter().collect();
+        self.sync_selection();
+    }
+
+    pub fn shift_history(&mut self, shift: isize) {
+        let min = LineId(self.history.start());
+        let max = LineId(self.history.end());
+        let current = self.groups.next_line.unwrap_or(max);
+        let new = current.saturating_add_signed(shift).clamp(min, max);
+        self.groups.next_line = if new == max { None } else { Some(new) };
+    }
+
+    /// Enters the line mode, focusing the top visible line of the selected group, or leaves it. The
+    /// mode is available if exactly one group is selected.
+    pub fn toggle_line_mode(&mut self) {
+        if self.line_cursor.take().is_some() { return }
+        let [group_id] = self.selected.iter().copied().collect::<Vec<_>>()[..] else { return };
+        let next_line = self.groups.next_line;
+        let Some(group) = self.groups.get(*group_id) else { return };
+        let view = LineRange { data: &group.state, next_line };
+        let lines = view.displayed_lines();
+        let visible = self.frame_buffer.group_to_group_lines.get(&group_id);
+        let top = view.scroll_index().or_else(|| visible.map(|t| *t.0)).unwrap_or_default();
+        let line = lines.get(top).or(lines.last());
+        self.line_cursor = line.map(|line| (group_id, line.timestamp));
+    }
+
+    /// Opens the group quick-switch palette, see [`palette::Palette`].
+    pub fn open_palette(&mut self) {
+        self.goto = None;
+        self.palette = Some(default());
+    }
+
+    fn shift_palette_selection(&mut self, shift: isize) {
+        let Some(palette) = &mut self.palette else { return };
+        let groups = self.groups.nonempty_unzoomed();
+        let match_count = palette.matches(groups.iter().map(|g| (g.id, g.header.as_str()))).len();
+        palette.shift_selection(shift, match_count);
+    }
+
+    /// Closes the palette, selecting the highlighted group. The group is expanded, and the zoom
+    /// of another group is left, so that the group is visible.
+    fn commit_palette(&mut self) {
+        let Some(palette) = self.palette.take() else { return };
+        let groups = self.groups.nonempty_unzoomed();
+        let matches = palette.matches(groups.iter().map(|g| (g.id, g.header.as_str())));
+        let Some(group_id) = palette.selected_group(&matches) else { return };
+        self.set_selection([group_id]);
+        if self.groups.zoomed.is_some_and(|zoomed| zoomed != group_id) {
+            self.groups.zoomed = None;
+        }
+        if let Ok(mut group) = self.group_mut(group_id) && group.as_ref().is_collapsed() {
+            group.collapsed = Some(false);
+        }
+    }
+
+    /// Index of the focused line in the displayed lines of its group. If the line is not displayed
+    /// anymore, the next displayed line is focused.
+    fn line_cursor_index(&self) -> Option<(group::Id, usize)> {
+        let (group_id, line_id) = self.line_cursor?;
+        let next_line = self.groups.next_line;
+        let view = LineRange { data: &self.groups.get(*group_id)?.state, next_line };
+        let lines = view.displayed_lines();
+        let index = lines.partition_point(|line| line.timestamp < line_id);
+        Some((group_id, index.min(lines.len().checked_sub(1)?)))
+    }
+
+    /// Moves the focused line of the line mode, scrolling the group to keep it visible.
+    pub fn move_line_cursor(&mut self, offset: isize) -> Result {
+        let Some((group_id, index)) = self.line_cursor_index() else { return Ok(()) };
+        let next_line = self.groups.next_line;
+        let view = LineRange { data: &self.groups[*group_id].state, next_line };
+        let lines = view.displayed_lines();
+        let new_index = (index as isize + offset).clamp(0, lines.len() as isize - 1) as usize;
+        self.line_cursor = Some((group_id, lines.at(new_index).timestamp));
+        let scrolled = view.scroll.is_some();
+        let Some(&(start, end)) = self.frame_buffer.group_to_group_lines.get(&group_id) else {
+            return Ok(())
+        };
+        // If scrolled, the last row is the ellipsis after the visible lines.
+        let last_visible = if scrolled { end.0.saturating_sub(1) } else { end.0 };
+        if new_index < start.0 {
+            self.scroll(group_id, new_index as isize - start.0 as isize)?;
+        } else if new_index > last_visible {
+            self.scroll(group_id, (new_index - last_visible) as isize)?;
+        }
+        Ok(())
+    }
+
+    /// Header and plain text of the group, see [`clipboard::group_text`].
+    fn group_text(&self, group_id: group::Id) -> Option<(String, String)> {
+        let next_line = self.groups.next_line;
+        let view = LineRange { data: self.groups.get(*group_id)?, next_line };
+        Some((view.header.clone(), clipboard::group_text(&view)))
+    }
+
+    /// Header and plain text of the only selected group.
+    fn selected_group_text(&self) -> Option<(String, String)> {
+        let [group_id] = self.selected.iter().copied().collect::<Vec<_>>()[..] else { return None };
+        self.group_text(group_id)
+    }
+
+    /// Copies the text of the only selected group to the clipboard of the terminal, see
+    /// [`Self::copy_group`].
+    pub fn copy_selected_group(&mut self) {
+        let [group_id] = self.selected.iter().copied().collect::<Vec<_>>()[..] else { return };
+        self.copy_group(group_id)
+    }
+
+    /// Copies the text of the group to the clipboard of the terminal, reporting the result in the
+    /// debug panel.
+    pub fn copy_group(&mut self, group_id: group::Id) {
+        let Some((header, text)) = self.group_text(group_id) else { return };
+        match clipboard::osc52(&text) {
+            Ok(sequence) => {
+                self.pending_output.push_str(&sequence);
+                self.push_debug_line(format!("Copied {header} to the clipboard"));
+            }
+            Err(e) => self.push_debug_line(format!("Cannot copy {header}: {e}")),
+        }
+    }
+
+    /// Writes the text of the only selected group to a file, reporting its path in the debug
+    /// panel.
+    pub fn write_selected_group(&mut self) {
+        let Some((header, text)) = self.selected_group_text() else { return };
+        match clipboard::write_file(&header, &text) {
+            Ok(path) => self.push_debug_line(format!("Saved {header} to {}", path.display())),
+            Err(e) => self.push_debug_line(format!("Cannot save {header}: {e:#}")),
+        }
+    }
+
+    /// Writes the last drawn frame with its colors to a standalone HTML file, see
+    /// [`html::document`].
+    pub fn export_html(&self, path: &std::path::Path) -> Result {
+        let rows = self.frame_buffer.lines.iter().map(|line| line.content.as_str());
+        let document = html::document(rows, self.frame_buffer.size());
+        std::fs::write(path, document).with_context(|| format!("Cannot write {}", path.display()))
+    }
+
+    /// Writes the last drawn frame to an HTML file in the current directory, reporting its path
+    /// in the debug panel.
+    pub fn write_frame_html(&mut self) {
+        let path = html::default_path();
+        match self.export_html(&path) {
+            Ok(()) => self.push_debug_line(format!("Saved the frame to {}", path.display())),
+            Err(e) => self.push_debug_line(format!("Cannot save the frame: {e:#}")),
+        }
+    }
+
+    /// Starts recording the drawn frames to the cast file, replacing the current recording. The
+    /// next frame is fully redrawn, so that the recording starts with the whole screen.
+    #[cfg(feature = "serde")]
+    pub fn record(&mut self, path: &std::path::Path) -> Result {
+        self.stop_recording()?;
+        self.recorder = Some(cast::Recorder::create(path, terminal::Size::current())?);
+        self.redraw = true;
+        Ok(())
+    }
+
+    /// Finishes the recording, if any.
+    #[cfg(feature = "serde")]
+    pub fn stop_recording(&mut self) -> Result {
+        self.recorder.take().map_or(Ok(()), |recorder| recorder.finish())
+    }
+
+    /// Records the output of the frame. Recording stops if the file cannot be written.
+    #[cfg(feature = "serde")]
+    fn record_frame(&mut self, size: terminal::Size, output: &[u8]) {
+        let Some(recorder) = &mut self.recorder else { return };
+        if let Err(e) = recorder.frame(size, output) {
+            self.recorder = None;
+            self.push_debug_line(format!("Recording stopped: {e:#}"));
+        }
+    }
+
+    /// Records the runs which exceeded their time budgets since the previous check for the
+    /// overrun listeners, see [`SharedLogger::on_time_budget_overrun`]. Called every frame, so
+    /// that overruns are reported without new lines. The history view does not affect the check.
+    fn check_time_budgets(&mut self, now: SystemTime) {
+        for group in &mut self.groups.range.data {
+            if group.time_budget.is_none() && group.reported_overrun.is_none() { continue }
+            let overrun = LineRange { data: &group.state, next_line: None }.overrun(now);
+            if self.track_overruns && overrun > group.reported_overrun
+                && let Some(overrun) = overrun {
+                self.overruns.push((group.path.clone(), overrun));
+            }
+            group.reported_overrun = overrun;
+        }
+    }
+
+    /// Marks the unfinished groups which did not output anything for longer than their stall
+    /// timeouts as stalled, and takes their stall actions. Called every frame. Stalled groups are
+    /// not checked again until they output a line.
+    fn check_stalls(&mut self, now: SystemTime) {
+        let mut stalled = Vec::new();
+        for group in &self.groups.range.data {
+            let Some(timeout) = group.stall_timeout.filter(|_| !group.stalled) else { continue };
+            let view = LineRange { data: &group.state, next_line: None };
+            let Some(last) = view.run_lines().last() else { continue };
+            let elapsed = now.duration_since(last.time).unwrap_or_default();
+            if !last.log.status.is_finished() && elapsed >= timeout {
+                stalled.push((group.id, elapsed));
+            }
+        }
+        for (group_id, elapsed) in stalled {
+            let action = self.groups[*group_id].stall_action.clone();
+            match action {
+                group::StallAction::MarkStalled => {}
+                group::StallAction::PushErrorLine => {
+                    let elapsed = format::duration(elapsed, format::DurationPrecision::Seconds);
+                    let content = format!("No output for {elapsed}");
+                    let log = Log { content, status: Status::error(), level: Level::Error };
+                    self.push_log(group_id, log, now);
+                }
+                group::StallAction::Callback(_) => self.stalls.push((group_id, action)),
+            }
+            self.groups[*group_id].stalled = true;
+        }
+    }
+
+    /// Makes the group depend on the parent group. Until the parent finishes successfully, the
+    /// unfinished group is shown as waiting on it, and if the parent fails, as cancelled, see
+    /// [`group::Blocked`]. Nothing is scheduled by dependencies. Dependencies creating a cycle are
+    /// rejected.
+    pub fn set_group_dependency
+    (&mut self, selector: impl GroupSelector, parent: impl GroupSelector) -> Result {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let parent_id = GroupSelector::group_id(parent, self)?;
+        if group_id == parent_id || self.depends_on(parent_id, group_id) {
+            let path = self.groups[*group_id].path.join(".");
+            let parent_path = self.groups[*parent_id].path.join(".");
+            return Err(anyhow!("Dependency of '{path}' on '{parent_path}' would create a cycle"));
+        }
+        if !self.dependencies.contains(&(group_id, parent_id)) {
+            self.dependencies.push((group_id, parent_id));
+        }
+        Ok(())
+    }
+
+    /// Whether the group depends on the other group, directly or through other dependencies.
+    fn depends_on(&self, group_id: group::Id, other: group::Id) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = vec![group_id];
+        while let Some(id) = pending.pop() {
+            for &(child, parent) in &self.dependencies {
+                if child == id && visited.insert(parent) {
+                    if parent == other {
+                        return true;
+                    }
+                    pending.push(parent);
+                }
+            }
+        }
+        false
+    }
+
+    /// Updates the presentation of the groups with dependencies, see [`group::Blocked`]. Called
+    /// every frame.
+    fn check_dependencies(&mut self) {
+        for ix in 0..self.dependencies.len() {
+            let (group_id, _) = self.dependencies[ix];
+            let blocked = self.blocked_on(group_id);
+            self.groups[*group_id].blocked = blocked;
+        }
+    }
+
+    /// Unmet dependency of the group, if it is unfinished. Failed dependencies take precedence
+    /// over the unfinished ones.
+    fn blocked_on(&self, group_id: group::Id) -> Option<group::Blocked> {
+        let last_status = |id: group::Id| self.groups[*id].lines.last().map(|t| t.log.status);
+        if last_status(group_id).is_some_and(|status| status.is_finished()) {
+            return None;
+        }
+        let mut waiting = Vec::new();
+        for &(child, parent) in &self.dependencies {
+            if child != group_id {
+                continue;
+            }
+            let header = self.groups[*parent].header.clone();
+            match last_status(parent) {
+                Some(status) if status.is_error() || status.is_cancelled() =>
+                    return Some(group::Blocked::Cancelled(header)),
+                Some(status) if status.is_finished() => {}
+                _ => waiting.push(header),
+            }
+        }
+        (!waiting.is_empty()).then_some(group::Blocked::Waiting(waiting))
+    }
+
+    /// Number of lines and of error lines the group logged within the window ending now, or at
+    /// the viewed point in time in the history view, see [`LineRange::window_stats`].
+    pub fn window_stats(&mut self, selector: impl GroupSelector, window: Duration)
+    -> Result<group::WindowStats> {
+        let group_id = GroupSelector::group_id(selector, self)?;
+        let next_line = self.groups.next_line;
+        let view = LineRange { data: &self.groups[*group_id].state, next_line };
+        Ok(view.window_stats(SystemTime::now(), window))
+    }
+
+    /// Location referenced by the focused line of the line mode.
+    pub fn focused_line_location(&self) -> Option<location::Location> {
+        let (group_id, index) = self.line_cursor_index()?;
+        let next_line = self.groups.next_line;
+        let view = LineRange { data: &self.groups[*group_id].state, next_line };
+        let line = view.displayed_lines().at(index);
+        let text = format::strip_ansi(&line.log.content);
+        #[cfg(feature = "regex")]
+        if let Some(regex) = &self.location_regex {
+            return location::find_with(regex, &text);
+        }
+        location::find(&text)
+    }
+
+    pub fn scroll(&mut self, selector: impl GroupSelector, offset: isize) -> Result {
+        let group_id = selector.group_id(self)?;
+        let line_range = self.frame_buffer.group_to_group_lines.get(&group_id).copied();
+        let next_line = self.groups.next_line;
+        let group = &mut self.groups[*group_id];
+        let view = LineRange { data: &group.state, next_line };
+        let lines = view.displayed_lines();
+        let line_count = line_range.map(|(start, end)| start.distance(end) + 1).unwrap_or_default();
+        let max = group::LineIndex(lines.len().saturating_sub(line_count));
+        let first_visible = line_range.unwrap_or_default().0;
+        let current_scroll = view.scroll_index().map_or(first_visible, group::LineIndex);
+        let new_scroll = current_scroll.offset(offset).map_or_else(default, |t| t.min(max));
+        let anchor = lines.get(*new_scroll).map(|line| line.timestamp);
+        group.scroll = anchor.filter(|_| new_scroll != max);
+        Ok(())
+    }
+}
+
+// ====================
+// === SharedLogger ===
+// ====================
+
+/// Handle of a logger instance. Multiple independent instances can be used in one process, and the
+/// free functions of this crate use the global one returned by [`logger`].
+#[derive(Clone, Debug, Default, Deref)]
+pub struct SharedLogger {
+    #[deref]
+    arc: Arc<Mutex<Logger>>,
+    error_hook: Arc<RwLock<Option<ErrorHook>>>,
+    status_listeners: Arc<RwLock<Vec<StatusListener>>>,
+    overrun_listeners: Arc<RwLock<Vec<OverrunListener>>>,
+    /// Number of failed calls of the simplified API, including the dropped log lines.
+    dropped_count: Arc<AtomicUsize>,
+}
+
+impl SharedLogger {
+    pub fn new(logger: Logger) -> Self {
+        let arc = Arc::new(Mutex::new(logger));
+        Self { arc, ..default() }
+    }
+
+    pub fn set_error_hook(&self, f: impl Fn(&Error) + Send + Sync + 'static) {
+        let hook = Some(ErrorHook::new(f));
+        match self.error_hook.write() {
+            Ok(mut guard) => *guard = hook,
+            Err(poisoned) => *poisoned.into_inner() = hook,
+        }
+    }
+
+    pub fn dropped_count(&self) -> usize {
+        self.dropped_count.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Registers a callback called when the status tag or the finished flag of any group differs
+    /// from the one of its previous line. Callbacks are called outside of the logger lock, after
+    /// the API call that pushed the line, so they can use the API.
+    pub fn on_status_change
+    (&self, f: impl Fn(&[String], Status) + Send + Sync + 'static) -> Result {
+        self.add_status_listener(StatusListener::new(default(), false, f))
+    }
+
+    /// Registers a callback called when a group at or under the selector path becomes finished,
+    /// or changes its status tag while finished.
+    pub fn on_group_finished(
+        &self,
+        selector: impl GroupStringSelector,
+        f: impl Fn(&[String], Status) + Send + Sync + 'static
+    ) -> Result {
+        let prefix = selector.with_selector(|sel| sel.to_vec());
+        self.add_status_listener(StatusListener::new(prefix, true, f))
+    }
+
+    /// Registers a callback called when the unfinished run of a group at or under the selector
+    /// path exceeds its time budget, and again when it exceeds twice the budget, see
+    /// [`Self::set_time_budget`]. The budgets are checked every frame, and the callbacks are
+    /// called outside of the logger lock.
+    pub fn on_time_budget_overrun(
+        &self,
+        selector: impl GroupStringSelector,
+        f: impl Fn(&[String], group::Overrun) + Send + Sync + 'static
+    ) -> Result {
+        let listener = OverrunListener::new(selector.with_selector(|sel| sel.to_vec()), f);
+        match self.overrun_listeners.write() {
+            Ok(mut guard) => guard.push(listener),
+            Err(poisoned) => poisoned.into_inner().push(listener),
+        }
+        self.modify(|l| l.track_overruns = true)
+    }
+
+    fn add_status_listener(&self, listener: StatusListener) -> Result {
+        match self.status_listeners.write() {
+            Ok(mut guard) => guard.push(listener),
+            Err(poisoned) => poisoned.into_inner().push(listener),
+        }
+        self.modify(|l| l.track_status_changes = true)
+    }
+
+    fn notify_status_listeners(&self, changes: Vec<(Vec<String>, Status)>) {
+        if changes.is_empty() { return }
+        let listeners = self.status_listeners.read()
+            .map_or_else(|e| e.into_inner().clone(), |t| t.clone());
+        for (path, status) in changes {
+            for listener in &listeners {
+                listener.call(&path, status);
+            }
+        }
+    }
+
+    fn notify_overrun_listeners(&self, overruns: Vec<(Vec<String>, group::Overrun)>) {
+        if overruns.is_empty() { return }
+        let listeners = self.overrun_listeners.read()
+            .map_or_else(|e| e.into_inner().clone(), |t| t.clone());
+        for (path, overrun) in overruns {
+            for listener in &listeners {
+                listener.call(&path, overrun);
+            }
+        }
+    }
+
+    /// Reports an error of the simplified API to the error hook, and to the error handler, or to
+    /// the debug panel if no handler is set. Both callbacks are called outside of the logger lock,
+    /// so they can use the API or panic.
+    pub fn report_error(&self, error: Error) {
+        self.dropped_count.fetch_add(1, atomic::Ordering::Relaxed);
+        let hook = self.error_hook.read().map_or_else(|e| e.into_inner().clone(), |t| t.clone());
+        if let Some(hook) = hook {
+            hook.call(&error);
+        }
+        let handler = self.lock().ok().and_then(|logger| logger.error_handler.clone());
+        if let Some(handler) = handler {
+            handler.call(error);
+        } else if let Ok(mut logger) = self.lock() {
+            logger.push_debug_line(format!("Error: {error}"));
+        }
+    }
+}
+
+static LOGGER: OnceLock<SharedLogger> = OnceLock::new();
+
+pub fn logger() -> &'static SharedLogger {
+    LOGGER.get_or_init(|| Builder::new().build())
+}
+
+// =====================
+// === GroupSelector ===
+// =====================
+
+pub trait GroupSelector {
+    fn group_id(self, logger: &mut Logger) -> Result<group::Id>;
+}
+
+impl GroupSelector for group::Id {
+    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
+        if self.0 >= logger.groups.len() {
+            return Err(anyhow!("Group index out of bounds: {}", self.0));
+        }
+        Ok(self)
+    }
+}
+
+impl GroupSelector for &[String] {
+    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
+        logger.path_to_group_id.get(&logger.normalize.path(self)).copied()
+            .with_context(|| format!("Group not found: '{}'", self.join(".")))
+    }
+}
+
+impl<const N: usize> GroupSelector for &[String; N] {
+    fn group_id(self, logger: &mut Logger) -> Result<group::Id> {
+        let slice: &[String] = self;
+        slice.group_id(logger)
+    }
+}
+
+
+// ======================
+// === GroupsSelector ===
+// ======================
+
+/// Selector of potentially many groups. Plain selector paths containing `*` or `**` segments are
+/// interpreted as [`Pattern`]s. Patterns matching no groups are not an error.
+pub trait GroupsSelector {
+    fn group_ids(self, logger: &mut Logger) -> Result<Vec<group::Id>>;
+}
+
+impl GroupsSelector for group::Id {
+    fn group_ids(self, logger: &mut Logger) -> Result<Vec<group::Id>> {
+        Ok(vec![self.group_id(logger)?])
+    }
+}
+
+impl GroupsSelector for &Pattern {
+    fn group_ids(self, logger: &mut Logger) -> Result<Vec<group::Id>> {
+        Ok(self.find(&logger.path_to_group_id).into_iter().copied().collect())
+    }
+}
+
+impl GroupsSelector for Pattern {
+    fn group_ids(self, logger: &mut Logger) -> Result<Vec<group::Id>> {
+        (&self).group_ids(logger)
+    }
+}
+
+impl<T: GroupStringSelector> GroupsSelector for T {
+    fn group_ids(self, logger: &mut Logger) -> Result<Vec<group::Id>> {
+        self.with_selector(|sel| {
+            let pattern = Pattern::parse(&logger.normalize.path(sel));
+            if pattern.is_wildcard() {
+                pattern.group_ids(logger)
+            } else {
+                Ok(vec![sel.group_id(logger)?])
+            }
+        })
+    }
+}
+
+// =================
+// === Normalize ===
+// =================
+
+/// Normalization of the segments of selector paths, applied when groups are created and looked
+/// up, see [`Logger::set_selector_normalizer`]. Patterns are normalized when given as selector
+/// paths.
+#[derive(Clone, Default)]
+pub enum Normalize {
+    /// Segments are compared exactly.
+    #[default]
+    None,
+    /// Surrounding whitespace is trimmed, and segments are compared ignoring the case.
+    TrimLowercase,
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl Debug for Normalize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::TrimLowercase => write!(f, "TrimLowercase"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Normalize {
+    pub fn custom(f: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(f))
+    }
+
+    pub fn segment(&self, segment: &str) -> String {
+        match self {
+            Self::None => segment.to_string(),
+            Self::TrimLowercase => segment.trim().to_lowercase(),
+            Self::Custom(f) => f(segment),
+        }
+    }
+
+    pub fn path<'t>(&self, path: &'t [String]) -> Cow<'t, [String]> {
+        match self {
+            Self::None => Cow::Borrowed(path),
+            _ => Cow::Owned(path.iter().map(|segment| self.segment(segment)).collect()),
+        }
+    }
+}
+
+// ===========================
+// === GroupStringSelector ===
+// ===========================
+
+pub trait GroupStringSelector {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T;
+}
+
+impl GroupStringSelector for &[String] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(self)
+    }
+}
+
+impl GroupStringSelector for &[&str] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+}
+
+impl GroupStringSelector for &[&String] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+}
+
+impl<const N: usize> GroupStringSelector for &[String; N] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(self)
+    }
+}
+
+impl<const N: usize> GroupStringSelector for &[&str; N] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+}
+
+impl<const N: usize> GroupStringSelector for &[&String; N] {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&self.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+}
+
+impl GroupStringSelector for &str {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&[self.to_string()])
+    }
+}
+
+impl GroupStringSelector for &String {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&[self.to_string()])
+    }
+}
+
+impl GroupStringSelector for String {
+    fn with_selector<T>(self, f: impl FnOnce(&[String]) -> T) -> T {
+        f(&[self])
+    }
+}
+
+// ===========
+// === API ===
+// ===========
+
+impl SharedLogger {
+    fn modify<T>(&self, f: impl FnOnce(&mut Logger) -> T) -> Result<T> {
+        let (out, status_changes, overruns, stalls) = {
+            let mut logger = self.lock().map_err(|e| anyhow!("Failed to lock logger: {}", e))?;
+            let out = f(&mut logger);
+            let status_changes = std::mem::take(&mut logger.status_changes);
+            let overruns = std::mem::take(&mut logger.overruns);
+            (out, status_changes, overruns, std::mem::take(&mut logger.stalls))
+        };
+        self.notify_status_listeners(status_changes);
+        self.notify_overrun_listeners(overruns);
+        for (group_id, action) in stalls {
+            if let group::StallAction::Callback(f) = action {
+                f(group_id);
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn modify_all_groups(&self, mut f: impl FnMut(LineRange<&'_ mut Group>)) -> Result {
+        self.modify(|logger| for group in logger.groups.nonempty_mut() { f(group); })
+    }
+
+    pub fn modify_groups
+    (&self, selector: impl GroupsSelector, mut f: impl FnMut(LineRange<&'_ mut Group>)) -> Result {
+        self.modify(|l| {
+            for group_id in selector.group_ids(l)? {
+                f(l.group_mut(group_id)?);
+            }
+            Ok(())
+        })?
+    }
+
+    pub fn modify_group<T>(
+        &self,
+        selector: impl GroupSelector,
+        f: impl FnOnce(LineRange<&'_ mut Group>) -> T
+    ) -> Result<T> {
+        self.modify(|l| l.group_mut(selector).map(f))?
+    }
+
+    pub fn push_line(&self, selector: impl GroupSelector, log: Log) -> Result {
+        self.modify(|l| l.push_line(selector, log))?
+    }
+
+    pub fn clear_group(&self, selector: impl GroupSelector) -> Result {
+        self.modify(|l| l.clear_group(selector))?
+    }
+
+    pub fn cancel_group(&self, selector: impl GroupSelector) -> Result {
+        self.modify(|l| l.cancel_group(selector))?
+    }
+
+    /// Number of lines and of error lines the group logged within the window, see
+    /// [`Logger::window_stats`].
+    pub fn window_stats(&self, selector: impl GroupSelector, window: Duration)
+    -> Result<group::WindowStats> {
+        self.modify(|l| l.window_stats(selector, window))?
+    }
+
+    /// Makes the group depend on the parent group, see [`Logger::set_group_dependency`].
+    pub fn set_group_dependency
+    (&self, selector: impl GroupSelector, parent: impl GroupSelector) -> Result {
+        self.modify(|l| l.set_group_dependency(selector, parent))?
+    }
+
+    /// Attaches the note to the line of the group, see [`Logger::annotate`].
+    pub fn annotate
+    (&self, selector: impl GroupSelector, line: LineId, annotation: group::Annotation) -> Result {
+        self.modify(|l| l.annotate(selector, line, annotation))?
+    }
+
+    /// Starts measuring the duration of the current run of the group now, instead of at its first
+    /// line.
+    pub fn start_timer(&self, selector: impl GroupStringSelector) -> Result {
+        selector.with_selector(|sel| self.modify(|l| {
+            let group_id = l.group_or_create(sel)?;
+            l.groups[*group_id].started_at = Some(SystemTime::now());
+            Ok(())
+        }))?
+    }
+
+    /// Starts measuring the duration of groups when they are created, see
+    /// [`Self::start_timer`].
+    pub fn set_start_timers(&self, start: bool) -> Result {
+        self.modify(|l| l.settings.start_timers = start)
+    }
+
+    /// Receiver of the lines pushed from now on to the groups at or under the path. At most
+    /// [`subscription::DEFAULT_CAPACITY`] lines are queued, the older ones are dropped.
+    pub fn subscribe(&self, selector: impl GroupStringSelector) -> Result<LineReceiver> {
+        self.subscribe_with_capacity(selector, subscription::DEFAULT_CAPACITY)
+    }
+
+    pub fn subscribe_with_capacity
+    (&self, selector: impl GroupStringSelector, capacity: usize) -> Result<LineReceiver> {
+        selector.with_selector(|sel| self.modify(|l| l.subscribe(sel, capacity)))
+    }
+
+    /// Mirrors the lines of the groups matching the pattern into the target group, prefixed with
+    /// the source header, e.g. to collect errors of all groups in one place. See
+    /// [`Logger::add_tee`].
+    pub fn add_tee(
+        &self,
+        from: impl GroupStringSelector,
+        to: impl GroupStringSelector,
+        filter: impl Fn(&Log) -> bool + Send + Sync + 'static
+    ) -> Result<tee::TeeId> {
+        from.with_selector(|from| to.with_selector(|to| {
+            self.modify(|l| l.add_tee(from, to, filter))
+        }))?
+    }
+
+    pub fn remove_tee(&self, id: tee::TeeId) -> Result {
+        self.modify(|l| l.remove_tee(id))
+    }
+
+    /// Saves the state of the logger as JSON, see [`Logger::snapshot`].
+    #[cfg(feature = "serde")]
+    pub fn save_state(&self, path: impl AsRef<std::path::Path>) -> Result {
+        let snapshot = self.modify(|l| l.snapshot())?;
+        let file = std::fs::File::create(path.as_ref())
+            .with_context(|| format!("Failed to create '{}'", path.as_ref().display()))?;
+        serde_json::to_writer(std::io::BufWriter::new(file), &snapshot)?;
+        Ok(())
+    }
+
+    /// Writes the last drawn frame with its colors to a standalone HTML file.
+    pub fn export_html(&self, path: impl AsRef<std::path::Path>) -> Result {
+        self.modify(|l| l.export_html(path.as_ref()))?
+    }
+
+    pub fn write_frame_html(&self) -> Result {
+        self.modify(|l| l.write_frame_html())
+    }
+
+    /// Records the drawn UI to the file in the asciinema v2 format, until
+    /// [`Self::stop_recording`] is called or the UI quits. The terminal resizes are recorded too.
+    #[cfg(feature = "serde")]
+    pub fn record(&self, path: impl AsRef<std::path::Path>) -> Result {
+        self.modify(|l| l.record(path.as_ref()))?
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn stop_recording(&self) -> Result {
+        self.modify(|l| l.stop_recording())?
+    }
+
+    /// Restores the state saved with [`Self::save_state`], see [`Logger::restore`].
+    #[cfg(feature = "serde")]
+    pub fn load_state(&self, path: impl AsRef<std::path::Path>) -> Result {
+        let file = std::fs::File::open(path.as_ref())
+            .with_context(|| format!("Failed to open '{}'", path.as_ref().display()))?;
+        let snapshot = serde_json::from_reader(std::io::BufReader::new(file))?;
+        self.modify(|l| l.restore(snapshot))?
+    }
+
+    /// Calls the function with all stored lines of the group, under the logger lock.
+    pub fn with_group_lines<T>
+    (&self, selector: impl GroupSelector, f: impl FnOnce(&[group::Line]) -> T) -> Result<T> {
+        self.modify(|l| {
+            let group_id = GroupSelector::group_id(selector, l)?;
+            Ok(f(&l.groups[*group_id].lines))
+        })?
+    }
+
+    /// Copies the time, status, and content of the stored lines of the group in the range. The
+    /// range is clamped to the number of stored lines.
+    pub fn read_lines(&self, selector: impl GroupSelector, range: Range<usize>)
+    -> Result<Vec<(SystemTime, Status, String)>> {
+        self.with_group_lines(selector, |lines| {
+            let end = range.end.min(lines.len());
+            let start = range.start.min(end);
+            lines[start..end].iter()
+                .map(|line| (line.time, line.log.status, line.log.content.clone()))
+                .collect()
+        })
+    }
+
+    pub fn set_style(&self, style: impl style::Style + 'static) -> Result {
+        self.modify(|l| l.style = style::Any::new(style))
+    }
+
+    pub fn set_theme(&self, theme: style::Theme) -> Result {
+        self.modify(|l| l.settings.theme = theme)
+    }
+
+    pub fn modify_theme<T>(&self, f: impl FnOnce(&mut style::Theme) -> T) -> Result<T> {
+        self.modify(|l| f(&mut l.settings.theme))
+    }
+
+    pub fn set_spinner(&self, spinner: widget::Spinner) -> Result {
+        self.modify_theme(|theme| theme.spinner = spinner)
+    }
+
+    pub fn set_duration_precision(&self, precision: format::DurationPrecision) -> Result {
+        self.modify_theme(|theme| theme.duration_precision = precision)
+    }
+
+    /// Overrides the duration precision of the theme for the groups, or restores it if `None`.
+    pub fn set_group_duration_precision(
+        &self,
+        selector: impl GroupsSelector,
+        precision: Option<format::DurationPrecision>,
+    ) -> Result {
+        self.modify_groups(selector, |mut g| g.duration_precision = precision)
+    }
+
+    pub fn set_stale_after(&self, stale_after: impl Into<Option<Duration>>) -> Result {
+        self.modify(|l| l.set_stale_after(stale_after.into()))
+    }
+
+    /// Sets the time without new lines after which unfinished groups stall, and the action taken
+    /// then, see [`group::State::stall_timeout`]. The groups are checked every frame.
+    pub fn set_stall_timeout
+    (&self, timeout: impl Into<Option<Duration>>, action: group::StallAction) -> Result {
+        self.modify(|l| l.set_stall_timeout(timeout.into(), action))
+    }
+
+    pub fn set_group_stall_timeout(
+        &self,
+        selector: impl GroupsSelector,
+        timeout: impl Into<Option<Duration>>,
+        action: group::StallAction
+    ) -> Result {
+        let timeout = timeout.into();
+        self.modify_groups(selector, |mut g| {
+            g.stall_timeout = timeout;
+            g.stall_action = action.clone();
+        })
+    }
+
+    pub fn set_show_empty_groups(&self, show: bool) -> Result {
+        self.modify(|l| l.set_show_empty_groups(show))
+    }
+
+    pub fn set_compact(&self, compact: bool) -> Result {
+        self.modify(|l| l.set_compact(compact))
+    }
+
+    pub fn set_level_filter(&self, selector: impl GroupsSelector, min_level: Level) -> Result {
+        self.modify_groups(selector, |mut g| {
+            g.min_level = min_level;
+            g.scroll = None;
+        })
+    }
+
+    pub fn set_default_level_filter(&self, min_level: Level) -> Result {
+        self.modify(|l| l.set_min_level(min_level))
+    }
+
+    /// Highlights the parts of the group lines matching the regex. Rules of the group are applied
+    /// before the global ones, see [`Self::add_global_highlight`].
+    #[cfg(feature = "regex")]
+    pub fn add_highlight
+    (&self, selector: impl GroupsSelector, regex: &str, style: highlight::HighlightStyle) -> Result {
+        let rule = highlight::Rule::new(regex, style)?;
+        self.modify_groups(selector, |mut g| g.highlights.push(rule.clone()))
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn add_global_highlight(&self, regex: &str, style: highlight::HighlightStyle) -> Result {
+        let rule = highlight::Rule::new(regex, style)?;
+        self.modify_theme(|theme| theme.highlights.push(rule))
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn add_status_rule(&self, selector: impl GroupsSelector, regex: &str, status: Status)
+    -> Result<status_rule::RuleId> {
+        self.modify(|l| l.add_status_rule(Some(selector), regex, status))?
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn add_global_status_rule(&self, regex: &str, status: Status)
+    -> Result<status_rule::RuleId> {
+        self.modify(|l| l.add_status_rule(None::<group::Id>, regex, status))?
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn remove_status_rule(&self, id: status_rule::RuleId) -> Result {
+        self.modify(|l| l.remove_status_rule(id))
+    }
+
+    /// Refreshes the body of the groups at most the given number of times per second, or restores
+    /// the default throttling of groups with fast output if `None`, see
+    /// [`Settings::fast_group_refresh_rate`].
+    pub fn set_group_tail_refresh_rate(&self, selector: impl GroupsSelector, rate: Option<u32>)
+    -> Result {
+        self.modify_groups(selector, |mut g| g.tail_refresh_rate = rate)
+    }
+
+    pub fn set_fast_group_refresh_rate(&self, rate: Option<u32>) -> Result {
+        self.modify(|l| l.settings.fast_group_refresh_rate = rate)
+    }
+
+    pub fn set_group_dedup(&self, selector: impl GroupsSelector, dedup: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.dedup = dedup)
+    }
+
+    pub fn set_group_wrap(&self, selector: impl GroupsSelector, wrap: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.wrap = wrap)
+    }
+
+    /// Enables column alignment of the tab-separated content of the group lines.
+    pub fn set_group_diff_highlight(&self, selector: impl GroupsSelector, highlight: bool)
+    -> Result {
+        self.modify_groups(selector, |mut g| g.diff_highlight = highlight)
+    }
+
+    /// Displays the groups before the unpinned ones, see [`group::State::pinned`].
+    pub fn set_group_pinned(&self, selector: impl GroupsSelector, pinned: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.pinned = pinned)
+    }
+
+    /// Sets the function stopping the work logging to the groups, offered in their context menus.
+    pub fn set_kill_handler(
+        &self,
+        selector: impl GroupsSelector,
+        f: impl Fn(group::Id) + Send + Sync + 'static,
+    ) -> Result {
+        let handler = group::KillHandler::new(f);
+        self.modify_groups(selector, |mut g| g.kill_handler = Some(handler.clone()))
+    }
+
+    pub fn set_group_tabular(&self, selector: impl GroupsSelector, tabular: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.tabular = tabular)
+    }
+
+    /// Sets the header icon of the groups. Only the first grapheme cluster of the icon is kept.
+    /// Sets the expected duration of the runs of the groups, or clears it. Unfinished runs
+    /// exceeding it are highlighted in yellow, and in red when exceeding it twice, see
+    /// [`Self::on_time_budget_overrun`].
+    pub fn set_time_budget
+    (&self, selector: impl GroupsSelector, budget: impl Into<Option<Duration>>) -> Result {
+        let budget = budget.into();
+        self.modify_groups(selector, |mut g| g.time_budget = budget)
+    }
+
+    pub fn set_group_icon(&self, selector: impl GroupsSelector, icon: Option<&str>) -> Result {
+        let icon = icon.and_then(|icon| icon.graphemes(true).next()).map(|t| t.to_string());
+        self.modify_groups(selector, |mut g| g.icon.clone_from(&icon))
+    }
+
+    pub fn set_group_show_when_empty
+    (&self, selector: impl GroupsSelector, show: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.show_when_empty = show)
+    }
+
+    pub fn set_group_compact(&self, selector: impl GroupsSelector, compact: bool) -> Result {
+        self.modify_groups(selector, |mut g| g.compact = compact)
+    }
+
+    pub fn set_debug_rows(&self, rows: usize) -> Result {
+        self.modify(|l| l.settings.debug_rows = rows)
+    }
+
+    pub fn set_frame_rate(&self, fps: u32) -> Result {
+        self.modify(|l| l.settings.frame_rate = fps)
+    }
+
+    pub fn set_adaptive_frame_rate(&self, adaptive: bool) -> Result {
+        self.modify(|l| l.settings.adaptive_frame_rate = adaptive)
+    }
+
+    pub fn set_notifications(&self, notifications: Notify) -> Result {
+        self.modify(|l| l.settings.notifications = notifications)
+    }
+
+    pub fn set_resize_debounce(&self, debounce: Duration) -> Result {
+        self.modify(|l| l.settings.resize_debounce = debounce)
+    }
+
+    pub fn effective_frame_rate(&self) -> Result<f32> {
+        self.modify(|l| l.frame_counter.fps)
+    }
+
+    pub fn toggle_debug_panel(&self) -> Result {
+        self.modify(|l| l.debug_hidden = !l.debug_hidden)
+    }
+
+    /// Removes all groups and their lines, see [`Logger::reset`].
+    pub fn reset(&self) -> Result {
+        self.modify(|l| l.reset())
+    }
+
+    /// Cost of rendering the latest drawn frame.
+    pub fn frame_stats(&self) -> Result<FrameStats> {
+        self.modify(|l| l.frame_stats)
+    }
+
+    /// Shows or hides the [`FrameStats`] of the latest frame in the menu row.
+    pub fn toggle_frame_stats(&self) -> Result {
+        self.modify(|l| l.show_frame_stats = !l.show_frame_stats)
+    }
+
+    pub fn copy_selected_group(&self) -> Result {
+        self.modify(|l| l.copy_selected_group())
+    }
+
+    pub fn write_selected_group(&self) -> Result {
+        self.modify(|l| l.write_selected_group())
+    }
+
+    pub fn toggle_line_mode(&self) -> Result {
+        self.modify(|l| l.toggle_line_mode())
+    }
+
+    pub fn scroll_to_next_annotation(&self) -> Result {
+        self.modify(|l| l.scroll_to_next_annotation())
+    }
+
+    pub fn open_palette(&self) -> Result {
+        self.modify(|l| l.open_palette())
+    }
+
+    pub fn move_line_cursor(&self, offset: isize) -> Result {
+        self.modify(|l| l.move_line_cursor(offset))?
+    }
+
+    /// Sets the callback opening the location referenced by the focused line of the line mode,
+    /// when `o` is pressed. It is called outside of the logger lock.
+    pub fn set_opener(&self, f: impl Fn(std::path::PathBuf, u32) + Send + Sync + 'static)
+    -> Result {
+        self.modify(|l| l.opener = Some(location::Opener::new(f)))
+    }
+
+    /// Sets the regex extracting locations from log lines. It has to capture the `path` and `line`
+    /// groups.
+    #[cfg(feature = "regex")]
+    pub fn set_location_regex(&self, regex: &str) -> Result {
+        let regex = regex::Regex::new(regex)?;
+        let names = regex.capture_names().flatten().collect::<Vec<_>>();
+        if !names.contains(&"path") || !names.contains(&"line") {
+            return Err(anyhow!("Location regex has to capture the 'path' and 'line' groups"));
+        }
+        self.modify(|l| l.location_regex = Some(regex))
+    }
+
+    /// Opens the location referenced by the focused line with the opener, returning whether
+    /// there was any.
+    pub fn open_focused_line(&self) -> Result<bool> {
+        let target = self.modify(|l| l.focused_line_location().zip(l.opener.clone()))?;
+        let Some((location, opener)) = target else { return Ok(false) };
+        opener.call(location);
+        Ok(true)
+    }
+
+    /// Registers an action of the groups matching the pattern, see
+    /// [`Logger::register_group_action`]. The callback is called outside of the logger lock, so
+    /// it can use the logger.
+    pub fn register_group_action(
+        &self,
+        selector: impl GroupStringSelector,
+        key: char,
+        label: &str,
+        f: impl Fn(group::Id) + Send + Sync + 'static
+    ) -> Result {
+        selector.with_selector(|sel| self.modify(|l| l.register_group_action(sel, key, label, f)))?
+    }
+
+    /// Calls the action bound to the key of the single selected group, returning whether there
+    /// was any.
+    pub fn run_group_action(&self, key: char) -> Result<bool> {
+        let Some((action, group_id)) = self.modify(|l| l.selected_group_action(key))? else {
+            return Ok(false)
+        };
+        action.call(group_id);
+        Ok(true)
+    }
+
+    /// Signals that the groups and headers registered at startup are in place, so that the first
+    /// frame can be drawn, see [`Builder::defer_first_draw_until_ready`].
+    pub fn ready(&self) -> Result {
+        self.modify(|l| l.ready())
+    }
+
+    pub fn set_selector_normalizer(&self, normalize: Normalize) -> Result {
+        self.modify(|l| l.set_selector_normalizer(normalize))
+    }
+
+    /// Clears the screen and redraws everything in the next frame, e.g. after running code which
+    /// prints around the UI.
+    pub fn force_redraw(&self) -> Result {
+        self.modify(|l| l.force_redraw())
+    }
+
+    pub fn set_show_summary(&self, show: bool) -> Result {
+        self.modify(|l| l.settings.show_summary = show)
+    }
+
+    pub fn toggle_summary(&self) -> Result {
+        self.modify(|l| l.settings.show_summary = !l.settings.show_summary)
+    }
+
+    pub fn set_show_history_times(&self, show: bool) -> Result {
+        self.modify(|l| l.settings.show_history_times = show)
+    }
+
+    pub fn toggle_history_times(&self) -> Result {
+        self.modify(|l| l.settings.show_history_times = !l.settings.show_history_times)
+    }
+
+    pub fn set_show_collapsed_errors(&self, show: bool) -> Result {
+        self.modify(|l| l.settings.show_collapsed_errors = show)
+    }
+
+    pub fn set_group_stale_after
+    (&self, selector: impl GroupSelector, stale_after: impl Into<Option<Duration>>) -> Result {
+        self.modify_group(selector, |mut g| g.stale_after = stale_after.into())
+    }
+
+    /// Creates the group if it does not exist yet. Groups are displayed in the creation order by
+    /// default, but a group without lines is not rendered until its first line is pushed, unless
+    /// it is shown when empty, see [`Self::set_show_empty_groups`].
+    pub fn create_group(&self, selector: impl GroupStringSelector) -> Result<group::Id> {
+        selector.with_selector(|sel| self.modify(|l| l.create_group(sel)))
+    }
+
+    /// Creates the group if it does not exist yet, and sets its display order key. Groups are
+    /// displayed by their order keys first, so the layout does not depend on which concurrent
+    /// task creates its group first. Groups have the key 0 by default.
+    pub fn create_group_ordered(&self, selector: impl GroupStringSelector, order_key: i64)
+    -> Result<group::Id> {
+        selector.with_selector(|sel| self.modify(|l| l.create_group_ordered(sel, order_key)))
+    }
+
+    pub fn group_exists(&self, selector: impl GroupStringSelector) -> Result<bool> {
+        selector.with_selector(|sel| self.modify(|l| sel.group_id(l).is_ok()))
+    }
+
+    /// Id of an existing group. It is a cheaper selector than the group path.
+    pub fn group_id(&self, selector: impl GroupStringSelector) -> Result<group::Id> {
+        selector.with_selector(|sel| self.modify(|l| sel.group_id(l))?)
+    }
+
+    /// Selector paths and ids of all groups, e.g. to complete the selectors. Only the paths are
+    /// cloned, not the lines, see [`Logger::list_groups`].
+    pub fn list_groups(&self) -> Result<Vec<(Vec<String>, group::Id)>> {
+        self.modify(|l| l.list_groups())
+    }
+
+    /// Ids of all groups at or under the selector path.
+    pub fn find_groups(&self, prefix: impl GroupStringSelector) -> Result<Vec<group::Id>> {
+        prefix.with_selector(|sel| self.modify(|l| l.group_ids_under(sel)))
+    }
+
+    pub fn set_strict_groups(&self, strict: bool) -> Result {
+        self.modify(|l| l.settings.strict_groups = strict)
+    }
+
+    pub fn set_error_handler(&self, f: impl Fn(Error) + Send + Sync + 'static) -> Result {
+        self.modify(|l| l.error_handler = Some(ErrorHandler::new(f)))
+    }
+
+    pub fn register_group_template
+    (&self, prefix: impl GroupStringSelector, template: GroupTemplate, back_apply: bool) -> Result {
+        prefix.with_selector(|sel|
+            self.modify(|l| l.register_group_template(sel, template, back_apply))
+        )
+    }
+
+    pub fn begin_run(&self, selector: impl GroupSelector) -> Result {
+        self.modify(|l| l.begin_run(selector))?
+    }
+
+    /// Sets the title of the header, keeping the other parts of its spec, see
+    /// [`Self::set_group_header_spec`].
+    pub fn set_group_header
+    (&self, selector: impl GroupSelector, s: impl Into<String>) -> Result {
+        self.modify_group_header(selector, |h| *h = s.into())
+    }
+
+    /// Sets the title, subtitle, badges, and progress visibility of the header.
+    pub fn set_group_header_spec
+    (&self, selector: impl GroupSelector, spec: impl Into<group::HeaderSpec>) -> Result {
+        self.modify_group(selector, |mut g| g.set_header_spec(spec.into()))
+    }
+
+    pub fn modify_group_header<T>
+    (&self, selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
+        self.modify_group(selector, |mut g| {
+            g.header_pending = false;
+            f(&mut g.header)
+        })
+    }
+
+    pub fn modify_group_footer<T>
+    (&self, selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
+        self.modify_group(selector, |mut g| f(&mut g.footer))
+    }
+
+    pub fn set_group_footer
+    (&self, selector: impl GroupsSelector, s: impl Into<String>) -> Result {
+        let footer = s.into();
+        self.modify_groups(selector, |mut g| g.footer.clone_from(&footer))
+    }
+
+    pub fn modify_group_collapsed<T>
+    (&self, selector: impl GroupSelector, f: impl FnOnce(&mut Option<bool>) -> T) -> Result<T> {
+        self.modify_group(selector, |mut g| f(&mut g.collapsed))
+    }
+
+    pub fn collapse_group(&self, selector: impl GroupsSelector) -> Result {
+        self.modify_groups(selector, |mut g| g.collapsed = Some(true))
+    }
+
+    pub fn expand_group(&self, selector: impl GroupsSelector) -> Result {
+        self.modify_groups(selector, |mut g| g.collapsed = Some(false))
+    }
+
+    pub fn set_group_order(&self, order: Order) -> Result {
+        self.modify(|l| l.groups.order = order)
+    }
+
+    pub fn cycle_group_order(&self) -> Result {
+        self.modify(|l| l.groups.order = l.groups.order.next())
+    }
+
+    pub fn set_hide_finished_after(&self, duration: impl Into<Option<Duration>>) -> Result {
+        self.modify(|l| l.groups.hide_finished_after = duration.into())
+    }
+
+    pub fn toggle_hidden_groups(&self) -> Result {
+        self.modify(|l| l.groups.show_hidden = !l.groups.show_hidden)
+    }
+
+    /// Displays only the given group, or all groups again if no group is given.
+    pub fn zoom_group(&self, selector: Option<impl GroupSelector>) -> Result {
+        self.modify(|l| {
+            l.groups.zoomed = selector.map(|t| GroupSelector::group_id(t, l)).transpose()?;
+            Ok(())
+        })?
+    }
+
+    pub fn shift_selection(&self, shift: isize) -> Result {
+        self.modify(|l| l.shift_selection(shift))
+    }
+
+    pub fn shift_history(&self, shift: isize) -> Result {
+        self.modify(|l| l.shift_history(shift))
+    }
+
+    pub fn scroll(&self, selector: impl GroupsSelector, offset: isize) -> Result {
+        self.modify(|l| {
+            for group_id in selector.group_ids(l)? {
+                l.scroll(group_id, offset)?;
+            }
+            Ok(())
+        })?
+    }
+
+    pub fn line_to_group_id
+    (&self, line_ix: framebuffer::LineIndex) -> Result<Option<group::Id>> {
+        self.modify(|logger| logger.frame_buffer.line_to_group(line_ix))
+    }
+
+    pub fn row_kind(&self, line_ix: framebuffer::LineIndex) -> Result<Option<RowKind>> {
+        self.modify(|logger| logger.frame_buffer.row_kind(line_ix))
+    }
+
+    pub fn group_to_lines(
+        &self,
+        group_ix: group::Id
+    ) -> Result<Option<(framebuffer::LineIndex, framebuffer::LineIndex)>> {
+        self.modify(|logger| logger.frame_buffer.group_to_lines(group_ix))
+    }
+}
+
+// =====================================
+// === Simplified API for common use ===
+// =====================================
+
+impl SharedLogger {
+    fn report_errors<T>(&self, result: Result<T>) {
+        if let Err(error) = result {
+            self.report_error(error);
+        }
+    }
+
+    pub fn push_log_helper(&self, selector: impl GroupStringSelector, log: Log) -> Result {
+        selector.with_selector(|sel|
+            self.modify(|l| {
+                l.group_or_create(sel)?;
+                l.push_line(sel, log)
+            })?
+        )
+    }
+
+    pub fn log_helper
+    (&self, selector: &[String], status: Option<Status>, log: String) -> Result {
+        self.log_at_helper(selector, default(), status, log)
+    }
+
+    pub fn log_at_helper
+    (&self, selector: &[String], level: Level, status: Option<Status>, log: String) -> Result {
+        let last_log_status =
+            self.modify(|l| {
+                l.group_or_create(selector)?;
+                l.get_last_line(selector).map(|t| t.map(|s| s.status))
+            })??;
+        let status = status.or(last_log_status).unwrap_or_default();
+        self.push_log(selector, Log { status, content: log, level });
+        Ok(())
+    }
+
+    pub fn set_header_helper
+    (&self, selector: impl GroupStringSelector, s: impl Into<String>) -> Result {
+        selector.with_selector(|sel| {
+            self.modify(|l| l.group_or_create(sel))??;
+            self.modify_group_header(sel, |h| *h = s.into())
+        })
+    }
+
+    pub fn debug(&self, log: impl Into<String>) {
+        self.report_errors(self.modify(|logger| logger.push_debug_line(log.into())))
+    }
+
+    pub fn log(
+        &self,
+        selector: impl GroupStringSelector,
+        status: impl Into<Option<Status>>,
+        log: impl Into<String>
+    ) {
+        selector.with_selector(|sel|
+            self.report_errors(self.log_helper(sel, status.into(), log.into()))
+        )
+    }
+
+    /// Logs a line with the given severity level, see [`Self::log`].
+    pub fn log_at(
+        &self,
+        selector: impl GroupStringSelector,
+        level: Level,
+        status: impl Into<Option<Status>>,
+        log: impl Into<String>
+    ) {
+        selector.with_selector(|sel|
+            self.report_errors(self.log_at_helper(sel, level, status.into(), log.into()))
+        )
+    }
+
+    pub fn push_log(&self, selector: impl GroupStringSelector, log: Log) {
+        self.report_errors(self.push_log_helper(selector, log))
+    }
+
+    /// Sets the header, creating the group first if needed. Threads may log to the group and set
+    /// its header in any order: lines logged before the header is set create the group and are
+    /// kept, and the header then only replaces its title. Until then, a placeholder is shown for
+    /// [`HEADER_GRACE`] after the creation and the path afterwards, so that the header does not
+    /// flicker. Default headers which must not overwrite the one set by another thread are set
+    /// with [`Self::set_header_once`].
+    pub fn set_header(&self, selector: impl GroupStringSelector, s: impl Into<String>) {
+        self.report_errors(self.set_header_helper(selector, s))
+    }
+
+    /// Sets the header unless it was already set explicitly, e.g. by [`Self::set_header`], so
+    /// that a default header never overwrites the one set by another thread.
+    pub fn set_header_once(&self, selector: impl GroupStringSelector, s: impl Into<String>) {
+        let result = selector.with_selector(|sel| self.modify(|l| {
+            let group_id = l.group_or_create(sel)?;
+            let group = &mut l.groups[*group_id];
+            if group.header_pending {
+                group.header = s.into();
+                group.header_pending = false;
+            }
+            Ok(())
+        }));
+        self.report_errors(result.and_then(|t| t))
+    }
+}
+
+// ==================
+// === Global API ===
+// ==================
+
+// Wrappers of the [`SharedLogger`] API using the global logger instance, see [`logger`].
+
+pub fn modify_all_groups(f: impl FnMut(LineRange<&'_ mut Group>)) -> Result {
+    logger().modify_all_groups(f)
+}
+
+pub fn modify_groups
+(selector: impl GroupsSelector, f: impl FnMut(LineRange<&'_ mut Group>)) -> Result {
+    logger().modify_groups(selector, f)
+}
+
+pub fn modify_group<T>
+(selector: impl GroupSelector, f: impl FnOnce(LineRange<&'_ mut Group>) -> T) -> Result<T> {
+    logger().modify_group(selector, f)
+}
+
+pub fn push_line(selector: impl GroupSelector, log: Log) -> Result {
+    logger().push_line(selector, log)
+}
+
+pub fn clear_group(selector: impl GroupSelector) -> Result {
+    logger().clear_group(selector)
+}
+
+pub fn cancel_group(selector: impl GroupSelector) -> Result {
+    logger().cancel_group(selector)
+}
+
+pub fn window_stats(selector: impl GroupSelector, window: Duration) -> Result<group::WindowStats> {
+    logger().window_stats(selector, window)
+}
+
+pub fn set_group_dependency(selector: impl GroupSelector, parent: impl GroupSelector) -> Result {
+    logger().set_group_dependency(selector, parent)
+}
+
+pub fn annotate(selector: impl GroupSelector, line: LineId, annotation: group::Annotation)
+-> Result {
+    logger().annotate(selector, line, annotation)
+}
+
+pub fn start_timer(selector: impl GroupStringSelector) -> Result {
+    logger().start_timer(selector)
+}
+
+pub fn set_start_timers(start: bool) -> Result {
+    logger().set_start_timers(start)
+}
+
+pub fn subscribe(selector: impl GroupStringSelector) -> Result<LineReceiver> {
+    logger().subscribe(selector)
+}
+
+pub fn subscribe_with_capacity
+(selector: impl GroupStringSelector, capacity: usize) -> Result<LineReceiver> {
+    logger().subscribe_with_capacity(selector, capacity)
+}
+
+pub fn add_tee(
+    from: impl GroupStringSelector,
+    to: impl GroupStringSelector,
+    filter: impl Fn(&Log) -> bool + Send + Sync + 'static
+) -> Result<tee::TeeId> {
+    logger().add_tee(from, to, filter)
+}
+
+pub fn remove_tee(id: tee::TeeId) -> Result {
+    logger().remove_tee(id)
+}
+
+pub fn export_html(path: impl AsRef<std::path::Path>) -> Result {
+    logger().export_html(path)
+}
+
+#[cfg(feature = "serde")]
+pub fn record(path: impl AsRef<std::path::Path>) -> Result {
+    logger().record(path)
+}
+
+#[cfg(feature = "serde")]
+pub fn stop_recording() -> Result {
+    logger().stop_recording()
+}
+
+#[cfg(feature = "serde")]
+pub fn save_state(path: impl AsRef<std::path::Path>) -> Result {
+    logger().save_state(path)
+}
+
+#[cfg(feature = "serde")]
+pub fn load_state(path: impl AsRef<std::path::Path>) -> Result {
+    logger().load_state(path)
+}
+
+pub fn capture_std(out: impl GroupStringSelector, err: impl GroupStringSelector) -> Result {
+    logger().capture_std(out, err)
+}
+
+pub fn with_group_lines<T>
+(selector: impl GroupSelector, f: impl FnOnce(&[group::Line]) -> T) -> Result<T> {
+    logger().with_group_lines(selector, f)
+}
+
+pub fn read_lines(selector: impl GroupSelector, range: Range<usize>)
+-> Result<Vec<(SystemTime, Status, String)>> {
+    logger().read_lines(selector, range)
+}
+
+pub fn set_style(style: impl style::Style + 'static) -> Result {
+    logger().set_style(style)
+}
+
+pub fn set_theme(theme: style::Theme) -> Result {
+    logger().set_theme(theme)
+}
+
+pub fn modify_theme<T>(f: impl FnOnce(&mut style::Theme) -> T) -> Result<T> {
+    logger().modify_theme(f)
+}
+
+pub fn set_spinner(spinner: widget::Spinner) -> Result {
+    logger().set_spinner(spinner)
+}
+
+pub fn set_duration_precision(precision: format::DurationPrecision) -> Result {
+    logger().set_duration_precision(precision)
+}
+
+pub fn set_group_duration_precision(
+    selector: impl GroupsSelector,
+    precision: Option<format::DurationPrecision>,
+) -> Result {
+    logger().set_group_duration_precision(selector, precision)
+}
+
+pub fn set_stale_after(stale_after: impl Into<Option<Duration>>) -> Result {
+    logger().set_stale_after(stale_after)
+}
+
+pub fn set_stall_timeout
+(timeout: impl Into<Option<Duration>>, action: group::StallAction) -> Result {
+    logger().set_stall_timeout(timeout, action)
+}
+
+pub fn set_group_stall_timeout(
+    selector: impl GroupsSelector,
+    timeout: impl Into<Option<Duration>>,
+    action: group::StallAction
+) -> Result {
+    logger().set_group_stall_timeout(selector, timeout, action)
+}
+
+pub fn set_show_empty_groups(show: bool) -> Result {
+    logger().set_show_empty_groups(show)
+}
+
+pub fn set_compact(compact: bool) -> Result {
+    logger().set_compact(compact)
+}
+
+pub fn set_level_filter(selector: impl GroupsSelector, min_level: Level) -> Result {
+    logger().set_level_filter(selector, min_level)
+}
+
+pub fn set_default_level_filter(min_level: Level) -> Result {
+    logger().set_default_level_filter(min_level)
+}
+
+#[cfg(feature = "regex")]
+pub fn add_highlight
+(selector: impl GroupsSelector, regex: &str, style: highlight::HighlightStyle) -> Result {
+    logger().add_highlight(selector, regex, style)
+}
+
+#[cfg(feature = "regex")]
+pub fn add_global_highlight(regex: &str, style: highlight::HighlightStyle) -> Result {
+    logger().add_global_highlight(regex, style)
+}
+
+#[cfg(feature = "regex")]
+pub fn add_status_rule(selector: impl GroupsSelector, regex: &str, status: Status)
+-> Result<status_rule::RuleId> {
+    logger().add_status_rule(selector, regex, status)
+}
+
+#[cfg(feature = "regex")]
+pub fn add_global_status_rule(regex: &str, status: Status) -> Result<status_rule::RuleId> {
+    logger().add_global_status_rule(regex, status)
+}
+
+#[cfg(feature = "regex")]
+pub fn remove_status_rule(id: status_rule::RuleId) -> Result {
+    logger().remove_status_rule(id)
+}
+
+pub fn set_group_tail_refresh_rate(selector: impl GroupsSelector, rate: Option<u32>) -> Result {
+    logger().set_group_tail_refresh_rate(selector, rate)
+}
+
+pub fn set_fast_group_refresh_rate(rate: Option<u32>) -> Result {
+    logger().set_fast_group_refresh_rate(rate)
+}
+
+pub fn set_group_dedup(selector: impl GroupsSelector, dedup: bool) -> Result {
+    logger().set_group_dedup(selector, dedup)
+}
+
+pub fn set_group_wrap(selector: impl GroupsSelector, wrap: bool) -> Result {
+    logger().set_group_wrap(selector, wrap)
+}
+
+pub fn set_group_diff_highlight(selector: impl GroupsSelector, highlight: bool) -> Result {
+    logger().set_group_diff_highlight(selector, highlight)
+}
+
+pub fn set_group_pinned(selector: impl GroupsSelector, pinned: bool) -> Result {
+    logger().set_group_pinned(selector, pinned)
+}
+
+pub fn set_kill_handler
+(selector: impl GroupsSelector, f: impl Fn(group::Id) + Send + Sync + 'static) -> Result {
+    logger().set_kill_handler(selector, f)
+}
+
+pub fn set_group_tabular(selector: impl GroupsSelector, tabular: bool) -> Result {
+    logger().set_group_tabular(selector, tabular)
+}
+
+pub fn set_time_budget
+(selector: impl GroupsSelector, budget: impl Into<Option<Duration>>) -> Result {
+    logger().set_time_budget(selector, budget)
+}
+
+pub fn set_group_icon(selector: impl GroupsSelector, icon: Option<&str>) -> Result {
+    logger().set_group_icon(selector, icon)
+}
+
+pub fn set_group_show_when_empty(selector: impl GroupsSelector, show: bool) -> Result {
+    logger().set_group_show_when_empty(selector, show)
+}
+
+pub fn set_group_compact(selector: impl GroupsSelector, compact: bool) -> Result {
+    logger().set_group_compact(selector, compact)
+}
+
+pub fn set_debug_rows(rows: usize) -> Result {
+    logger().set_debug_rows(rows)
+}
+
+pub fn set_frame_rate(fps: u32) -> Result {
+    logger().set_frame_rate(fps)
+}
+
+pub fn set_adaptive_frame_rate(adaptive: bool) -> Result {
+    logger().set_adaptive_frame_rate(adaptive)
+}
+
+pub fn set_notifications(notifications: Notify) -> Result {
+    logger().set_notifications(notifications)
+}
+
+pub fn set_resize_debounce(debounce: Duration) -> Result {
+    logger().set_resize_debounce(debounce)
+}
+
+pub fn effective_frame_rate() -> Result<f32> {
+    logger().effective_frame_rate()
+}
+
+pub fn toggle_debug_panel() -> Result {
+    logger().toggle_debug_panel()
+}
+
+pub fn reset() -> Result {
+    logger().reset()
+}
+
+pub fn frame_stats() -> Result<FrameStats> {
+    logger().frame_stats()
+}
+
+pub fn toggle_frame_stats() -> Result {
+    logger().toggle_frame_stats()
+}
+
+pub fn set_opener(f: impl Fn(std::path::PathBuf, u32) + Send + Sync + 'static) -> Result {
+    logger().set_opener(f)
+}
+
+pub fn register_group_action(
+    selector: impl GroupStringSelector,
+    key: char,
+    label: &str,
+    f: impl Fn(group::Id) + Send + Sync + 'static
+) -> Result {
+    logger().register_group_action(selector, key, label, f)
+}
+
+#[cfg(feature = "regex")]
+pub fn set_location_regex(regex: &str) -> Result {
+    logger().set_location_regex(regex)
+}
+
+pub fn set_show_summary(show: bool) -> Result {
+    logger().set_show_summary(show)
+}
+
+pub fn set_show_history_times(show: bool) -> Result {
+    logger().set_show_history_times(show)
+}
+
+pub fn toggle_summary() -> Result {
+    logger().toggle_summary()
+}
+
+pub fn set_show_collapsed_errors(show: bool) -> Result {
+    logger().set_show_collapsed_errors(show)
+}
+
+pub fn set_group_stale_after
+(selector: impl GroupSelector, stale_after: impl Into<Option<Duration>>) -> Result {
+    logger().set_group_stale_after(selector, stale_after)
+}
+
+/// Creates the group if it does not exist yet. Groups are displayed in the creation order by
+/// default, but a group without lines is not rendered until its first line is pushed, unless it is
+/// shown when empty, see [`set_show_empty_groups`].
+pub fn create_group(selector: impl GroupStringSelector) -> Result<group::Id> {
+    logger().create_group(selector)
+}
+
+pub fn create_group_ordered(selector: impl GroupStringSelector, order_key: i64)
+-> Result<group::Id> {
+    logger().create_group_ordered(selector, order_key)
+}
+
+pub fn group_exists(selector: impl GroupStringSelector) -> Result<bool> {
+    logger().group_exists(selector)
+}
+
+/// Id of an existing group. It is a cheaper selector than the group path.
+pub fn group_id(selector: impl GroupStringSelector) -> Result<group::Id> {
+    logger().group_id(selector)
+}
+
+pub fn list_groups() -> Result<Vec<(Vec<String>, group::Id)>> {
+    logger().list_groups()
+}
+
+pub fn find_groups(prefix: impl GroupStringSelector) -> Result<Vec<group::Id>> {
+    logger().find_groups(prefix)
+}
+
+pub fn set_strict_groups(strict: bool) -> Result {
+    logger().set_strict_groups(strict)
+}
+
+pub fn set_error_hook(f: impl Fn(&Error) + Send + Sync + 'static) {
+    logger().set_error_hook(f)
+}
+
+pub fn on_status_change(f: impl Fn(&[String], Status) + Send + Sync + 'static) -> Result {
+    logger().on_status_change(f)
+}
+
+pub fn on_group_finished(
+    selector: impl GroupStringSelector,
+    f: impl Fn(&[String], Status) + Send + Sync + 'static
+) -> Result {
+    logger().on_group_finished(selector, f)
+}
+
+pub fn on_time_budget_overrun(
+    selector: impl GroupStringSelector,
+    f: impl Fn(&[String], group::Overrun) + Send + Sync + 'static
+) -> Result {
+    logger().on_time_budget_overrun(selector, f)
+}
+
+pub fn dropped_count() -> usize {
+    logger().dropped_count()
+}
+
+pub fn set_error_handler(f: impl Fn(Error) + Send + Sync + 'static) -> Result {
+    logger().set_error_handler(f)
+}
+
+pub fn register_group_template
+(prefix: impl GroupStringSelector, template: GroupTemplate, back_apply: bool) -> Result {
+    logger().register_group_template(prefix, template, back_apply)
+}
+
+pub fn begin_run(selector: impl GroupSelector) -> Result {
+    logger().begin_run(selector)
+}
+
+pub fn set_group_header(selector: impl GroupSelector, s: impl Into<String>) -> Result {
+    logger().set_group_header(selector, s)
+}
+
+pub fn set_group_header_spec(
+    selector: impl GroupSelector,
+    spec: impl Into<group::HeaderSpec>
+) -> Result {
+    logger().set_group_header_spec(selector, spec)
+}
+
+pub fn modify_group_header<T>
+(selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
+    logger().modify_group_header(selector, f)
+}
+
+pub fn modify_group_footer<T>
+(selector: impl GroupSelector, f: impl FnOnce(&mut String) -> T) -> Result<T> {
+    logger().modify_group_footer(selector, f)
+}
+
+pub fn set_group_footer(selector: impl GroupsSelector, s: impl Into<String>) -> Result {
+    logger().set_group_footer(selector, s)
+}
+
+pub fn modify_group_collapsed<T>
+(selector: impl GroupSelector, f: impl FnOnce(&mut Option<bool>) -> T) -> Result<T> {
+    logger().modify_group_collapsed(selector, f)
+}
+
+pub fn collapse_group(selector: impl GroupsSelector) -> Result {
+    logger().collapse_group(selector)
+}
+
+pub fn expand_group(selector: impl GroupsSelector) -> Result {
+    logger().expand_group(selector)
+}
+
+pub fn set_group_order(order: Order) -> Result {
+    logger().set_group_order(order)
+}
+
+pub fn cycle_group_order() -> Result {
+    logger().cycle_group_order()
+}
+
+pub fn set_hide_finished_after(duration: impl Into<Option<Duration>>) -> Result {
+    logger().set_hide_finished_after(duration)
+}
+
+pub fn toggle_hidden_groups() -> Result {
+    logger().toggle_hidden_groups()
+}
+
+pub fn zoom_group(selector: Option<impl GroupSelector>) -> Result {
+    logger().zoom_group(selector)
+}
+
+pub fn shift_selection(shift: isize) -> Result {
+    logger().shift_selection(shift)
+}
+
+pub fn shift_history(shift: isize) -> Result {
+    logger().shift_history(shift)
+}
+
+pub fn scroll(selector: impl GroupsSelector, offset: isize) -> Result {
+    logger().scroll(selector, offset)
+}
+
+pub fn line_to_group_id(line_ix: framebuffer::LineIndex) -> Result<Option<group::Id>> {
+    logger().line_to_group_id(line_ix)
+}
+
+pub fn row_kind(line_ix: framebuffer::LineIndex) -> Result<Option<RowKind>> {
+    logger().row_kind(line_ix)
+}
+
+pub fn group_to_lines
+(group_ix: group::Id) -> Result<Option<(framebuffer::LineIndex, framebuffer::LineIndex)>> {
+    logger().group_to_lines(group_ix)
+}
+
+pub fn push_log_helper(selector: impl GroupStringSelector, log: Log) -> Result {
+    logger().push_log_helper(selector, log)
+}
+
+pub fn log_helper(selector: &[String], status: Option<Status>, log: String) -> Result {
+    logger().log_helper(selector, status, log)
+}
+
+pub fn log_at_helper
+(selector: &[String], level: Level, status: Option<Status>, log: String) -> Result {
+    logger().log_at_helper(selector, level, status, log)
+}
+
+pub fn set_header_helper(selector: impl GroupStringSelector, s: impl Into<String>) -> Result {
+    logger().set_header_helper(selector, s)
+}
+
+pub fn debug(log: impl Into<String>) {
+    logger().debug(log)
+}
+
+pub fn log
+(selector: impl GroupStringSelector, status: impl Into<Option<Status>>, log: impl Into<String>) {
+    logger().log(selector, status, log)
+}
+
+pub fn log_at(
+    selector: impl GroupStringSelector,
+    level: Level,
+    status: impl Into<Option<Status>>,
+    log: impl Into<String>
+) {
+    logger().log_at(selector, level, status, log)
+}
+
+pub fn push_log(selector: impl GroupStringSelector, log: Log) {
+    logger().push_log(selector, log)
+}
+
+pub fn set_header(selector: impl GroupStringSelector, s: impl Into<String>) {
+    logger().set_header(selector, s)
+}
+
+pub fn set_header_once(selector: impl GroupStringSelector, s: impl Into<String>) {
+    logger().set_header_once(selector, s)
+}
+
+#[macro_export]
+macro_rules! log {
+    ($sel:expr, $msg:literal $($ts:tt)*) => {
         $crate::log($sel, None, format!($msg $($ts)*))
     };
-    ($sel:expr, $status:expr, $msg:literal $($ts:tt)*) => {
-        $crate::log($sel, $status, format!($msg $($ts)*))
+    ($sel:expr, $status:expr, $msg:literal $($ts:tt)*) => {
+        $crate::log($sel, $status, format!($msg $($ts)*))
+    };
+}
+
+// ============
+// === Main ===
+// ============
+
+/// Group the panics of all threads are reported to.
+pub const PANIC_GROUP: &[&str] = &["lmux", "panic"];
+
+/// Location, message, and backtrace of the panic. The backtrace is included if enabled by the
+/// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+fn panic_report(info: &std::panic::PanicHookInfo) -> String {
+    let mut err = String::new();
+    if let Some(location) = info.location() {
+        let file = location.file();
+        let line = location.line();
+        let column = location.column();
+        err.push_str(&format!("At: {file}:{line}:{column}\n"));
+    }
+
+    err.push_str("Message: ");
+    if let Some(msg) = info.payload().downcast_ref::<&'static str>() {
+        err.push_str(&format!("{msg}\n"));
+    } else if let Some(msg) = info.payload().downcast_ref::<String>() {
+        err.push_str(&format!("{msg}\n"));
+    } else {
+        err.push_str("<non-string panic payload>\n");
+    }
+
+    let backtrace = std::backtrace::Backtrace::capture();
+    if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+        err.push_str(&format!("Backtrace:\n{backtrace}"));
+    }
+    err
+}
+
+impl SharedLogger {
+    /// Runs the UI until the user quits. If not `enabled`, or if the output is not interactive, see
+    /// [`is_interactive`], lines are printed as `[header] content` instead, the ones with error
+    /// statuses to the standard error.
+    pub fn main(&self, enabled: bool) -> Result {
+        if enabled && is_interactive() {
+            let error = self.set_panic_hook();
+            if !terminal::Capabilities::detect().unicode {
+                self.modify(|l| l.settings.theme.unicode = false)?;
+            }
+            let (mouse_capture, inline) =
+                self.modify(|l| (l.settings.mouse_capture, l.settings.inline_mode))?;
+            terminal::capture(mouse_capture, inline)?;
+            let result = std::panic::catch_unwind(|| self.run());
+            terminal::cleanup(inline)?;
+            #[cfg(feature = "serde")]
+            self.stop_recording()?;
+
+            result.unwrap_or_else(move |_| {
+                let locked_err = error.lock();
+                let msg = locked_err
+                    .as_ref()
+                    .map(|t| t.as_ref().map(|t| t.as_str()))
+                    .ok()
+                    .flatten()
+                    .unwrap_or("unknown panic (no message captured)");
+                Err(anyhow!("Panic occurred: {msg}"))
+            })
+        } else {
+            self.modify(|logger| logger.disabled = true)?;
+            Ok(())
+        }
+    }
+
+    /// Installs a panic hook showing the panic reports in the [`PANIC_GROUP`] group. Returns the
+    /// report of the last panic.
+    fn set_panic_hook(&self) -> Arc<Mutex<Option<String>>> {
+        let error: Arc<Mutex<Option<String>>> = default();
+        let error2 = error.clone();
+        let logger = self.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            let err = panic_report(info);
+            logger.report_panic(&err);
+            if let Ok(mut t) = error2.lock() {
+                *t = Some(err);
+            }
+        }));
+        error
+    }
+
+    /// Shows the panic report in the [`PANIC_GROUP`] group. The report is pushed from a new
+    /// thread, as the panicking one might hold the logger lock.
+    fn report_panic(&self, report: &str) {
+        let logger = self.clone();
+        let lines = report.lines().map(|t| t.to_owned()).collect::<Vec<_>>();
+        std::thread::spawn(move || {
+            for line in lines {
+                logger.log(PANIC_GROUP, Status::error(), line);
+            }
+        });
+    }
+
+    pub fn run(&self) -> Result {
+        let mut stdout = terminal::Output::current();
+        let mut resize = terminal::Resize::default();
+
+        loop {
+            if self.modify(|logger| std::mem::take(&mut logger.quit_requested))? {
+                break;
+            }
+            match on_frame(self, &mut stdout, &mut resize) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => {
+                    self.modify(|logger| {
+                        logger.push_debug_line(format!("Error: {error}"));
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn main(enabled: bool) -> Result {
+    logger().main(enabled)
+}
+
+/// Checks whether the UI can be shown, see [`terminal::is_interactive`].
+pub fn is_interactive() -> bool {
+    terminal::is_interactive()
+}
+
+pub fn set_selector_normalizer(normalize: Normalize) -> Result {
+    logger().set_selector_normalizer(normalize)
+}
+
+pub fn force_redraw() -> Result {
+    logger().force_redraw()
+}
+
+pub fn ready() -> Result {
+    logger().ready()
+}
+
+pub fn freeze_rendering() -> RenderGuard {
+    logger().freeze_rendering()
+}
+
+pub fn spawn() -> Result<TuiHandle> {
+    logger().spawn(true)
+}
+
+pub fn run() -> Result {
+    logger().run()
+}
+
+fn history_tile(char: char, tag: group::StatusTag, active: bool) -> String {
+    match (active, tag) {
+        (true,  group::StatusTag::Success)   => char.black().on_green(),
+        (true,  group::StatusTag::Error)     => char.black().on_red(),
+        (true,  group::StatusTag::Cancelled) => char.black().on_grey(),
+        (false, group::StatusTag::Success)   => char.dark_green().on_green(),
+        (false, group::StatusTag::Error)     => char.dark_red().on_red(),
+        (false, group::StatusTag::Cancelled) => char.dark_grey().on_grey(),
+    }.to_string()
+}
+
+/// Minimum number of columns between the labels of [`history_times_row`].
+const HISTORY_TIMES_GAP: usize = 3;
+
+/// Row below the history strip with sparse relative times of the entries in the range. Labels
+/// start at the column of their entry, and are skipped if they would overlap the label to their
+/// right or repeat it.
+fn history_times_row(
+    history: &history::History,
+    range: Range<usize>,
+    padding: usize,
+    width: usize,
+    now: SystemTime
+) -> String {
+    let mut row = vec![' '; width];
+    let mut free_end = width;
+    let mut last_label = None;
+    for index in range.clone().rev() {
+        let Some(time) = history.time_at(index) else { continue };
+        let label = format::relative(now.duration_since(time).unwrap_or_default());
+        let len = label.chars().count();
+        let start = (padding + index - range.start).min(width.saturating_sub(len));
+        if start + len > free_end || last_label.as_ref() == Some(&label) { continue }
+        row.splice(start..start + len, label.chars());
+        free_end = start.saturating_sub(HISTORY_TIMES_GAP);
+        last_label = Some(label);
+    }
+    row.into_iter().collect::<String>().dark_grey().to_string()
+}
+
+fn history_tile_active((char, tag): (char, group::StatusTag)) -> String {
+    history_tile(char, tag, true)
+}
+
+fn history_tile_non_active((char, tag): (char, group::StatusTag)) -> String {
+    history_tile(char, tag, false)
+}
+
+// =====================
+// === GroupViewport ===
+// =====================
+
+/// Number of rows of an expanded group besides its body. Compact groups have no footer row.
+fn header_and_footer_rows(group: &group::State) -> usize {
+    if group.compact { 1 } else { 2 }
+}
+
+/// Range of the displayed groups drawn in the frame. If the groups do not fit the terminal, the
+/// list is paged, and rows indicating the number of groups above and below the range are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GroupViewport {
+    /// Display index of the first drawn group.
+    pub offset: usize,
+    /// Number of drawn groups.
+    pub count: usize,
+    /// Number of displayed groups.
+    pub len: usize,
+}
+
+impl GroupViewport {
+    /// Fits the groups with the given minimum heights into `rows`, starting at `offset` if the list
+    /// is paged. The offset is moved so that the group with the `keep_visible` index is drawn, and
+    /// so that no rows are left empty below the last group. A group is drawn even if it does not
+    /// fit, unless the rows are taken by the rows indicating the groups above and below.
+    pub fn new(min_rows: &[usize], rows: usize, offset: usize, keep_visible: Option<usize>)
+    -> Self {
+        let len = min_rows.len();
+        if min_rows.iter().sum::<usize>() <= rows {
+            return Self { offset: 0, count: len, len };
+        }
+        let fit = |offset: usize, rows: usize| {
+            let mut used = 0;
+            min_rows[offset..].iter().take_while(|t| {
+                used += **t;
+                used <= rows
+            }).count()
+        };
+        let count_at = |offset: usize| {
+            let rows = rows.saturating_sub(usize::from(offset > 0));
+            let count = fit(offset, rows);
+            let has_below = offset + count < len;
+            let rows = if has_below { rows.saturating_sub(1) } else { rows };
+            let count = if has_below { fit(offset, rows) } else { count };
+            count.max(usize::from(rows > 0)).min(len - offset)
+        };
+        let max_offset = (0..len).find(|t| t + count_at(*t) >= len).unwrap_or_default();
+        let mut offset = offset.min(max_offset);
+        if let Some(index) = keep_visible {
+            offset = offset.min(index);
+            while offset < index && index >= offset + count_at(offset) {
+                offset += 1;
+            }
+        }
+        Self { offset, count: count_at(offset), len }
+    }
+
+    /// Minimum number of rows of the group. Expanded groups need at least one body row.
+    pub fn min_rows(group: &LineRange<&Group>, show_collapsed_errors: bool) -> usize {
+        if group.is_single_row() {
+            1 + usize::from(show_collapsed_errors && group.collapsed_error().is_some())
+        } else {
+            header_and_footer_rows(group) + 1
+        }
+    }
+
+    /// Checks whether some of the displayed groups are not drawn.
+    pub fn is_paged(&self) -> bool {
+        self.count < self.len
+    }
+
+    pub fn above(&self) -> usize {
+        self.offset
+    }
+
+    pub fn below(&self) -> usize {
+        self.len - self.offset - self.count
+    }
+}
+
+// =================
+// === TailCache ===
+// =================
+
+/// Rendered body rows of a throttled group, reused until its refresh interval passes, see
+/// [`Settings::fast_group_refresh_rate`].
+#[derive(Clone, Debug)]
+struct TailCache {
+    refreshed_at: SystemTime,
+    key: TailCacheKey,
+    rows: Vec<(group::LineIndex, String)>,
+}
+
+/// Rendering inputs other than the lines. The cache is not used if they change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TailCacheKey {
+    space: usize,
+    width: usize,
+    selected: bool,
+}
+
+/// Interval the body of the group is rendered at, if the group is throttled.
+fn tail_refresh_interval(group: &LineRange<&Group>, settings: &Settings, now: SystemTime)
+-> Option<Duration> {
+    let rate = group.tail_refresh_rate.or_else(|| {
+        let is_fast = group.activity.lines_per_second(now) > FAST_GROUP_RATE;
+        settings.fast_group_refresh_rate.filter(|_| is_fast)
+    })?;
+    Some(Duration::from_secs(1) / rate.max(1))
+}
+
+/// Renders the body rows of the group. The rows of a throttled group are reused until its refresh
+/// interval passes, see [`Settings::fast_group_refresh_rate`].
+fn render_body(
+    group: &LineRange<&Group>,
+    style: &mut style::Any,
+    tail_cache: &mut HashMap<group::Id, TailCache>,
+    settings: &Settings,
+    (space, width): (usize, usize),
+    focused: Option<group::LineIndex>,
+    now: SystemTime,
+) -> Vec<(group::LineIndex, String)> {
+    // Only the followed tail of a group is throttled.
+    let is_tail = group.scroll.is_none() && group.next_line.is_none();
+    let refresh_interval =
+        tail_refresh_interval(group, settings, now).filter(|_| is_tail && focused.is_none());
+    let key = TailCacheKey { space, width, selected: group.selected };
+    let cached = refresh_interval.and_then(|interval| {
+        tail_cache.get(&group.id).filter(|cache| {
+            let age = now.duration_since(cache.refreshed_at).unwrap_or_default();
+            cache.key == key && age < interval
+        })
+    });
+    if let Some(cache) = cached {
+        return cache.rows.clone();
+    }
+    let rows = body_rows(group, space, width, style).into_iter()
+        .map(|(line_ix, row)| (line_ix, match row {
+            BodyRow::Log(content) if Some(line_ix) == focused =>
+                style.focused_log_line(group, group.id, &content),
+            BodyRow::Log(content) => style.log_line(group, group.id, &content),
+            BodyRow::Separator(run) => style.separator(group, group.id, run),
+            BodyRow::Annotation(annotation) => style.annotation(group, group.id, annotation),
+            BodyRow::Ellipsis => style.log_line(group, group.id, "..."),
+        }))
+        .collect::<Vec<_>>();
+    if refresh_interval.is_some() {
+        let rows = rows.clone();
+        tail_cache.insert(group.id, TailCache { refreshed_at: now, key, rows });
+    } else {
+        tail_cache.remove(&group.id);
+    }
+    rows
+}
+
+/// Physical row of a group body.
+enum BodyRow<'t> {
+    Log(Cow<'t, str>),
+    Separator(usize),
+    /// Note attached to the line, below its rows.
+    Annotation(&'t group::Annotation),
+    /// Marks that the group is scrolled and there are more lines below.
+    Ellipsis,
+}
+
+/// Physical rows of the group body, each with the index of the logical line it belongs to. Wrapped
+/// and annotated lines take multiple rows, but scrolling is in logical lines. If not scrolled, the
+/// body follows its last line, and the first visible line can be shown partially.
+fn body_rows<'t>(
+    group: &LineRange<&'t Group>,
+    space: usize,
+    width: usize,
+    style: &mut style::Any
+) -> Vec<(group::LineIndex, BodyRow<'t>)> {
+    let lines = group.state().displayed_lines();
+    let scroll = group.state().scroll_index();
+    let column_widths = if group.tabular {
+        let window = match scroll {
+            Some(scroll) => scroll..(scroll + space).min(lines.len()),
+            None => lines.len().saturating_sub(space)..lines.len(),
+        };
+        let window = window.filter_map(|ix| lines.get(ix)).filter(|t| !t.is_run_start());
+        format::column_widths(window.map(|line| line.log.content.as_str()))
+    } else {
+        default()
+    };
+    let mut line_rows = |line_ix: usize| {
+        let line = lines.at(line_ix);
+        let previous = line_ix.checked_sub(1).and_then(|ix| lines.get(ix))
+            .filter(|previous| previous.kind == group::LineKind::Log);
+        let changed = previous.filter(|_| group.diff_highlight && line.kind == group::LineKind::Log)
+            .and_then(|previous| format::changed_range(&previous.log.content, &line.log.content));
+        let columns = group.tabular.then(|| format::columns(&line.log.content));
+        let content = if let Some(columns) = columns.filter(|t| t.len() > 1) {
+            Cow::Owned(format::align_columns(&columns, &column_widths, width))
+        } else if let Some(changed) = changed {
+            let chars = line.log.content.chars().collect::<Vec<_>>();
+            let text = |range: Range<usize>| chars[range].iter().collect::<String>();
+            let (prefix, suffix) = (text(0..changed.start), text(changed.end..chars.len()));
+            let span = style.changed_span(group, &text(changed));
+            Cow::Owned(format!("{prefix}{span}{suffix}"))
+        } else {
+            Cow::Borrowed(line.log.content.as_str())
+        };
+        let content = if line.log.level == Level::Info {
+            content
+        } else {
+            Cow::Owned(style.level_content(group, line.log.level, &content))
+        };
+        let content = if line.repeat > 1 {
+            let suffix = style.repeat_suffix(group, line.repeat);
+            Cow::Owned(format!("{content}{suffix}"))
+        } else {
+            content
+        };
+        let mut rows = match line.kind {
+            group::LineKind::RunStart(run) => vec![BodyRow::Separator(run)],
+            group::LineKind::Log if group.wrap => format::wrap(&content, width)
+                .into_iter().map(|row| BodyRow::Log(Cow::Owned(row))).collect(),
+            group::LineKind::Log => vec![BodyRow::Log(content)],
+        };
+        let annotations = group.annotations.get(&line.timestamp).into_iter().flatten();
+        rows.extend(annotations.map(BodyRow::Annotation));
+        rows.into_iter().map(move |row| (group::LineIndex(line_ix), row))
     };
+    let empty_row = |line_ix: usize| (group::LineIndex(line_ix), BodyRow::Log(Cow::Borrowed("")));
+    let mut rows = Vec::with_capacity(space);
+    let Some(content_space) = space.checked_sub(1) else { return rows };
+    if let Some(scroll) = scroll {
+        let mut line_ix = scroll;
+        while rows.len() < content_space {
+            if line_ix < lines.len() {
+                rows.extend(line_rows(line_ix));
+            } else {
+                rows.push(empty_row(line_ix));
+            }
+            line_ix += 1;
+        }
+        rows.truncate(content_space);
+        rows.push((group::LineIndex(line_ix), BodyRow::Ellipsis));
+    } else {
+        let mut line_ix = lines.len();
+        while rows.len() < space && line_ix > 0 {
+            line_ix -= 1;
+            rows.extend(line_rows(line_ix).rev());
+        }
+        rows.truncate(space);
+        rows.reverse();
+        let mut line_ix = lines.len();
+        while rows.len() < space {
+            rows.push(empty_row(line_ix));
+            line_ix += 1;
+        }
+    }
+    rows
 }
 
-// ============
-// === Main ===
-// ============
+/// Full text of the row if it is cut off by the terminal. If the text does not fit the menu row
+/// either, its beginning is elided instead, as the end is the part hidden in the row.
+fn tooltip(writer: &framebuffer::Writer, row: framebuffer::LineIndex, cols: usize)
+-> Option<String> {
+    let line = writer.lines.get(*row).filter(|line| line.truncated)?;
+    let text = format::strip_ansi(&line.content);
+    let text = text.trim();
+    let space = cols.saturating_sub(1);
+    let len = text.chars().count();
+    if len <= space {
+        Some(text.to_string())
+    } else {
+        let tail = text.chars().skip(len + 1 - space).collect::<String>();
+        Some(format!("…{tail}"))
+    }
+}
 
-pub fn main(enabled: bool) -> Result {
-    if enabled {
-        let error: Arc<Mutex<Option<String>>> = default();
-        let error2 = error.clone();
-        std::panic::set_hook(Box::new(move |info| {
-            let mut err = String::new();
-            if let Some(location) = info.location() {
-                let file = location.file();
-                let line = location.line();
-                let column = location.column();
-                err.push_str(&format!("At: {file}:{line}:{column}\n"));
-            }
-
-            err.push_str("Message: ");
-            if let Some(msg) = info.payload().downcast_ref::<&'static str>() {
-                err.push_str(&format!("{msg}\n"));
-            } else if let Some(msg) = info.payload().downcast_ref::<String>() {
-                err.push_str(&format!("{msg}\n"));
-            } else {
-                err.push_str("<non-string panic payload>\n");
+/// Input mode the bottom menu lists the keys of.
+#[derive(Clone, Copy, Debug)]
+enum MenuMode<'t> {
+    Normal,
+    /// Label prefix typed so far in the goto mode.
+    Goto(&'t str),
+    Palette,
+    ConfirmReset,
+    LineMode,
+}
+
+impl<'t> MenuMode<'t> {
+    /// The mode of the prompt or overlay taking the keys first.
+    fn new(goto: Option<&'t str>, palette: bool, confirm_reset: bool, line_mode: bool) -> Self {
+        if let Some(prefix) = goto {
+            Self::Goto(prefix)
+        } else if palette {
+            Self::Palette
+        } else if confirm_reset {
+            Self::ConfirmReset
+        } else if line_mode {
+            Self::LineMode
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Rows of the context menu and the palette drawn over the frame, see
+/// [`framebuffer::Writer::overlay`]. The palette matches the given groups, which include the
+/// groups hidden by the zoom.
+fn overlay(
+    groups: &[LineRange<&Group>],
+    menu: Option<&context_menu::ContextMenu>,
+    palette: Option<(&palette::Palette, Vec<LineRange<&Group>>)>,
+    zoomed: Option<group::Id>,
+    size: terminal::Size
+) -> HashMap<framebuffer::LineIndex, (usize, String)> {
+    let mut overlay = HashMap::new();
+    if let Some(menu) = menu && let Some(group) = groups.iter().find(|g| g.id == menu.group) {
+        overlay.extend(menu.overlay(group, zoomed == Some(menu.group), size));
+    }
+    if let Some((palette, groups)) = palette {
+        let matches = palette.matches(groups.iter().map(|g| (g.id, g.header.as_str())));
+        overlay.extend(palette.overlay(&matches, size));
+    }
+    overlay
+}
+
+/// Segments of the bottom menu row. `history` is the position in the history view and the number
+/// of all lines, if the view is active. The buttons are always listed in the same order, and the
+/// ones without effect in the current state are disabled.
+fn menu_segments(
+    groups: &[LineRange<&Group>],
+    mode: MenuMode,
+    history: Option<(usize, usize)>,
+    frame_stats: Option<FrameStats>,
+    tooltip: Option<String>,
+    actions: &[GroupAction]
+) -> Vec<style::MenuSegment> {
+    use style::MenuSegment as Segment;
+    if let Some(tooltip) = tooltip {
+        return vec![Segment::Tooltip(tooltip)];
+    }
+    let selected = groups.iter().filter(|g| g.selected).collect::<Vec<_>>();
+    let any_groups = !groups.is_empty();
+    let any_selected = !selected.is_empty();
+    let any_collapsible = selected.iter().any(|g| !g.state().displayed_lines().is_empty());
+    let any_annotations = matches!(selected.as_slice(), [g] if !g.annotations.is_empty());
+    let mut segments = match mode {
+        MenuMode::Goto(prefix) => vec![
+            Segment::button("Goto", &format!("'{prefix}_")),
+            Segment::button("Select", "Enter"),
+            Segment::button("Cancel", "Esc"),
+        ],
+        MenuMode::Palette => vec![
+            Segment::button("Select", "Enter"),
+            Segment::button("Move", "↑↓"),
+            Segment::button("Cancel", "Esc"),
+        ],
+        MenuMode::ConfirmReset => vec![
+            Segment::button("Reset all groups", "y"),
+            Segment::button("Cancel", "Esc"),
+        ],
+        MenuMode::LineMode => vec![
+            Segment::button("Open", "o"),
+            Segment::button("Move", "↑↓"),
+            Segment::button("Exit", "Esc"),
+        ],
+        MenuMode::Normal => vec![
+            Segment::button("Help", "?"),
+            Segment::button("Quit", "q"),
+            Segment::button_if("Select", "1-9 a-z ' ↑↓", any_groups),
+            Segment::button_if("Find", "^P /", any_groups),
+            Segment::button_if("Collapse", "Enter", any_collapsible),
+            Segment::button_if("Level", "V", any_selected),
+            Segment::button_if("Compact", "=", any_selected),
+            Segment::button_if("Lines", "L", selected.len() == 1),
+            Segment::button_if("Copy", "Y", selected.len() == 1),
+            Segment::button_if("Save", "W", selected.len() == 1),
+            Segment::button_if("Next Note", "N", any_annotations),
+            Segment::button_if("Deselect", "Esc", any_selected),
+            Segment::button_if("Inverse Selection", "0", any_groups),
+            Segment::button_if("History", "←→", any_groups),
+            Segment::button("Order", "O"),
+            Segment::button("Export", "H"),
+            Segment::button_if("Reset", "R", any_groups),
+            Segment::button("Debug", "D"),
+        ],
+    };
+    if let (MenuMode::Normal, [group]) = (&mode, selected.as_slice()) {
+        let actions = actions.iter().filter(|action| action.matches(group));
+        segments.extend(actions.map(|t| Segment::button(&t.label, &t.key.to_string())));
+    }
+    if let [group] = selected.as_slice() {
+        let total = group.state().displayed_lines().len();
+        let line = group.state().scroll_index().map_or(total, |scroll| (scroll + 1).min(total));
+        segments.push(Segment::Selection(group.header.clone()));
+        segments.push(Segment::Scroll { line, total });
+        if group.min_level > Level::Trace {
+            segments.push(Segment::LevelFilter(group.min_level));
+        }
+    } else if selected.len() > 1 {
+        segments.push(Segment::Selection(format!("{} groups", selected.len())));
+    }
+    if let Some((line, total)) = history {
+        segments.push(Segment::History { line, total });
+    }
+    if let Some(stats) = frame_stats {
+        segments.push(Segment::FrameStats(stats));
+    }
+    segments
+}
+
+/// Rows of the debug panel. The first row shows the effective frame rate.
+fn debug_panel
+(lines: &[DebugLine], scroll: usize, rows: usize, cols: usize, fps: f32) -> Vec<String> {
+    let end = lines.len().saturating_sub(scroll);
+    let start = end.saturating_sub(rows);
+    let fps = format!("{fps:.0} fps ");
+    let mut out: Vec<_> = lines[start..end].iter().enumerate().map(|(i, line)| {
+        let line = line.to_string();
+        let label = if i == 0 { fps.as_str() } else { "" };
+        let fill = " ".repeat(cols.saturating_sub(line.len() + label.len()));
+        format!("{line}{fill}{label}").black().on_blue().to_string()
+    }).collect();
+    out.resize(rows, " ".repeat(cols).on_blue().to_string());
+    out
+}
+
+/// Queues the clearing of the screen before the changed lines are drawn: of the whole screen if
+/// redrawing, or else of the rows of the previous frame which are not written anymore, as they
+/// would be left on the screen. The attributes left by the previous frame are reset first, so
+/// that the cleared cells get the default background.
+fn queue_clear(
+    out: &mut Vec<u8>,
+    writer: &mut framebuffer::Writer,
+    size: terminal::Size,
+    redraw: bool,
+) -> Result {
+    let had_stale_rows = writer.truncate();
+    let reset = crossterm::style::SetAttribute(crossterm::style::Attribute::Reset);
+    if redraw {
+        let clear = crossterm::terminal::Clear(crossterm::terminal::ClearType::All);
+        crossterm::queue!(out, reset, clear)?;
+    } else if had_stale_rows && writer.end.0 < size.rows {
+        crossterm::queue!(
+            out,
+            crossterm::cursor::MoveTo(0, writer.end.0 as u16),
+            reset,
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::FromCursorDown)
+        )?;
+    }
+    Ok(())
+}
+
+fn on_frame(
+    shared: &SharedLogger,
+    stdout: &mut impl std::io::Write,
+    resize: &mut terminal::Resize
+) -> Result<bool> {
+    draw_frame(shared, stdout, resize, terminal::Size::current())?;
+    handle_event(shared)
+}
+
+/// Composes the frame for the terminal size and writes its changed rows.
+fn draw_frame(
+    shared: &SharedLogger,
+    stdout: &mut impl std::io::Write,
+    resize: &mut terminal::Resize,
+    size: terminal::Size
+) -> Result {
+    // Output printed around the UI moves the cursor away from where the last frame left it.
+    if let Some(parked) = shared.modify(|l| l.parked_cursor)?
+        && crossterm::cursor::position().ok() != Some(parked) {
+        shared.force_redraw()?;
+    }
+
+    let lock_start = Instant::now();
+    shared.modify(|logger| {
+        let lock_wait_time = lock_start.elapsed();
+        let compose_start = Instant::now();
+        let now = SystemTime::now();
+        logger.check_time_budgets(now);
+        logger.check_stalls(now);
+        logger.check_dependencies();
+        let frame = resize.update(size, now, logger.settings.resize_debounce);
+        let is_ready = logger.check_ready(now);
+        if frame == terminal::Frame::Skip || logger.is_frozen(now) || !is_ready {
+            return Ok(());
+        }
+        let redraw = std::mem::take(&mut logger.redraw) | logger.is_repaint_due();
+        logger.frame_counter.tick(now);
+        let line_cursor = logger.line_cursor_index();
+        let mut writer = framebuffer::Writer::new(&mut logger.frame_buffer, size);
+        if frame == terminal::Frame::Full || redraw {
+            writer.clear();
+        }
+
+        // The optional rows are dropped if the terminal is too low to fit them.
+        let history_times_rows = usize::from(logger.settings.show_history_times);
+        let bottom_menu_rows = 3 + history_times_rows;
+        let no_menu_rows = size.rows.saturating_sub(bottom_menu_rows);
+        let debug_rows_if_any = logger.settings.debug_rows.min(no_menu_rows);
+        let no_debug_rows = logger.debug_lines.is_empty() || logger.debug_hidden;
+        let debug_rows = if no_debug_rows { 0 } else { debug_rows_if_any };
+        let rows_left = no_menu_rows - debug_rows;
+        let hidden_count = logger.groups.finished_hidden_count();
+        let hidden_rows = usize::from(hidden_count > 0).min(rows_left);
+        let rows_left = rows_left - hidden_rows;
+        let summary_rows = usize::from(logger.settings.show_summary).min(rows_left);
+        let content_rows = rows_left - summary_rows;
+
+        let groups = logger.groups.nonempty();
+        let style = &mut logger.style;
+        style.set_focused(!logger.unfocused);
+        style.set_size(size);
+        style.on_frame(&logger.settings.theme);
+        let menu = logger.context_menu.as_ref();
+        let zoomed = logger.groups.zoomed;
+        let palette = logger.palette.as_ref().map(|t| (t, logger.groups.nonempty_unzoomed()));
+        writer.overlay = overlay(&groups, menu, palette, zoomed, size);
+
+        let show_collapsed_errors = logger.settings.show_collapsed_errors;
+        let has_error_row = |g: &&LineRange<&Group>| {
+            show_collapsed_errors && g.collapsed_error().is_some()
+        };
+        let min_rows = groups.iter()
+            .map(|g| GroupViewport::min_rows(g, show_collapsed_errors))
+            .collect::<Vec<_>>();
+        let keep_visible = groups.iter().position(|g| g.selected);
+        let offset = logger.group_viewport.offset;
+        let viewport = GroupViewport::new(&min_rows, content_rows, offset, keep_visible);
+        logger.group_viewport = viewport;
+        let above_rows = usize::from(viewport.above() > 0).min(content_rows);
+        let below_rows = usize::from(viewport.below() > 0).min(content_rows - above_rows);
+        let drawn = &groups[viewport.offset..viewport.offset + viewport.count];
+        let group_rows = content_rows.saturating_sub(above_rows + below_rows);
+        let single_row_count = drawn.iter().filter(|g| g.is_single_row()).count();
+        let error_row_count = drawn.iter().filter(has_error_row).count();
+        let expanded_count = drawn.len() - single_row_count;
+        let expanded_rows = group_rows.saturating_sub(single_row_count + error_row_count);
+        let lines_per_group = expanded_rows.checked_div(expanded_count).unwrap_or_default();
+        let mut lines_left = expanded_rows.checked_rem(expanded_count).unwrap_or_default();
+
+        // === Summary ===
+
+        if summary_rows > 0 {
+            writer.line(RowKind::Summary, style.summary(&logger.groups.summary()));
+        }
+
+        // === Groups ===
+
+        if above_rows > 0 {
+            writer.line(RowKind::MoreGroups, style.more_groups(viewport.above(), true));
+        }
+        for group in groups.iter().skip(viewport.offset).take(viewport.count) {
+            let placeholder = if logger.settings.theme.unicode { "…" } else { "..." };
+            let title = if group.is_header_pending(now) { placeholder } else { &group.header };
+            let new_line = style.header(group, group.id, title);
+            writer.line(RowKind::Header(group.id), new_line);
+            if let Some(line) = group.collapsed_error().filter(|_| show_collapsed_errors) {
+                let new_line = style.collapsed_error_line(group, group.id, &line.log.content);
+                writer.line(RowKind::CollapsedError(group.id), new_line);
             }
-            if let Ok(mut t) = error2.lock() {
-                *t = Some(err);
+            if !group.is_single_row() {
+                let extra_line = if lines_left == 0 { 0 } else {
+                    lines_left -= 1;
+                    1
+                };
+                let height = lines_per_group + extra_line;
+                let space = height.saturating_sub(header_and_footer_rows(group));
+                let width = size.cols.saturating_sub(style.log_line_indent());
+                let focused =
+                    line_cursor.filter(|t| t.0 == group.id).map(|t| group::LineIndex(t.1));
+                let tail_cache = &mut logger.tail_cache;
+                let settings = &logger.settings;
+                let rows =
+                    render_body(group, style, tail_cache, settings, (space, width), focused, now);
+                for (line_ix, new_line) in rows {
+                    writer.line(RowKind::Content(group.id, line_ix), new_line);
+                }
+                if !group.compact {
+                    let new_line = style.footer(group, group.id, &group.footer);
+                    writer.line(RowKind::Footer(group.id), new_line);
+                }
             }
-        }));
+        }
+        for _ in writer.line.0 .. (summary_rows + content_rows).saturating_sub(below_rows) {
+            writer.line(RowKind::Blank, "".to_string());
+        }
+        if below_rows > 0 {
+            writer.line(RowKind::MoreGroups, style.more_groups(viewport.below(), false));
+        }
 
-        terminal::capture()?;
-        let result = std::panic::catch_unwind(run);
-        terminal::cleanup()?;
-
-        result.unwrap_or_else(move |_| {
-            let locked_err = error.lock();
-            let msg = locked_err
-                .as_ref()
-                .map(|t| t.as_ref().map(|t| t.as_str()))
-                .ok()
-                .flatten()
-                .unwrap_or("unknown panic (no message captured)");
-            Err(anyhow!("Panic occurred: {msg}"))
-        })
+        // === Hidden Groups ===
+
+        if hidden_rows > 0 {
+            let action = if logger.groups.show_hidden { "hide" } else { "show" };
+            let check = "✓".green().bold();
+            let label = format!("{hidden_count} finished groups (press F to {action})").dark_grey();
+            writer.line(RowKind::HiddenGroups, format!(" {check} {label}"));
+        }
+
+        // === Scroll Bar ===
+
+        {
+            let first_line = logger.history.start();
+            let line_count = (*logger.next_line_id).saturating_sub(first_line);
+            let len_f = if line_count == 0 { 1.0 } else {
+                (size.cols as f32 / line_count as f32).max(1.0)
+            };
+            let len = len_f.ceil() as usize;
+            let visible_line_count =
+                logger.groups.next_line.map(|t| (*t).saturating_sub(first_line));
+            let shift = visible_line_count.map(|t| t as f32 / line_count as f32).unwrap_or(1.0);
+            let left_space_count = (size.cols.saturating_sub(len) as f32 * shift) as usize;
+            let left_space = " ".repeat(left_space_count);
+            let bar = "▂".repeat(len).bold().dark_green();
+            writer.line(RowKind::ScrollBar, format!("{left_space}{bar}"))
+        };
+
+        // === History ===
+
+        {
+            let padding = 1;
+            let cols = size.cols.saturating_sub(2 * padding);
+            let all_count = logger.history.len();
+            let view_count = logger.groups.next_line
+                .map(|t| (*t).saturating_sub(logger.history.start()))
+                .unwrap_or(all_count);
+            let rhs_count = all_count.saturating_sub(view_count);
+            let max_shift = view_count.saturating_sub(cols/2);
+            let shift = rhs_count.min(cols/2).min(max_shift);
+            let start_ix = view_count.saturating_sub(cols) + shift;
+            let end_ix_succ = (start_ix + cols).min(logger.history.len());
+            let is_lhs_clipped = start_ix > 0;
+            let is_rhs_clipped = rhs_count > cols/2;
+            let visible_count = view_count.saturating_sub(start_ix);
+            let history = logger.history.range(start_ix..end_ix_succ).iter()
+                .map(|t| t.map0(|s| index_to_group_char_opt(*s)))
+                .collect::<Vec<_>>();
+            let (before, current) = visible_count.checked_sub(1).map(|current_ix| {
+                let before_start = if is_lhs_clipped { 1 } else { 0 };
+                let current = history.get(current_ix).copied().map(history_tile_active)
+                    .unwrap_or_default();
+                let before = history.get(before_start..current_ix).map(
+                    |t| t.iter().copied().map(history_tile_active).collect::<String>()
+                ).unwrap_or_default();
+                (before, current)
+            }).unwrap_or_default();
+            let after_end = history.len().saturating_sub(usize::from(is_rhs_clipped));
+            let dots1 = if is_lhs_clipped { "…" } else { "" }.black().on_green();
+            let dots2 = if is_rhs_clipped { "…" } else { "" }.dark_green().on_green();
+            let after: String = history.get(visible_count .. after_end).map(
+                |t| t.iter().copied().map(history_tile_non_active).collect()
+            ).unwrap_or_default();
+            let pad_str = " ".repeat(padding).on_green();
+            let history_str = format!("{pad_str}{dots1}{before}{current}{after}{dots2}{pad_str}");
+            let rhs_spaces = " ".repeat(cols.saturating_sub(visible_count)).on_green();
+            let new_line = format!("{history_str}{rhs_spaces}");
+            writer.line(RowKind::History, new_line);
+            if history_times_rows > 0 {
+                let range = start_ix..end_ix_succ;
+                let new_line = history_times_row(&logger.history, range, padding, size.cols, now);
+                writer.line(RowKind::HistoryTimes, new_line);
+            }
+        };
+
+        // === Menu ===
+
+        let first_line = logger.history.start();
+        let history = logger.groups.next_line.map(|line| {
+            (line.0.saturating_sub(first_line), logger.next_line_id.0.saturating_sub(first_line))
+        });
+        let tooltip = logger.hover.and_then(|row| tooltip(&writer, row, size.cols));
+        let frame_stats = logger.show_frame_stats.then_some(logger.frame_stats);
+        let mode = MenuMode::new(
+            logger.goto.as_deref(),
+            logger.palette.is_some(),
+            logger.confirm_reset,
+            logger.line_cursor.is_some()
+        );
+        let actions = &logger.group_actions;
+        let segments = menu_segments(&groups, mode, history, frame_stats, tooltip, actions);
+        let new_line = style.menu(&segments, size.cols);
+        writer.line(RowKind::Menu, new_line);
+
+        // === Debug Panel ===
+
+        let fps = logger.frame_counter.fps;
+        let debug_lines = &logger.debug_lines;
+        for line in debug_panel(debug_lines, logger.debug_scroll, debug_rows, size.cols, fps) {
+            writer.line(RowKind::Debug, line);
+        }
+
+        // === Draw ===
+
+        let compose_time = compose_start.elapsed();
+        let draw_start = Instant::now();
+        // The frame is written at once, which also measures its size.
+        let mut out = Vec::new();
+        queue_clear(&mut out, &mut writer, size, redraw)?;
+        let mut changed_lines = 0;
+        // Rows below the terminal are not drawn, as the cursor cannot move past the last row. The
+        // attributes are reset before every row, as an unterminated SGR sequence in the content of
+        // the previous row, e.g. of a killed process, would bleed into it and into its clearing.
+        for (i, line) in writer.lines.iter_mut().enumerate().take(size.rows) {
+            if line.changed {
+                crossterm::queue!(
+                        out,
+                        crossterm::cursor::MoveTo(0, i as u16),
+                        crossterm::style::SetAttribute(crossterm::style::Attribute::Reset),
+                        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
+                        crossterm::style::Print(&line.content)
+                    )?;
+                line.changed = false;
+                changed_lines += 1;
+            }
+        }
+        logger.queue_frame_end(&mut out)?;
+        stdout.write_all(&out)?;
+        stdout.flush()?;
+        #[cfg(feature = "serde")]
+        logger.record_frame(size, &out);
+        let draw_time = draw_start.elapsed();
+        let bytes_written = out.len();
+        logger.frame_stats =
+            FrameStats { lock_wait_time, compose_time, draw_time, changed_lines, bytes_written };
+        Result::<(), Error>::Ok(())
+    })?
+}
+
+/// Handles the input event arriving within the frame time, if any. Returns whether the UI keeps
+/// running.
+fn handle_event(shared: &SharedLogger) -> Result<bool> {
+    use crossterm::event;
+    if event::poll(shared.modify(|l| l.frame_time())?)? {
+        shared.modify(|l| l.last_activity = Some(SystemTime::now()))?;
+        return on_event(shared, event::read()?);
+    }
+    Ok(true)
+}
+
+/// Handles the input event. Returns whether the UI keeps running.
+fn on_event(shared: &SharedLogger, input: crossterm::event::Event) -> Result<bool> {
+    use crossterm::event;
+    match input {
+        event::Event::Key(event) if shared.modify(|l| l.context_menu.is_some())? =>
+            on_context_menu_key(shared, event.code)?,
+        event::Event::Key(event) if shared.modify(|l| l.palette.is_some())? =>
+            on_palette_key(shared, event)?,
+        event::Event::Key(event) if shared.modify(|l| l.confirm_reset)? =>
+            on_confirm_reset_key(shared, event.code)?,
+        event::Event::Key(event) if shared.modify(|l| l.line_cursor.is_some())? =>
+            on_line_mode_key(shared, event.code)?,
+        event::Event::Key(event) if shared.modify(|l| l.goto.is_some())?
+            && !event.modifiers.contains(event::KeyModifiers::CONTROL) => {
+            match event.code {
+                event::KeyCode::Char(char) if char.is_ascii_alphanumeric() =>
+                    goto_push(shared, char)?,
+                event::KeyCode::Enter => goto_commit(shared)?,
+                event::KeyCode::Backspace => shared.modify(|l| {
+                    l.goto.as_mut().map(|prefix| prefix.pop());
+                })?,
+                _ => shared.modify(|l| l.goto = None)?,
+            }
+        }
+        event::Event::Key(event) => {
+            let event = shared.modify(|l| l.settings.keymap.map(event))?;
+            if shared.modify(|l| l.settings.quit_policy.quits(event))? {
+                return Ok(false);
+            }
+            let is_palette_key = event.code == event::KeyCode::Char('p')
+                && event.modifiers.contains(event::KeyModifiers::CONTROL);
+            if is_palette_key {
+                shared.open_palette()?;
+                return Ok(true);
+            }
+            if let event::KeyCode::Char(key) = event.code && shared.run_group_action(key)? {
+                return Ok(true);
+            }
+
+            match event.code {
+                event::KeyCode::Char(char) => {
+                    match char {
+                        '0' => shared.modify(|l| l.invert_selection()),
+                        '\'' => shared.modify(|l| l.goto = Some(default())),
+                        'g' if shared.modify(|l| l.groups.len() > SINGLE_CHAR_LABELS)? =>
+                            shared.modify(|l| l.goto = Some(default())),
+                        '/' if shared.modify(|l| l.selected.is_empty())? =>
+                            shared.open_palette(),
+                        'O' => shared.cycle_group_order(),
+                        'F' => shared.toggle_hidden_groups(),
+                        'D' => shared.toggle_debug_panel(),
+                        'P' => shared.toggle_frame_stats(),
+                        'R' => shared.modify(|l| if l.allows_local_changes("Reset") {
+                            l.confirm_reset = true
+                        }),
+                        'S' => shared.toggle_summary(),
+                        'T' => shared.toggle_history_times(),
+                        'L' => shared.toggle_line_mode(),
+                        'N' => shared.scroll_to_next_annotation(),
+                        'Y' => shared.copy_selected_group(),
+                        'W' => shared.write_selected_group(),
+                        'H' => shared.write_frame_html(),
+                        'V' => shared.modify_all_groups(|mut g| if g.selected {
+                            g.min_level = g.min_level.next();
+                            g.scroll = None;
+                        }),
+                        '=' => shared.modify_all_groups(|mut g| if g.selected {
+                            g.compact = !g.compact
+                        }),
+                        _ => {
+                            if let Some(index) = group_char_to_index(char) {
+                                toggle_labeled_group(shared, index)?;
+                            }
+                            Ok(())
+                        }
+                    }
+                }
+                event::KeyCode::Enter => shared.modify_all_groups(|mut g| if g.selected {
+                    g.collapsed = Some(!g.as_ref().is_collapsed())
+                }),
+                event::KeyCode::Esc => shared.modify(|l| {
+                    l.groups.zoomed = None;
+                    l.set_selection([]);
+                }),
+                event::KeyCode::PageUp => shared.modify(|l| {
+                    if l.selected.is_empty() && l.group_viewport.is_paged() {
+                        l.page_groups(-1)
+                    } else if !l.debug_hidden {
+                        l.scroll_debug(l.settings.debug_rows as isize)
+                    }
+                }),
+                event::KeyCode::PageDown => shared.modify(|l| {
+                    if l.selected.is_empty() && l.group_viewport.is_paged() {
+                        l.page_groups(1)
+                    } else if !l.debug_hidden {
+                        l.scroll_debug(-(l.settings.debug_rows as isize))
+                    }
+                }),
+                event::KeyCode::Down => shared.shift_selection(1),
+                event::KeyCode::Up => shared.shift_selection(-1),
+                event::KeyCode::Left => {
+                    let mult = if event.modifiers.contains(event::KeyModifiers::SHIFT) {
+                        10
+                    } else {
+                        1
+                    };
+                    shared.shift_history(-mult)
+                },
+                event::KeyCode::Right => {
+                    let mult = if event.modifiers.contains(event::KeyModifiers::SHIFT) {
+                        10
+                    } else {
+                        1
+                    };
+                    shared.shift_history(mult)
+                },
+                _ => { Ok (()) }
+            }?
+        }
+        event::Event::Mouse(event) => {
+            let row = framebuffer::LineIndex(event.row as usize);
+            let column = event.column as usize;
+            match event.kind {
+                event::MouseEventKind::Moved =>
+                    shared.modify(|l| l.hover = Some(row))?,
+                event::MouseEventKind::ScrollUp =>
+                    on_mouse_scroll(shared, row, -1, event.modifiers)?,
+                event::MouseEventKind::ScrollDown =>
+                    on_mouse_scroll(shared, row, 1, event.modifiers)?,
+                event::MouseEventKind::Down(button) =>
+                    on_mouse_down(shared, row, column, button)?,
+                _ => {}
+            }
+        }
+        event::Event::Paste(text) => on_paste(shared, &text)?,
+        event::Event::FocusLost => shared.modify(|l| l.unfocused = true)?,
+        event::Event::FocusGained => shared.modify(|l| l.unfocused = false)?,
+        _ => {}
+    }
+    Ok(true)
+}
+
+// We start naming from 1, as `0` has a special meaning.
+fn group_char_to_index(c: char) -> Option<usize> {
+    match c {
+        '1'..='9' => Some(c as usize - '0' as usize),
+        'a'..='z' => Some(c as usize - 'a' as usize + 10),
+        _ => None,
+    }.map(|i| i - 1)
+}
+
+// We start naming from 1, as `0` has a special meaning.
+fn index_to_group_char(d: usize) -> Option<char> {
+    match d {
+        0..=8 => Some((d as u8 + b'1') as char),
+        9..=34 => Some((d as u8 - 9 + b'a') as char),
+        _ => None
+    }
+}
+
+/// Label of the group in the history tiles. Tiles are one cell wide, so groups beyond the 35th,
+/// which have two-character labels, are shown as `…` there. Their headers show the full labels,
+/// see [`index_to_group_label`].
+fn index_to_group_char_opt(d: usize) -> char {
+    index_to_group_char(d).unwrap_or('…')
+}
+
+// ====================
+// === Group Labels ===
+// ====================
+
+/// Base of the group labels. Labels are base-36 numbers of `index + 1`, so the first 35 groups
+/// get the single-character labels `1-9 a-z`, and groups beyond get two-character ones.
+const GROUP_LABEL_BASE: usize = 36;
+
+/// Maximum number of characters in a group label.
+const GROUP_LABEL_MAX_LEN: usize = 2;
+
+/// Number of groups with single-character labels. With more groups, `g` enters the goto mode
+/// like `'` instead of selecting the 16th group, which stays selectable as `'g` followed by Enter.
+const SINGLE_CHAR_LABELS: usize = GROUP_LABEL_BASE - 1;
+
+fn group_label_to_index(label: &str) -> Option<usize> {
+    let mut value = 0;
+    for char in label.chars() {
+        let digit = if char == '0' { 0 } else { group_char_to_index(char)? + 1 };
+        value = value * GROUP_LABEL_BASE + digit;
+    }
+    value.checked_sub(1)
+}
+
+fn index_to_group_label(d: usize) -> Option<String> {
+    let mut value = d + 1;
+    let mut label = Vec::new();
+    while value > 0 {
+        let digit = value % GROUP_LABEL_BASE;
+        label.push(if digit == 0 { '0' } else { index_to_group_char(digit - 1)? });
+        value /= GROUP_LABEL_BASE;
+    }
+    (label.len() <= GROUP_LABEL_MAX_LEN).then(|| label.into_iter().rev().collect())
+}
+
+/// Maximum time between two clicks on the same row for them to count as a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Scrolling over a group scrolls its lines. Over the history strip and the scroll bar, or
+/// anywhere with `Shift`, the history is stepped instead, and over the debug panel, the panel is
+/// scrolled. Over the other rows, the group list is paged if it does not fit the terminal.
+fn on_mouse_scroll(
+    shared: &SharedLogger,
+    row: framebuffer::LineIndex,
+    shift: isize,
+    modifiers: crossterm::event::KeyModifiers
+) -> Result {
+    let kind = shared.row_kind(row)?;
+    let history_row = matches!(
+        kind,
+        Some(RowKind::ScrollBar | RowKind::History | RowKind::HistoryTimes)
+    );
+    if history_row || modifiers.contains(crossterm::event::KeyModifiers::SHIFT) {
+        return shared.shift_history(shift);
+    }
+    if let Some(group_id) = kind.and_then(RowKind::group) {
+        return shared.scroll(group_id, shift);
+    }
+    match kind {
+        Some(RowKind::Debug) => shared.modify(|l| l.scroll_debug(-shift)),
+        _ => shared.modify(|l| {
+            if l.group_viewport.is_paged() {
+                l.page_groups(shift);
+            }
+        }),
+    }
+}
+
+/// Clicking the collapse toggle of a header toggles the collapse, and clicking elsewhere selects
+/// the group. Double-clicking a header zooms the group, or unzooms it. Right-clicking a group
+/// opens its context menu. If the menu is open, any click runs the clicked action or closes it.
+fn on_mouse_down(
+    shared: &SharedLogger,
+    row: framebuffer::LineIndex,
+    column: usize,
+    button: crossterm::event::MouseButton
+) -> Result {
+    let size = shared.modify(|l| l.frame_buffer.size())?;
+    if shared.modify(|l| l.palette.take().is_some())? {
+        return Ok(());
+    }
+    if let Some(menu) = shared.modify(|l| l.context_menu)? {
+        let action = menu.action_at(row, column, size).unwrap_or(context_menu::Action::Close);
+        return run_context_menu_action(shared, action);
+    }
+    let Some(kind) = shared.row_kind(row)? else { return Ok(()) };
+    let Some(group_id) = kind.group() else { return Ok(()) };
+    if button == crossterm::event::MouseButton::Right {
+        return shared.modify(|l| {
+            let group = l.groups.get(*group_id);
+            l.context_menu = group.map(|group| context_menu::ContextMenu::new(group, row, column));
+        });
+    }
+    let is_header = kind == RowKind::Header(group_id);
+    let now = SystemTime::now();
+    let is_double_click = shared.modify(|l| {
+        let last_click = l.last_click.replace((now, row));
+        last_click.is_some_and(|(time, last_row)| last_row == row
+            && now.duration_since(time).is_ok_and(|t| t <= DOUBLE_CLICK_TIME))
+    })?;
+    if is_header && is_double_click {
+        return shared.modify(|l| {
+            l.last_click = None;
+            l.groups.toggle_zoom(group_id);
+        });
+    }
+    let is_toggle = is_header && shared.modify(|l| {
+        let groups = l.groups.nonempty();
+        let group = groups.iter().find(|g| g.id == group_id);
+        let toggle = group.and_then(|g| l.style.collapse_toggle(g));
+        toggle.is_some_and(|columns| columns.contains(&column))
+    })?;
+    if is_toggle {
+        shared.modify_group(group_id, |mut g| g.collapsed = Some(!g.as_ref().is_collapsed()))
     } else {
-        modify_logger(|logger| logger.disabled = true)?;
-        Ok(())
+        shared.modify(|l| l.set_selection([group_id]))
+    }
+}
+
+fn on_context_menu_key(shared: &SharedLogger, code: crossterm::event::KeyCode) -> Result {
+    use crossterm::event::KeyCode;
+    let shift_selection = |shift: isize| shared.modify(|l| {
+        if let Some(menu) = &mut l.context_menu {
+            menu.shift_selection(shift);
+        }
+    });
+    match code {
+        KeyCode::Up => shift_selection(-1),
+        KeyCode::Down => shift_selection(1),
+        KeyCode::Enter => {
+            let action = shared.modify(|l| l.context_menu.map(|t| t.selected_action()))?;
+            run_context_menu_action(shared, action.unwrap_or(context_menu::Action::Close))
+        }
+        _ => shared.modify(|l| l.context_menu = None),
     }
 }
 
-pub fn run() -> Result {
-    let mut stdout = std::io::stdout();
-    let mut prev_size = terminal::Size::default();
+/// Resets the logger if confirmed with `y`. Other keys cancel the reset.
+fn on_confirm_reset_key(shared: &SharedLogger, code: crossterm::event::KeyCode) -> Result {
+    match code {
+        crossterm::event::KeyCode::Char('y') => shared.reset(),
+        _ => shared.modify(|l| l.confirm_reset = false),
+    }
+}
+
+/// Types the query of the palette and moves its highlight. Enter selects the highlighted group,
+/// and Esc closes the palette.
+fn on_palette_key(shared: &SharedLogger, event: crossterm::event::KeyEvent) -> Result {
+    use crossterm::event::KeyCode;
+    let control = event.modifiers.contains(crossterm::event::KeyModifiers::CONTROL);
+    shared.modify(|l| match event.code {
+        KeyCode::Char(char) if !control => if let Some(palette) = &mut l.palette {
+            palette.push(char);
+        }
+        KeyCode::Backspace => if let Some(palette) = &mut l.palette {
+            palette.pop();
+        }
+        KeyCode::Up => l.shift_palette_selection(-1),
+        KeyCode::Down => l.shift_palette_selection(1),
+        KeyCode::Enter => l.commit_palette(),
+        KeyCode::Esc | KeyCode::Char(_) => l.palette = None,
+        _ => {}
+    })
+}
+
+/// Moves the focused line, or opens its location. Other keys leave the line mode.
+fn on_line_mode_key(shared: &SharedLogger, code: crossterm::event::KeyCode) -> Result {
+    use crossterm::event::KeyCode;
+    match code {
+        KeyCode::Up => shared.move_line_cursor(-1),
+        KeyCode::Down => shared.move_line_cursor(1),
+        KeyCode::PageUp => shared.move_line_cursor(-10),
+        KeyCode::PageDown => shared.move_line_cursor(10),
+        KeyCode::Char('o') => shared.open_focused_line().map(|_| ()),
+        _ => shared.modify(|l| l.line_cursor = None),
+    }
+}
 
-    loop {
-        match on_frame(&mut stdout, &mut prev_size) {
-            Ok(true) => {}
-            Ok(false) => break,
-            Err(error) => {
-                modify_logger(|logger| {
-                    logger.debug_lines.push(format!("Error: {error}"));
-                })?;
-            }
+/// Closes the context menu and runs the action on its group.
+fn run_context_menu_action(shared: &SharedLogger, action: context_menu::Action) -> Result {
+    let Some(menu) = shared.modify(|l| l.context_menu.take())? else { return Ok(()) };
+    let group_id = menu.group;
+    match action {
+        context_menu::Action::ToggleCollapse =>
+            shared.modify_group(group_id, |mut g| g.collapsed = Some(!g.as_ref().is_collapsed())),
+        context_menu::Action::ToggleZoom => {
+            let zoomed = shared.modify(|l| l.groups.zoomed)?;
+            shared.zoom_group((zoomed != Some(group_id)).then_some(group_id))
         }
+        context_menu::Action::TogglePin =>
+            shared.modify_group(group_id, |mut g| g.pinned = !g.pinned),
+        context_menu::Action::CopyLines => shared.modify(|l| l.copy_group(group_id)),
+        context_menu::Action::Clear if shared.modify(|l| l.allows_local_changes("Clear"))? =>
+            shared.clear_group(group_id),
+        context_menu::Action::Clear => Ok(()),
+        context_menu::Action::Kill => {
+            let kill_handler = shared.modify(|l| {
+                l.groups.get(*group_id).and_then(|group| group.kill_handler.clone())
+            })?;
+            if let Some(handler) = kill_handler { handler.call(group_id) }
+            Ok(())
+        }
+        context_menu::Action::Close => Ok(()),
+    }
+}
+
+/// Types the pasted text into the palette or the goto prompt if any is open. Otherwise the text is
+/// ignored, so that it does not trigger shortcuts.
+fn on_paste(shared: &SharedLogger, text: &str) -> Result {
+    // Only the first line is typed, as the query is a single line.
+    let query = text.lines().next().unwrap_or_default();
+    let typed = shared.modify(|l| l.palette.as_mut().map(|palette| {
+        query.chars().for_each(|char| palette.push(char));
+    }))?;
+    if typed.is_some() { return Ok(()) }
+    for char in text.chars().filter(char::is_ascii_alphanumeric) {
+        if !shared.modify(|l| l.goto.is_some())? { break }
+        goto_push(shared, char)?;
     }
     Ok(())
 }
 
-fn history_tile(char: char, tag: group::StatusTag, active: bool) -> String {
-    match (active, tag) {
-        (true,  group::StatusTag::Success) => char.black().on_green(),
-        (true,  group::StatusTag::Error)   => char.black().on_red(),
-        (false, group::StatusTag::Success) => char.dark_green().on_green(),
-        (false, group::StatusTag::Error)   => char.dark_red().on_red(),
-    }.to_string()
+fn goto_push(shared: &SharedLogger, char: char) -> Result {
+    let complete = shared.modify(|logger| {
+        let prefix = logger.goto.get_or_insert_with(default);
+        prefix.push(char.to_ascii_lowercase());
+        prefix.len() >= GROUP_LABEL_MAX_LEN
+    })?;
+    if complete { goto_commit(shared) } else { Ok(()) }
 }
 
-fn history_tile_active((char, tag): (char, group::StatusTag)) -> String {
-    history_tile(char, tag, true)
+fn goto_commit(shared: &SharedLogger) -> Result {
+    let label = shared.modify(|logger| logger.goto.take())?.unwrap_or_default();
+    if let Some(index) = group_label_to_index(&label) {
+        toggle_labeled_group(shared, index)?;
+    }
+    Ok(())
 }
 
-fn history_tile_non_active((char, tag): (char, group::StatusTag)) -> String {
-    history_tile(char, tag, false)
+/// Toggles the selection of the displayed group with the given label index. Labels are derived
+/// from the group ids, so they do not change when groups move, e.g. in the history view.
+fn toggle_labeled_group(shared: &SharedLogger, index: usize) -> Result {
+    shared.modify(|logger| {
+        let group_id = group::Id(index);
+        if logger.displayed_ids().contains(&group_id) {
+            logger.toggle_selection(group_id);
+        }
+    })
 }
 
-fn on_frame(stdout: &mut std::io::Stdout, prev_size: &mut terminal::Size) -> Result<bool> {
-    let size = terminal::Size::current();
-    let bottom_menu_rows = 3;
-    let header_and_footer_rows = 2;
-    let default_debug_rows = 5;
-    let no_menu_rows = size.rows.saturating_sub(bottom_menu_rows);
+// =============
+// === Tests ===
+// =============
 
-    modify_logger(|logger| {
-        let mut writer = framebuffer::Writer::new(&mut logger.frame_buffer);
-        if size != *prev_size {
-            writer.clear();
-            *prev_size = size;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let debug_rows_if_any = default_debug_rows.min(no_menu_rows);
-        let debug_rows = if logger.debug_lines.is_empty() { 0 } else { debug_rows_if_any };
-        let content_rows = no_menu_rows - debug_rows;
+    fn contents(logger: &SharedLogger, selector: &[&str]) -> Result<Vec<String>> {
+        let group_id = logger.group_id(selector)?;
+        let lines = logger.read_lines(group_id, 0..usize::MAX)?;
+        Ok(lines.into_iter().map(|(_, _, content)| content).collect())
+    }
 
-        let groups = logger.groups.nonempty();
-        let style = &mut logger.style;
+    #[test]
+    fn logger_instances_are_independent() -> Result {
+        let first = SharedLogger::default();
+        let second = SharedLogger::default();
+        first.log(&["build"][..], None, "first");
+        second.log(&["build"][..], None, "second");
+        second.log(&["test"][..], None, "second");
+        assert_eq!(contents(&first, &["build"])?, ["first"]);
+        assert_eq!(contents(&second, &["build"])?, ["second"]);
+        assert!(!first.group_exists(&["test"][..])?);
+        assert!(second.group_exists(&["test"][..])?);
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_lines_do_not_advance_history() -> Result {
+        let logger = SharedLogger::default();
+        let group_id = logger.create_group(&["net"][..])?;
+        logger.set_group_dedup(group_id, true)?;
+        for _ in 0..3 {
+            logger.log(&["net"][..], None, "connection refused, retrying");
+        }
+        logger.log(&["net"][..], Status::error(), "connection refused, retrying");
+        logger.log(&["net"][..], None, "connected");
+        logger.modify(|logger| {
+            let lines = &logger.groups[*group_id].lines;
+            let repeats = lines.iter().map(|t| (t.timestamp, t.repeat)).collect::<Vec<_>>();
+            assert_eq!(repeats, [(LineId(0), 3), (LineId(1), 1), (LineId(2), 1)]);
+            assert_eq!(logger.history.len(), 3);
+            assert_eq!(logger.next_line_id, LineId(3));
+            if let Some(line) = logger.groups[*group_id].lines.last_mut() {
+                line.repeat = u32::MAX;
+            }
+        })?;
+        logger.log(&["net"][..], None, "connected");
+        let last_repeat = logger.modify(|logger| logger.groups[*group_id].lines.last()
+            .map(|line| line.repeat))?;
+        assert_eq!(last_repeat, Some(u32::MAX));
+        Ok(())
+    }
 
-        let collapsed_count = groups.iter().filter(|g| g.is_collapsed()).count();
-        let expanded_count = groups.len() - collapsed_count;
-        let expanded_rows = content_rows.saturating_sub(collapsed_count);
-        let (lines_per_group, mut lines_left) = if expanded_count == 0 { (0, 0) } else {
-            ((expanded_rows / expanded_count), (expanded_rows % expanded_count))
+    #[test]
+    fn level_filter_hides_lower_levels() -> Result {
+        let logger = SharedLogger::default();
+        logger.log_at(&["app"][..], Level::Debug, None, "debug");
+        logger.log_at(&["app"][..], Level::Warn, None, "warn");
+        logger.log(&["app"][..], None, "info");
+        let group_id = logger.group_id(&["app"])?;
+        let contents = |logger: &mut Logger| {
+            let view = LineRange { data: &logger.groups[*group_id].state, next_line: None };
+            let lines = view.displayed_lines();
+            (0..lines.len()).map(|ix| lines.at(ix).log.content.clone()).collect::<Vec<_>>()
         };
+        assert_eq!(logger.modify(contents)?, ["debug", "warn", "info"]);
+        logger.set_level_filter(group_id, Level::Info)?;
+        assert_eq!(logger.modify(contents)?, ["warn", "info"]);
+        Ok(())
+    }
 
-        for (group_ix, group) in groups.iter().enumerate().map(|t| (group::Id(t.0), t.1)) {
-            let new_line = style.header(group, group_ix, &group.header);
-            writer.line(Some(group_ix), None, new_line);
-            if !group.is_collapsed() {
-                let extra_line = if lines_left == 0 { 0 } else {
-                    lines_left -= 1;
-                    1
-                };
-                let height = lines_per_group + extra_line;
-                let space = height.saturating_sub(header_and_footer_rows);
-                let state = group.state();
-                let lines = state.view_lines();
-                let (scrolled, start_line) = if let Some(scroll) = group.scroll {
-                    (true, scroll)
-                } else {
-                    (false, lines.len().saturating_sub(space))
-                };
-                for line_index_rel in 0 .. space {
-                    let is_last_line = line_index_rel == space - 1;
-                    let line_ix = group::LineIndex(start_line + line_index_rel);
-                    let content = if scrolled && is_last_line {
-                        "..."
-                    } else {
-                        lines.get(*line_ix).map_or_else(default, |t| t.log.content.as_str())
-                    };
-                    let new_line = style.log_line(group, group_ix, content);
-                    writer.line(Some(group_ix), Some(line_ix), new_line);
+    #[test]
+    fn status_callbacks_fire_once_per_transition() -> Result {
+        let logger = SharedLogger::default();
+        let changes: Arc<Mutex<Vec<(group::StatusTag, bool)>>> = default();
+        let finished: Arc<Mutex<Vec<(group::StatusTag, bool)>>> = default();
+        let changes2 = changes.clone();
+        let logger2 = logger.clone();
+        logger.on_status_change(move |path, status| {
+            if path == ["build"] {
+                changes2.lock().map(|mut t| t.push((status.tag, status.finished))).ok();
+                logger2.log(&["events"][..], None, "status changed");
+            }
+        })?;
+        let finished2 = finished.clone();
+        logger.on_group_finished(&["build"][..], move |_, status| {
+            finished2.lock().map(|mut t| t.push((status.tag, status.finished))).ok();
+        })?;
+        logger.log(&["build"][..], None, "compiling");
+        logger.log(&["build"][..], None, "compiling");
+        logger.log(&["build"][..], Status::error(), "failed");
+        logger.log(&["build"][..], Status::error(), "failed again");
+        logger.log(&["build"][..], Status::error().finished(), "done");
+        logger.log(&["build"][..], Status::error().finished(), "summary");
+        let changes = changes.lock().map(|t| t.clone()).unwrap_or_default();
+        let finished = finished.lock().map(|t| t.clone()).unwrap_or_default();
+        let error = group::StatusTag::Error;
+        assert_eq!(changes, [(error, false), (error, true)]);
+        assert_eq!(finished, [(error, true)]);
+        assert_eq!(contents(&logger, &["events"])?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn failure_notifications_are_capped() -> Result {
+        let logger = Builder::default().notifications(Notify::Both).build();
+        for i in 0..MAX_PENDING_NOTIFICATIONS * 2 {
+            logger.log(&[format!("job{i}")][..], Status::error().finished(), "failed");
+        }
+        logger.log(&["job0"][..], Status::error().finished(), "failed again");
+        let notifications = logger.modify(|logger| logger.take_notifications())?;
+        assert_eq!(notifications.matches('\x07').count(), MAX_PENDING_NOTIFICATIONS + 1);
+        assert!(notifications.contains("\x1b]9;job0 failed\x07"));
+        assert!(logger.modify(|logger| logger.take_notifications())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn tabular_columns_fit_the_displayed_lines() -> Result {
+        let logger = SharedLogger::default();
+        let group_id = logger.create_group(&["bench"][..])?;
+        logger.set_group_tabular(group_id, true)?;
+        logger.log(&["bench"][..], None, "a_very_long_benchmark_name\t1 ms");
+        logger.log(&["bench"][..], None, "parse\t12 ms");
+        logger.log(&["bench"][..], None, "render    130 ms");
+        logger.modify(|logger| {
+            assert_eq!(body_text(logger, group_id, 2, 40), ["parse   12 ms", "render  130 ms"]);
+            let all = body_text(logger, group_id, 3, 40);
+            assert_eq!(all[2], format!("render{}130 ms", " ".repeat(22)));
+        })
+    }
+
+    #[test]
+    fn rows_refer_to_groups_by_id() -> Result {
+        let logger = SharedLogger::default();
+        for name in ["first", "second"] {
+            logger.log(&[name][..], None, "line");
+        }
+        let second = logger.group_id(&["second"][..])?;
+        logger.set_group_pinned(second, true)?;
+        let size = terminal::Size { cols: 80, rows: 24 };
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        let headers = logger.modify(|l| {
+            let mut rows = l.frame_buffer.row_kind.iter().collect::<Vec<_>>();
+            rows.sort_by_key(|(row, _)| **row);
+            rows.into_iter().filter_map(|(row, kind)| match kind {
+                RowKind::Header(group_id) => Some((*row, *group_id)),
+                _ => None,
+            }).collect::<Vec<_>>()
+        })?;
+        let group_ids = headers.iter().map(|(_, group_id)| *group_id).collect::<Vec<_>>();
+        assert_eq!(group_ids, [second, group::Id(0)]);
+        let (row, _) = headers[0];
+        assert_eq!(logger.group_to_lines(second)?.map(|lines| lines.0), Some(row));
+        on_mouse_down(&logger, row, 40, crossterm::event::MouseButton::Left)?;
+        assert_eq!(logger.modify(|l| l.selected.iter().copied().collect::<Vec<_>>())?, [second]);
+        Ok(())
+    }
+
+    /// Kinds of the drawn rows with their text without styles, in the order of the rows.
+    fn drawn_rows(logger: &SharedLogger) -> Result<Vec<(RowKind, String)>> {
+        logger.modify(|l| {
+            let mut rows = l.frame_buffer.row_kind.iter().collect::<Vec<_>>();
+            rows.sort_by_key(|(row, _)| **row);
+            rows.into_iter().map(|(row, kind)| {
+                (*kind, format::strip_ansi(&l.frame_buffer.lines[row.0].content))
+            }).collect()
+        })
+    }
+
+    #[test]
+    fn header_labels_match_the_history_tiles_while_scrubbing() -> Result {
+        let logger = SharedLogger::default();
+        for name in ["a", "b", "c", "a"] {
+            logger.log(&[name][..], None, "line");
+        }
+        logger.modify(|l| l.groups.order = group::Order::LastActivity)?;
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let tiles = [0, 1, 2, 0].map(index_to_group_char_opt).iter().collect::<String>();
+        let mut orders = Vec::new();
+        for shift in [0, -1, -1] {
+            logger.shift_history(shift)?;
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            let mut order = Vec::new();
+            for (kind, text) in drawn_rows(&logger)? {
+                match kind {
+                    RowKind::Header(group_id) => {
+                        let label = index_to_group_label(group_id.0).unwrap_or_default();
+                        assert!(text.contains(&format!("[{label}]")), "{text}");
+                        order.push(group_id.0);
+                    }
+                    RowKind::History => assert_eq!(text.trim(), tiles),
+                    _ => {}
                 }
-                let new_line = style.footer(group, group_ix, &group.footer);
-                writer.line(Some(group_ix), None, new_line);
             }
+            orders.push(order);
         }
-        for _ in writer.line.0 .. content_rows {
-            writer.line(None, None, "".to_string());
+        assert_eq!(orders, [[0, 2, 1], [2, 1, 0], [1, 0, 2]]);
+        Ok(())
+    }
+
+    #[test]
+    fn bottom_rows_stay_within_short_terminals() -> Result {
+        use RowKind::*;
+        let logger = SharedLogger::default();
+        for name in ["a", "b", "c"] {
+            logger.log(&[name][..], Status::error(), "line");
         }
+        let draw = |rows| -> Result<Vec<RowKind>> {
+            let mut out = Vec::new();
+            draw_frame(&logger, &mut out, &mut default(), terminal::Size { cols: 40, rows })?;
+            // Rows of the cursor moves, which are 1-based in the escape sequences.
+            let out = String::from_utf8_lossy(&out);
+            let moves = out.split("\x1b[").skip(1).filter_map(|t| {
+                let (row, rest) = t.split_once(';')?;
+                rest.split_once('H')?.0.parse::<usize>().ok()?;
+                row.parse::<usize>().ok()
+            }).collect::<Vec<_>>();
+            assert!(moves.iter().all(|row| *row <= rows), "{moves:?}");
+            Ok(drawn_rows(&logger)?.into_iter().map(|(kind, _)| kind).collect())
+        };
+        assert_eq!(draw(4)?, [MoreGroups, ScrollBar, History, Menu]);
+        assert_eq!(draw(3)?, [ScrollBar, History, Menu]);
+        logger.modify(|l| l.push_debug_line("debug".into()))?;
+        assert_eq!(draw(4)?, [ScrollBar, History, Menu, Debug]);
+        assert_eq!(draw(3)?, [ScrollBar, History, Menu]);
+        Ok(())
+    }
 
-        // === Scroll Bar ===
+    #[test]
+    fn tees_mirror_the_error_lines_of_their_sources() -> Result {
+        use group::StatusTag::*;
+        let logger = SharedLogger::default();
+        logger.add_tee(&["*"][..], &["errors"][..], |log| log.status.is_error())?;
+        logger.log(&["a"][..], None, "compiling");
+        logger.log(&["a"][..], Status::error(), "failed");
+        logger.log(&["b"][..], Status::error(), "panicked");
+        let lines = logger.read_lines(logger.group_id(&["errors"])?, 0..usize::MAX)?;
+        let lines = lines.into_iter().map(|(_, status, content)| (status.tag, content));
+        let lines = lines.collect::<Vec<_>>();
+        assert_eq!(lines, [(Error, "[a] failed".into()), (Error, "[b] panicked".into())]);
+        let a = logger.group_id(&["a"])?;
+        let b = logger.group_id(&["b"])?;
+        let errors = logger.group_id(&["errors"])?;
+        let history = logger.modify(|l| l.history.iter().collect::<Vec<_>>())?;
+        let expected = [(a, Success), (a, Error), (errors, Error), (b, Error), (errors, Error)];
+        assert_eq!(history, expected);
+        assert!(logger.add_tee(&["errors"][..], &["a"][..], |_| true).is_err());
+        logger.add_tee(&["c"][..], &["d"][..], |_| true)?;
+        assert!(logger.add_tee(&["d"][..], &["c"][..], |_| true).is_err());
+        assert!(logger.add_tee(&["c"][..], &["c"][..], |_| true).is_err());
+        Ok(())
+    }
 
-        {
-            let line_count = *logger.next_line_id;
-            let len_f = if line_count == 0 { 1.0 } else {
-                (size.cols as f32 / line_count as f32).max(1.0)
-            };
-            let len = len_f.ceil() as usize;
-            let visible_line_count = logger.groups.next_line;
-            let shift = visible_line_count.map(|t| *t as f32 / line_count as f32).unwrap_or(1.0);
-            let left_space_count = ((size.cols - len) as f32 * shift) as usize;
-            let left_space = " ".repeat(left_space_count);
-            let bar = "▂".repeat(len).bold().dark_green();
-            writer.line(None, None, format!("{left_space}{bar}"))
+    #[test]
+    fn cancelled_groups_stay_expanded_and_are_crossed_out() -> Result {
+        let logger = SharedLogger::default();
+        logger.modify(|l| l.set_auto_collapse(group::AutoCollapse::collapse_on_success()))?;
+        logger.log(&["done"][..], Status::ok().finished(), "done");
+        logger.log(&["stop"][..], Status::ok().progress(0.5), "running");
+        let done = logger.group_id(&["done"])?;
+        let stop = logger.group_id(&["stop"])?;
+        logger.cancel_group(done)?;
+        logger.cancel_group(stop)?;
+        logger.cancel_group(stop)?;
+        assert_eq!(contents(&logger, &["done"])?, ["done"]);
+        assert_eq!(contents(&logger, &["stop"])?, ["running", "cancelled"]);
+        let last = logger.read_lines(stop, 1..2)?.into_iter().map(|(_, status, _)| status);
+        let last = last.map(|t| (t.tag, t.progress, t.finished)).collect::<Vec<_>>();
+        assert_eq!(last, [(group::StatusTag::Cancelled, Some(0.5), true)]);
+        let size = terminal::Size { cols: 80, rows: 24 };
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        let rows = drawn_rows(&logger)?;
+        let has_content = |id| rows.iter().any(|(kind, _)| matches!(kind,
+            RowKind::Content(group_id, _) if *group_id == id));
+        assert!(!has_content(done));
+        assert!(has_content(stop));
+        let header = logger.modify(|l| l.frame_buffer.row_kind.iter()
+            .find(|(_, kind)| **kind == RowKind::Header(stop))
+            .map(|(row, _)| l.frame_buffer.lines[row.0].content.clone()))?;
+        let crossed_out = crossterm::style::Attribute::CrossedOut.to_string();
+        assert!(header.is_some_and(|header| header.contains(&crossed_out)));
+        Ok(())
+    }
+
+    #[test]
+    fn frame_stats_count_the_drawn_rows_and_bytes() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], None, "compiling");
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let mut resize = default();
+        let mut out = Vec::new();
+        draw_frame(&logger, &mut out, &mut resize, size)?;
+        let stats = logger.frame_stats()?;
+        let rows = drawn_rows(&logger)?;
+        // Blank rows are empty in the initial frame buffer too, so they are not redrawn.
+        let non_blank = rows.iter().filter(|(kind, _)| *kind != RowKind::Blank).count();
+        assert_eq!(stats.changed_lines, non_blank);
+        assert_eq!(stats.bytes_written, out.len());
+        logger.toggle_frame_stats()?;
+        draw_frame(&logger, &mut Vec::new(), &mut resize, size)?;
+        let stats = logger.frame_stats()?;
+        assert!(stats.changed_lines > 0 && stats.changed_lines < non_blank);
+        let menu = drawn_rows(&logger)?.into_iter().find(|(kind, _)| *kind == RowKind::Menu);
+        assert!(menu.is_some_and(|(_, menu)| menu.contains(" rows, ")));
+        Ok(())
+    }
+
+    #[test]
+    fn deferred_first_frame_is_drawn_when_ready() -> Result {
+        let deferred = || Builder::default().defer_first_draw_until_ready(true)
+            .ready_timeout(Duration::from_secs(60)).build();
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let logger = deferred();
+        logger.log(&["build"][..], None, "compiling");
+        let mut out = Vec::new();
+        draw_frame(&logger, &mut out, &mut default(), size)?;
+        assert!(out.is_empty());
+        assert!(drawn_rows(&logger)?.is_empty());
+        logger.ready()?;
+        draw_frame(&logger, &mut out, &mut default(), size)?;
+        let clear = crossterm::terminal::Clear(crossterm::terminal::ClearType::All);
+        let mut clear_all = Vec::new();
+        crossterm::queue!(clear_all, clear)?;
+        assert!(out.windows(clear_all.len()).any(|t| t == clear_all));
+        assert!(!drawn_rows(&logger)?.is_empty());
+
+        let logger = deferred();
+        logger.modify(|l| {
+            let deadline = l.ready_deadline.unwrap_or(SystemTime::UNIX_EPOCH);
+            assert!(!l.check_ready(deadline - Duration::from_millis(1)));
+            assert!(!l.redraw);
+            assert!(l.check_ready(deadline));
+            assert!(l.redraw);
+            assert!(l.check_ready(deadline - Duration::from_millis(1)));
+        })
+    }
+
+    #[test]
+    fn forced_redraw_repaints_every_row() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], None, "compiling");
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let mut resize = default();
+        draw_frame(&logger, &mut Vec::new(), &mut resize, size)?;
+        draw_frame(&logger, &mut Vec::new(), &mut resize, size)?;
+        assert!(logger.frame_stats()?.changed_lines < size.rows);
+        logger.force_redraw()?;
+        let mut out = Vec::new();
+        draw_frame(&logger, &mut out, &mut resize, size)?;
+        assert_eq!(logger.frame_stats()?.changed_lines, size.rows);
+        assert_eq!(drawn_rows(&logger)?.len(), size.rows);
+        let clear = crossterm::terminal::Clear(crossterm::terminal::ClearType::All);
+        let mut clear_all = Vec::new();
+        crossterm::queue!(clear_all, clear)?;
+        assert!(out.windows(clear_all.len()).any(|t| t == clear_all));
+        draw_frame(&logger, &mut Vec::new(), &mut resize, size)?;
+        assert!(logger.frame_stats()?.changed_lines < size.rows);
+        Ok(())
+    }
+
+    #[test]
+    fn headers_set_after_the_first_lines_keep_the_lines() -> Result {
+        let logger = SharedLogger::default();
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let title = |name| -> Result<String> {
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            let group_id = logger.group_id(&[name])?;
+            let header = drawn_rows(&logger)?.into_iter()
+                .find(|(kind, _)| *kind == RowKind::Header(group_id));
+            Ok(header.map(|(_, header)| header).unwrap_or_default())
         };
+        let worker = std::thread::spawn({
+            let logger = logger.clone();
+            move || (0..100).for_each(|i| logger.log(&["build"][..], None, format!("{i}")))
+        });
+        logger.log(&["build"][..], None, "start");
+        assert!(title("build")?.ends_with(" …"));
+        logger.set_header(&["build"][..], "Build");
+        logger.set_header_once(&["build"][..], "Default");
+        assert!(worker.join().is_ok());
+        assert!(title("build")?.ends_with(" Build"));
+        let lines = contents(&logger, &["build"])?;
+        assert_eq!(lines.len(), 101);
+        let numbers = lines.iter().filter_map(|t| t.parse::<usize>().ok()).collect::<Vec<_>>();
+        assert_eq!(numbers, (0..100).collect::<Vec<_>>());
 
-        // === History ===
+        logger.log(&["test"][..], None, "start");
+        let test = logger.group_id(&["test"])?;
+        logger.modify(|l| l.groups[*test].created_at -= HEADER_GRACE)?;
+        assert!(title("test")?.ends_with(" test"));
+        logger.set_header_once(&["test"][..], "Test");
+        assert!(title("test")?.ends_with(" Test"));
+        Ok(())
+    }
 
-        {
-            let padding = 1;
-            let cols = size.cols.saturating_sub(2 * padding);
-            let all_count = logger.history.len();
-            let view_count = logger.groups.next_line.map(|t| *t).unwrap_or(all_count);
-            let rhs_count = all_count - view_count;
-            let max_shift = view_count.saturating_sub(cols/2);
-            let shift = rhs_count.min(cols/2).min(max_shift);
-            let start_ix = view_count.saturating_sub(cols) + shift;
-            let end_ix_succ = (start_ix + cols).min(logger.history.len());
-            let is_lhs_clipped = start_ix > 0;
-            let is_rhs_clipped = rhs_count > cols/2;
-            let visible_count = view_count.saturating_sub(start_ix);
-            let history = logger.history[start_ix..end_ix_succ].iter()
-                .map(|t| t.map0(|s| index_to_group_char_opt(*s)))
-                .collect::<Vec<_>>();
-            let (before, current) = visible_count.checked_sub(1).map(|current_ix| {
-                let before_start = if is_lhs_clipped { 1 } else { 0 };
-                let current = history.get(current_ix).copied().map(history_tile_active)
-                    .unwrap_or_default();
-                let before = history.get(before_start..current_ix).map(
-                    |t| t.iter().copied().map(history_tile_active).collect::<String>()
-                ).unwrap_or_default();
-                (before, current)
-            }).unwrap_or_default();
-            let after_end = if is_rhs_clipped { history.len() - 1 } else { history.len() };
-            let dots1 = if is_lhs_clipped { "…" } else { "" }.black().on_green();
-            let dots2 = if is_rhs_clipped { "…" } else { "" }.dark_green().on_green();
-            let after: String = history.get(visible_count .. after_end).map(
-                |t| t.iter().copied().map(history_tile_non_active).collect()
-            ).unwrap_or_default();
-            let pad_str = " ".repeat(padding).on_green();
-            let history_str = format!("{pad_str}{dots1}{before}{current}{after}{dots2}{pad_str}");
-            let rhs_spaces = " ".repeat(cols.saturating_sub(visible_count)).on_green();
-            let new_line = format!("{history_str}{rhs_spaces}");
-            writer.line(None, None, new_line)
+    #[test]
+    fn cleared_lines_are_not_shown_in_the_history_view() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], Status::error().finished(), "old 1");
+        logger.log(&["test"][..], Status::error().finished(), "test");
+        logger.log(&["build"][..], Status::error().finished(), "old 2");
+        let build = logger.group_id(&["build"])?;
+        logger.scroll(build, -1)?;
+        logger.clear_group(build)?;
+        logger.log(&["build"][..], Status::error().finished(), "new");
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let mut frames = Vec::new();
+        for _ in 0..5 {
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            let rows = drawn_rows(&logger)?.into_iter().filter_map(|(kind, row)| {
+                (kind.group() == Some(build)).then(|| row.trim().to_string())
+            });
+            frames.push(rows.filter(|row| !row.is_empty()).skip(1).collect::<Vec<_>>());
+            logger.shift_history(-1)?;
+            logger.scroll(build, 1)?;
+        }
+        assert!(frames.iter().flatten().all(|row| !row.contains("old")), "{frames:?}");
+        assert!(frames[0].iter().any(|row| row.contains("new")));
+        assert!(frames[1..].iter().all(|rows| rows.iter().all(|row| !row.contains("new"))));
+        Ok(())
+    }
+
+    #[test]
+    fn group_templates_apply_to_new_and_existing_groups() -> Result {
+        let logger = SharedLogger::default();
+        for path in [&["build", "lib"][..], &["build", "cli"], &["build", "lib", "core"]] {
+            logger.create_group(path)?;
+        }
+        let template = |footer: &str| GroupTemplate { footer: Some(footer.into()), ..default() };
+        let footers = || -> Result<Vec<String>> {
+            let paths = [&["build", "lib"][..], &["build", "cli"], &["build", "lib", "core"]];
+            paths.iter().map(|path| {
+                let group_id = logger.group_id(*path)?;
+                logger.modify(|l| l.groups[*group_id].footer.clone())
+            }).collect()
         };
+        logger.register_group_template(&["build", "lib"][..], template("lib"), false)?;
+        assert_eq!(footers()?, ["", "", ""]);
+        logger.register_group_template(&["build"][..], template("build"), true)?;
+        assert_eq!(footers()?, ["", "build", ""]);
+        logger.register_group_template(&["build", "lib"][..], template("lib 2"), true)?;
+        assert_eq!(footers()?, ["lib 2", "build", "lib 2"]);
+        let header = GroupTemplate { header: Some("Docs".into()), ..template("docs") };
+        logger.register_group_template(&["build", "docs"][..], header, false)?;
+        logger.log(&["build", "docs"][..], None, "line");
+        logger.log(&["build", "lib", "alloc"][..], None, "line");
+        logger.log(&["test"][..], None, "line");
+        logger.modify(|l| {
+            let groups = l.groups.iter().map(|g| (g.path.join("."), g.footer.as_str(),
+                g.header.as_str(), g.header_pending)).collect::<Vec<_>>();
+            assert_eq!(groups[3..], [
+                ("build.docs".into(), "docs", "Docs", false),
+                ("build.lib.alloc".into(), "lib 2", "build::lib::alloc", true),
+                ("test".into(), "", "test", true),
+            ]);
+        })
+    }
 
-        // === Menu ===
+    #[test]
+    fn line_ids_saturate() {
+        assert_eq!(LineId(3).saturating_add_signed(-5), LineId(0));
+        assert_eq!(LineId(3).saturating_add_signed(2), LineId(5));
+        assert_eq!(LineId(usize::MAX).saturating_add_signed(isize::MAX), LineId(usize::MAX));
+        assert_eq!(LineId(0).saturating_add_signed(isize::MIN), LineId(0));
+    }
 
-        let menu_no_selection: &[(&str, &str)] = &[
-            ("Help", "?"),
-            ("Quit", "q"),
-            ("Select", "1-9 a-z ↑↓"),
-            ("Inverse Selection", "0"),
-            ("Deselect", "Esc"),
-            ("History", "←→")
-        ];
-        let menu_selection: &[(&str, &str)] = &[("Help", "?"), ("Collapse", "Enter")];
-        let menu_button = if groups.iter().any(|g| g.selected) {
-            menu_selection
-        } else {
-            menu_no_selection
+    #[test]
+    fn extreme_shifts_and_sizes_do_not_panic() -> Result {
+        let logger = SharedLogger::default();
+        for shift in [isize::MIN, -1, 1, isize::MAX] {
+            logger.shift_history(shift)?;
+        }
+        for i in 0..30 {
+            logger.log(&["build"][..], None, format!("line {i}"));
+        }
+        let group_id = logger.group_id(&["build"][..])?;
+        let sizes = [(0, 0), (1, 1), (0, 24), (80, 0), (3, 5), (80, 24)];
+        for (shift, (cols, rows)) in [isize::MIN, -3, 0, 7, isize::MAX].into_iter().zip(sizes) {
+            logger.shift_history(shift)?;
+            logger.scroll(group_id, shift)?;
+            logger.scroll(group_id, -shift.saturating_add(1))?;
+            let size = terminal::Size { cols, rows };
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unterminated_styles_do_not_bleed_into_other_rows() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], None, "\x1b[31mkilled mid-line");
+        logger.log(&["build"][..], None, "plain");
+        let group_id = logger.group_id(&["build"][..])?;
+        logger.modify_group(group_id, |mut g| g.collapsed = Some(false))?;
+        let mut out = Vec::new();
+        let size = terminal::Size { cols: 40, rows: 12 };
+        draw_frame(&logger, &mut out, &mut default(), size)?;
+        let out = String::from_utf8(out)?;
+        let (reset, clear_row) = ("\x1b[0m", "\x1b[2K");
+        assert!(out.contains("\x1b[31mkilled mid-line"));
+        let rows = out.matches(clear_row).count();
+        assert!(rows > 2);
+        assert_eq!(out.matches(&format!("{reset}{clear_row}")).count(), rows);
+        Ok(())
+    }
+
+    #[test]
+    fn group_viewport_pages_the_groups() {
+        let viewport = |offset, count, len| GroupViewport { offset, count, len };
+        let single_rows = [1; 10];
+        assert_eq!(GroupViewport::new(&single_rows, 10, 3, None), viewport(0, 10, 10));
+        let first_page = GroupViewport::new(&single_rows, 5, 0, None);
+        assert_eq!(first_page, viewport(0, 4, 10));
+        assert_eq!((first_page.above(), first_page.below()), (0, 6));
+        assert!(first_page.is_paged());
+        assert_eq!(GroupViewport::new(&single_rows, 5, 3, None), viewport(3, 3, 10));
+        let last_page = GroupViewport::new(&single_rows, 5, 100, None);
+        assert_eq!(last_page, viewport(6, 4, 10));
+        assert_eq!((last_page.above(), last_page.below()), (6, 0));
+        assert_eq!(GroupViewport::new(&single_rows, 5, 0, Some(9)), viewport(6, 4, 10));
+        assert_eq!(GroupViewport::new(&single_rows, 5, 6, Some(1)), viewport(1, 3, 10));
+        assert_eq!(GroupViewport::new(&[10, 1], 5, 0, None), viewport(0, 1, 2));
+        assert_eq!(GroupViewport::new(&[], 0, 2, None), viewport(0, 0, 0));
+    }
+
+    #[test]
+    fn annotations_are_drawn_below_their_lines() -> Result {
+        let logger = SharedLogger::default();
+        for i in 0..4 {
+            logger.log(&["test"][..], None, format!("line {i}"));
+        }
+        let group_id = logger.group_id(&["test"][..])?;
+        let note = |text| group::Annotation::new(text, Level::Warn);
+        logger.annotate(group_id, LineId(1), note("slow"))?;
+        logger.annotate(group_id, LineId(3), note("flaky"))?;
+        assert!(logger.annotate(group_id, LineId(9), note("missing")).is_err());
+        logger.modify(|logger| {
+            let rows = ["line 0", "line 1", "slow", "line 2", "line 3", "flaky"];
+            assert_eq!(body_text(logger, group_id, 6, 40), rows);
+            logger.set_selection([group_id]);
+            let mut scrolls = Vec::new();
+            for _ in 0..3 {
+                logger.scroll_to_next_annotation();
+                scrolls.push(logger.groups[*group_id].scroll);
+            }
+            assert_eq!(scrolls, [Some(LineId(1)), Some(LineId(3)), Some(LineId(1))]);
+        })
+    }
+
+    #[test]
+    fn compact_groups_are_drawn_without_footers() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["full"][..], Status::ok(), "line");
+        logger.set_compact(true)?;
+        logger.log(&["compact"][..], Status::ok(), "line");
+        logger.modify_all_groups(|mut g| g.collapsed = Some(false))?;
+        let full = logger.group_id(&["full"][..])?;
+        let compact = logger.group_id(&["compact"][..])?;
+        assert!(logger.modify(|l| l.groups[*full].compact && l.groups[*compact].compact)?);
+        logger.set_group_compact(full, false)?;
+        let size = terminal::Size { cols: 80, rows: 24 };
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        let footers = logger.modify(|l| {
+            l.frame_buffer.row_kind.values().filter_map(|kind| match kind {
+                RowKind::Footer(group_id) => Some(*group_id),
+                _ => None,
+            }).collect::<Vec<_>>()
+        })?;
+        assert_eq!(footers, [full]);
+        Ok(())
+    }
+
+    #[test]
+    fn silent_groups_stall_until_they_output_a_line() -> Result {
+        let logger = SharedLogger::default();
+        let called: Arc<Mutex<Vec<group::Id>>> = default();
+        let called2 = called.clone();
+        logger.log(&["quiet"][..], None, "waiting");
+        logger.log(&["loud"][..], None, "waiting");
+        logger.log(&["done"][..], Status::ok().finished(), "finished");
+        let quiet = logger.group_id(&["quiet"][..])?;
+        let loud = logger.group_id(&["loud"][..])?;
+        let timeout = Duration::from_secs(10);
+        logger.set_stall_timeout(timeout, StallAction::PushErrorLine)?;
+        let callback = StallAction::callback(move |id| {
+            _ = called2.lock().map(|mut v| v.push(id));
+        });
+        logger.set_group_stall_timeout(loud, timeout, callback)?;
+        let stalled = |l: &mut Logger| l.groups.iter().filter(|g| g.stalled).map(|g| g.id)
+            .collect::<Vec<_>>();
+        let now = SystemTime::now();
+        logger.modify(|l| l.check_stalls(now))?;
+        assert!(logger.modify(stalled)?.is_empty());
+        for _ in 0..2 {
+            logger.modify(|l| l.check_stalls(now + 2 * timeout))?;
+        }
+        assert_eq!(logger.modify(stalled)?, [quiet, loud]);
+        assert_eq!(*called.lock().map_err(|e| anyhow!("{e}"))?, [loud]);
+        let quiet_lines = contents(&logger, &["quiet"])?;
+        assert_eq!(quiet_lines.len(), 2);
+        assert!(quiet_lines[1].starts_with("No output for"));
+        logger.log(&["loud"][..], None, "progress");
+        assert_eq!(logger.modify(stalled)?, [quiet]);
+        Ok(())
+    }
+
+    #[test]
+    fn normalized_selectors_find_one_group() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["Build"][..], None, "first");
+        logger.log(&["build "][..], None, "second");
+        logger.set_selector_normalizer(Normalize::TrimLowercase)?;
+        let warnings = logger.modify(|l| {
+            l.debug_lines.iter().map(|line| line.content.clone()).collect::<Vec<_>>()
+        })?;
+        assert_eq!(warnings, ["Groups 'Build' and 'build ' have the same normalized selector"]);
+        logger.log(&[" BUILD"][..], None, "third");
+        assert_eq!(contents(&logger, &["build"])?, ["first", "third"]);
+        assert_eq!(logger.modify(|l| l.groups[0].header.clone())?, "Build");
+        logger.set_selector_normalizer(Normalize::custom(|s| s.replace('-', "_")))?;
+        logger.log(&["run-tests"][..], None, "line");
+        assert!(logger.group_exists(&["run_tests"][..])?);
+        assert!(!logger.group_exists(&["build"][..])?);
+        Ok(())
+    }
+
+    #[test]
+    fn dependencies_block_until_the_parents_succeed() -> Result {
+        let logger = SharedLogger::default();
+        for name in ["a", "b", "c"] {
+            logger.log(&[name][..], None, "running");
+        }
+        let a = logger.group_id(&["a"][..])?;
+        let b = logger.group_id(&["b"][..])?;
+        let c = logger.group_id(&["c"][..])?;
+        logger.set_group_dependency(c, a)?;
+        logger.set_group_dependency(c, b)?;
+        logger.set_group_dependency(b, a)?;
+        assert!(logger.set_group_dependency(a, c).is_err());
+        assert!(logger.set_group_dependency(a, a).is_err());
+        let blocked = |logger: &SharedLogger| logger.modify(|l| {
+            l.check_dependencies();
+            l.groups[*c].blocked.clone()
+        });
+        let waiting = |headers: &[&str]| {
+            Some(group::Blocked::Waiting(headers.iter().map(|t| t.to_string()).collect()))
         };
+        assert_eq!(blocked(&logger)?, waiting(&["a", "b"]));
+        logger.log(&["a"][..], Status::ok().finished(), "done");
+        assert_eq!(blocked(&logger)?, waiting(&["b"]));
+        logger.log(&["b"][..], Status::error().finished(), "failed");
+        assert_eq!(blocked(&logger)?, Some(group::Blocked::Cancelled("b".into())));
+        logger.log(&["c"][..], Status::cancelled().finished(), "skipped");
+        assert_eq!(blocked(&logger)?, None);
+        logger.modify(|l| {
+            l.reset();
+            assert!(l.dependencies.is_empty());
+        })
+    }
+
+    #[test]
+    #[expect(clippy::panic)]
+    fn panics_of_other_threads_are_reported_in_the_panic_group() -> Result {
+        let logger = SharedLogger::default();
+        let previous_hook = std::panic::take_hook();
+        let report = logger.set_panic_hook();
+        let joined = std::thread::spawn(|| std::panic::panic_any("worker failed")).join();
+        std::panic::set_hook(previous_hook);
+        assert!(joined.is_err());
+        let report = report.lock().map_err(|e| anyhow!("{e}"))?.clone().unwrap_or_default();
+        assert!(report.contains("Message: worker failed"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut lines = Vec::new();
+        while lines.len() < report.lines().count() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            if let Ok(group_id) = logger.group_id(PANIC_GROUP) {
+                lines = logger.read_lines(group_id, 0..usize::MAX)?;
+            }
+        }
+        let contents = lines.iter().map(|t| t.2.as_str()).collect::<Vec<_>>();
+        assert_eq!(contents, report.lines().collect::<Vec<_>>());
+        assert!(lines.iter().all(|(_, status, _)| status.is_error()));
+        Ok(())
+    }
+
+    #[test]
+    fn errors_of_a_poisoned_logger_reach_the_error_hook() -> Result {
+        let logger = SharedLogger::default();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        // Unwinding without a panic skips the panic hook, but still poisons the held locks.
+        let unwind = || std::panic::resume_unwind(Box::new(()));
+        let unwound = std::thread::scope(|scope| scope.spawn(|| {
+            let _guard = logger.error_hook.write();
+            unwind()
+        }).join().is_err());
+        assert!(unwound && logger.error_hook.is_poisoned());
+        let hook_errors = errors.clone();
+        logger.set_error_hook(move |error| {
+            if let Ok(mut errors) = hook_errors.lock() {
+                errors.push(error.to_string());
+            }
+        });
+        let unwound = std::thread::scope(|scope| scope.spawn(|| {
+            let _guard = logger.lock();
+            unwind()
+        }).join().is_err());
+        assert!(unwound && logger.is_poisoned());
+        logger.log(&["build"][..], None, "line");
+        logger.set_header(&["build"][..], "Build");
+        let errors = errors.lock().map_err(|e| anyhow!("{e}"))?.clone();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|error| error.starts_with("Failed to lock logger")));
+        assert_eq!(logger.dropped_count(), 2);
+        Ok(())
+    }
 
-        let new_line = menu_button.iter().map(|(label, shortcut)| {
-            let left = format!(" {label}");
-            let right = format!(" {shortcut} ").green().bold();
-            format!("{left}{right}")
-        }).collect::<Vec<_>>().join("");
-        writer.line(None, None, new_line);
+    fn press(logger: &SharedLogger, char: char) -> Result<bool> {
+        use crossterm::event::*;
+        on_event(logger, Event::Key(KeyEvent::from(KeyCode::Char(char))))
+    }
 
-        // === Debug Panel ===
+    fn selected(logger: &SharedLogger) -> Result<Vec<usize>> {
+        logger.modify(|l| l.selected.iter().map(|id| id.0).collect())
+    }
 
-        let debug_lines_start = logger.debug_lines.len().saturating_sub(debug_rows);
-        let debug_lines_count = logger.debug_lines.len().saturating_sub(debug_lines_start);
-        for line in &logger.debug_lines[debug_lines_start..] {
-            let fill = " ".repeat(size.cols.saturating_sub(line.len()));
-            writer.line(None, None, format!("{line}{fill}").black().on_blue().to_string());
+    #[test]
+    fn g_enters_the_goto_mode_only_beyond_the_single_character_labels() -> Result {
+        let logger = SharedLogger::default();
+        let size = terminal::Size { cols: 80, rows: 100 };
+        for i in 0..16 {
+            logger.log(&[format!("group {i}")][..], None, "line");
         }
-        for _ in debug_lines_count .. debug_rows {
-            writer.line(None, None, " ".repeat(size.cols).on_blue().to_string());
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        press(&logger, 'g')?;
+        assert_eq!(selected(&logger)?, [15]);
+        press(&logger, 'g')?;
+        for i in 16..40 {
+            logger.log(&[format!("group {i}")][..], None, "line");
         }
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        press(&logger, 'g')?;
+        assert_eq!(logger.modify(|l| l.goto.clone())?, Some(String::new()));
+        press(&logger, '1')?;
+        press(&logger, '0')?;
+        assert_eq!(selected(&logger)?, [35]);
+        assert_eq!(index_to_group_label(35).as_deref(), Some("10"));
+        assert_eq!(index_to_group_char_opt(35), '…');
+        Ok(())
+    }
 
-        // === Draw ===
+    #[test]
+    fn pasted_text_is_typed_only_into_the_palette_and_goto_mode() -> Result {
+        use crossterm::event::Event;
+        let logger = SharedLogger::default();
+        for i in 0..40 {
+            logger.log(&[format!("group {i}")][..], None, "line");
+        }
+        let size = terminal::Size { cols: 80, rows: 100 };
+        draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+        let paste = |text: &str| on_event(&logger, Event::Paste(text.into()));
+        assert!(paste("1q/\n2")?);
+        assert!(selected(&logger)?.is_empty());
+        logger.modify(|l| assert!(l.palette.is_none() && l.goto.is_none()))?;
+        logger.open_palette()?;
+        paste("grou\np 3")?;
+        let query = logger.modify(|l| l.palette.as_ref().map(|palette| palette.query.clone()))?;
+        assert_eq!(query.as_deref(), Some("grou"));
+        logger.modify(|l| l.palette = None)?;
+        press(&logger, 'g')?;
+        paste("1-0")?;
+        assert_eq!(selected(&logger)?, [35]);
+        Ok(())
+    }
 
-        for (i, line) in writer.lines.iter_mut().enumerate() {
-            if line.changed {
-                crossterm::queue!(
-                        stdout,
-                        crossterm::cursor::MoveTo(0, i as u16),
-                        crossterm::terminal::Clear(crossterm::terminal::ClearType::CurrentLine),
-                        crossterm::style::Print(&line.content)
-                    )?;
-                line.changed = false;
+    #[test]
+    fn unfocused_terminals_dim_the_headers_and_slow_down_frames() -> Result {
+        use crossterm::event::Event;
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], Status::ok().progress(0.5), "compiling");
+        logger.set_header(&["build"][..], "Build");
+        let build = logger.group_id(&["build"])?;
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let header = || -> Result<String> {
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            logger.modify(|l| l.frame_buffer.row_kind.iter()
+                .find(|(_, kind)| **kind == RowKind::Header(build))
+                .map(|(row, _)| l.frame_buffer.lines[row.0].content.clone())
+                .unwrap_or_default())
+        };
+        let green = "Build".green().bold().to_string();
+        let dark_green = "Build".dark_green().to_string();
+        let focused = header()?;
+        assert!(focused.contains(&green));
+        assert!(logger.modify(|l| l.frame_time())? < UNFOCUSED_FRAME_TIME);
+        on_event(&logger, Event::FocusLost)?;
+        assert!(header()?.contains(&dark_green));
+        assert!(logger.modify(|l| l.frame_time())? >= UNFOCUSED_FRAME_TIME);
+        on_event(&logger, Event::FocusGained)?;
+        assert!(header()?.contains(&green));
+        Ok(())
+    }
+
+    #[test]
+    fn mouse_wheel_over_chrome_rows_pages_the_groups_and_steps_the_history() -> Result {
+        use crossterm::event::*;
+        let logger = SharedLogger::default();
+        for i in 0..20 {
+            for line in ["compiling", "failed"] {
+                logger.log(&[format!("group {i}")][..], Status::error(), line);
             }
         }
-        std::io::Write::flush(stdout)?;
-        Result::<(), Error>::Ok(())
-    })??;
+        for i in 0..10 {
+            logger.debug(format!("debug {i}"));
+        }
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let row_of = |kind: RowKind| -> Result<u16> {
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            let mut rows = drawn_rows(&logger)?.into_iter().map(|(kind, _)| kind);
+            let row = rows.position(|t| t == kind).ok_or_else(|| anyhow!("No {kind:?} row"))?;
+            Ok(row as u16)
+        };
+        let scroll = |kind, down: bool, modifiers| -> Result {
+            let row = row_of(kind)?;
+            let kind = if down { MouseEventKind::ScrollDown } else { MouseEventKind::ScrollUp };
+            on_event(&logger, Event::Mouse(MouseEvent { kind, column: 0, row, modifiers }))?;
+            Ok(())
+        };
+        let viewport = || logger.modify(|l| l.group_viewport);
+        let next_line = || logger.modify(|l| l.groups.next_line);
+        let end = LineId(logger.modify(|l| l.history.end())?);
+        let first = logger.group_id(&["group 0"])?;
 
-    use crossterm::event;
-    if event::poll(std::time::Duration::from_millis(16))? {
-        match event::read()? {
-            event::Event::Key(event) => {
-                if event.code == event::KeyCode::Char('q') ||
-                    event.code == event::KeyCode::Char('c')
-                        && event.modifiers.contains(event::KeyModifiers::CONTROL) {
-                    return Ok(false);
-                }
+        scroll(RowKind::Menu, true, KeyModifiers::NONE)?;
+        let paged = viewport()?;
+        assert!(paged.is_paged());
+        assert_eq!(paged.offset, paged.count);
+        scroll(RowKind::MoreGroups, false, KeyModifiers::NONE)?;
+        assert_eq!(viewport()?.offset, 0);
 
-                match event.code {
-                    event::KeyCode::Char(char) => {
-                        match char {
-                            '0' => modify_all_groups(|mut g| g.selected = !g.selected),
-                            _ => {
-                                if let Some(index) = group_char_to_index(char).map(group::Id) {
-                                    modify_group(index, |mut g| g.selected = !g.selected).ok();
-                                }
-                                Ok(())
-                            }
-                        }
-                    }
-                    event::KeyCode::Enter => modify_all_groups(|mut g| if g.selected {
-                        g.collapsed = Some(!g.as_ref().is_collapsed())
-                    }),
-                    event::KeyCode::Esc => modify_all_groups(|mut g| g.selected = false),
-                    event::KeyCode::Down => shift_selection(1),
-                    event::KeyCode::Up => shift_selection(-1),
-                    event::KeyCode::Left => {
-                        let mult = if event.modifiers.contains(event::KeyModifiers::SHIFT) {
-                            10
-                        } else {
-                            1
-                        };
-                        shift_history(-mult)
-                    },
-                    event::KeyCode::Right => {
-                        let mult = if event.modifiers.contains(event::KeyModifiers::SHIFT) {
-                            10
-                        } else {
-                            1
-                        };
-                        shift_history(mult)
-                    },
-                    _ => { Ok (()) }
-                }?
-            }
-            event::Event::Mouse(event) => {
-                let row = framebuffer::LineIndex(event.row as usize);
-                let column = event.column as usize;
-                match event.kind {
-                    event::MouseEventKind::ScrollUp => {
-                        if let Some(group_id) = line_to_group_id(row)? {
-                            scroll(group_id, -1)?;
-                        }
-                    }
-                    event::MouseEventKind::ScrollDown => {
-                        if let Some(group_id) = line_to_group_id(row)? {
-                            scroll(group_id, 1)?;
-                        }
-                    }
-                    event::MouseEventKind::Down(_) => {
-                        if let Some(group_id) = line_to_group_id(row)? {
-                            let first_line = group_to_lines(group_id)?.unwrap_or_default().0;
-                            if row == first_line && column < 4 {
-                                modify_group(group_id, |mut g|
-                                    g.collapsed = Some(!g.as_ref().is_collapsed())
-                                )?;
-                            } else {
-                                modify_all_groups(|mut g| g.selected = false)?;
-                                modify_group(group_id, |mut g| g.selected = true)?;
-                            }
-                        }
-                    }
-                    _ => {}
+        scroll(RowKind::History, false, KeyModifiers::NONE)?;
+        assert_eq!(next_line()?, Some(end.saturating_add_signed(-1)));
+        scroll(RowKind::ScrollBar, true, KeyModifiers::NONE)?;
+        assert_eq!(next_line()?, None);
+        scroll(RowKind::Header(first), false, KeyModifiers::SHIFT)?;
+        assert_eq!(next_line()?, Some(end.saturating_add_signed(-1)));
+        assert_eq!(logger.modify(|l| l.groups[*first].scroll)?, None);
+        scroll(RowKind::History, true, KeyModifiers::NONE)?;
+
+        scroll(RowKind::Debug, false, KeyModifiers::NONE)?;
+        assert_eq!(logger.modify(|l| l.debug_scroll)?, 1);
+        assert_eq!((viewport()?.offset, next_line()?), (0, None));
+        Ok(())
+    }
+
+    #[test]
+    fn palette_keys_edit_the_query_and_select_the_match() -> Result {
+        use crossterm::event::*;
+        let logger = SharedLogger::default();
+        for name in ["docs", "test-runner", "trace", "build"] {
+            logger.log(&[name][..], None, "line");
+        }
+        let key = |code, modifiers| on_event(&logger, Event::Key(KeyEvent::new(code, modifiers)));
+        let open = || key(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let palette = || logger.modify(|l| {
+            l.palette.as_ref().map(|palette| (palette.query.clone(), palette.selected))
+        });
+        open()?;
+        assert!(press(&logger, 'q')?);
+        assert_eq!(palette()?, Some(("q".into(), 0)));
+        key(KeyCode::Backspace, KeyModifiers::NONE)?;
+        press(&logger, 't')?;
+        press(&logger, 'r')?;
+        for (code, selected) in [(KeyCode::Down, 1), (KeyCode::Down, 0), (KeyCode::Up, 1)] {
+            key(code, KeyModifiers::NONE)?;
+            assert_eq!(palette()?, Some(("tr".into(), selected)));
+        }
+        let docs = logger.group_id(&["docs"])?;
+        let test_runner = logger.group_id(&["test-runner"])?;
+        logger.zoom_group(Some(docs))?;
+        key(KeyCode::Enter, KeyModifiers::NONE)?;
+        assert_eq!(palette()?, None);
+        assert_eq!(selected(&logger)?, [test_runner.0]);
+        logger.modify(|l| {
+            assert_eq!(l.groups.zoomed, None);
+            assert_eq!(l.groups[*test_runner].collapsed, Some(false));
+        })?;
+        for close in [KeyCode::Esc, KeyCode::Char('x')] {
+            open()?;
+            press(&logger, 'd')?;
+            key(close, KeyModifiers::CONTROL)?;
+            assert_eq!(palette()?, None);
+            assert_eq!(selected(&logger)?, [test_runner.0]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn status_rules_mark_the_matching_lines() -> Result {
+        use group::StatusTag::*;
+        let logger = SharedLogger::default();
+        let build = logger.create_group(&["build"][..])?;
+        let global = logger.add_global_status_rule("^error", Status::error())?;
+        logger.add_global_status_rule("E0425", Status::cancelled())?;
+        logger.add_status_rule(build, "^error: could not compile", Status::error().finished())?;
+        let transcript = [
+            "   Compiling lmux v0.1.0 (/src/lmux)",
+            "error[E0425]: cannot find value `x` in this scope",
+            " --> src/lib.rs:3:5",
+            "error: could not compile `lmux` (lib) due to 1 previous error",
+        ];
+        for line in transcript {
+            logger.log(&["build"][..], None, line);
+        }
+        let statuses = |name| -> Result<Vec<_>> {
+            let group_id = logger.group_id(&[name][..])?;
+            let lines = logger.read_lines(group_id, 0..usize::MAX)?;
+            Ok(lines.into_iter().map(|(_, status, _)| (status.tag, status.finished)).collect())
+        };
+        let expected = [(Success, false), (Error, false), (Error, false), (Error, true)];
+        assert_eq!(statuses("build")?, expected);
+        logger.remove_status_rule(global)?;
+        logger.log(&["test"][..], None, "error[E0425]: cannot find value `y` in this scope");
+        logger.log(&["test"][..], None, "error: could not compile `lmux` (lib test)");
+        assert_eq!(statuses("test")?, [(Cancelled, false), (Cancelled, false)]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn restored_snapshots_draw_the_same_frame() -> Result {
+        let logger = SharedLogger::default();
+        logger.log(&["build"][..], None, "compiling");
+        logger.log(&["build"][..], Status::ok().finished(), "done");
+        logger.log(&["test"][..], Status::error(), "assertion failed");
+        logger.log(&["test"][..], Status::error().finished(), "1 failed");
+        logger.log(&["docs"][..], Status::ok().finished(), "generated");
+        let test = logger.group_id(&["test"][..])?;
+        logger.modify(|l| {
+            l.groups[*test].collapsed = Some(false);
+            l.toggle_selection(test);
+            // Restored headers are never pending, and snapshots store the times in milliseconds,
+            // which would change the drawn averages.
+            for (i, group) in l.groups.iter_mut().enumerate() {
+                group.header_pending = false;
+                for (j, line) in group.lines.iter_mut().enumerate() {
+                    line.time = SystemTime::UNIX_EPOCH + Duration::from_secs((10 * i + j) as u64);
                 }
             }
-            _ => {}
+        })?;
+        let size = terminal::Size { cols: 80, rows: 24 };
+        let frame = |logger: &SharedLogger| -> Result<Vec<(RowKind, String)>> {
+            draw_frame(logger, &mut Vec::new(), &mut default(), size)?;
+            drawn_rows(logger)
+        };
+        let drawn = frame(&logger)?;
+        let snapshot = logger.modify(|l| l.snapshot())?;
+        let json = serde_json::to_string(&snapshot)?;
+        let restored = SharedLogger::default();
+        restored.modify(|l| l.restore(serde_json::from_str(&json)?))??;
+        assert_eq!(frame(&restored)?, drawn);
+        assert!(restored.modify(|l| l.restore(snapshot))?.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn group_actions_cannot_use_reserved_keys() -> Result {
+        let logger = SharedLogger::default();
+        for key in ['q', 'R', '1', '9', 'a', 'z'] {
+            assert!(logger.register_group_action("*", key, "act", |_| {}).is_err(), "{key}");
         }
+        logger.register_group_action("*", 'X', "act", |_| {})?;
+        logger.register_group_action("*", '!', "act", |_| {})
     }
-    Ok(true)
-}
 
-// We start naming from 1, as `0` has a special meaning.
-fn group_char_to_index(c: char) -> Option<usize> {
-    match c {
-        '1'..='9' => Some(c as usize - '0' as usize),
-        'a'..='z' => Some(c as usize - 'a' as usize + 10),
-        _ => None,
-    }.map(|i| i - 1)
-}
+    #[test]
+    fn group_actions_run_for_the_single_selected_group() -> Result {
+        let logger = SharedLogger::default();
+        for path in [&["build", "lib"][..], &["test"][..]] {
+            logger.log(path, None, "line");
+        }
+        let build = logger.group_id(&["build", "lib"])?;
+        let test = logger.group_id(&["test"])?;
+        for (pattern, label) in [(&["build", "*"][..], "Rebuild"), (&["**"][..], "Rerun")] {
+            let logger_ref = logger.clone();
+            logger.register_group_action(pattern, 'X', label, move |group_id| {
+                logger_ref.log(&["actions"][..], None, format!("{label} {}", group_id.0));
+            })?;
+        }
+        let menu = |selection: &[group::Id]| -> Result<String> {
+            logger.modify(|l| l.set_selection(selection.iter().copied()))?;
+            let size = terminal::Size { cols: 400, rows: 24 };
+            draw_frame(&logger, &mut Vec::new(), &mut default(), size)?;
+            let rows = drawn_rows(&logger)?.into_iter();
+            Ok(rows.filter(|(kind, _)| *kind == RowKind::Menu).map(|(_, t)| t).collect())
+        };
+        assert!(!menu(&[])?.contains("Rebuild"));
+        press(&logger, 'X')?;
+        assert!(!logger.group_exists(&["actions"][..])?);
+        assert!(menu(&[build])?.contains("Rebuild X"));
+        press(&logger, 'X')?;
+        let menu_of_test = menu(&[test])?;
+        assert!(menu_of_test.contains("Rerun X") && !menu_of_test.contains("Rebuild"));
+        press(&logger, 'X')?;
+        assert!(!menu(&[build, test])?.contains("Rebuild"));
+        press(&logger, 'X')?;
+        let expected = [format!("Rebuild {}", build.0), format!("Rerun {}", test.0)];
+        assert_eq!(contents(&logger, &["actions"])?, expected);
+        Ok(())
+    }
 
-// We start naming from 1, as `0` has a special meaning.
-fn index_to_group_char(d: usize) -> Option<char> {
-    match d {
-        0..=8 => Some((d as u8 + b'1') as char),
-        9..=34 => Some((d as u8 - 9 + b'a') as char),
-        _ => None
+    /// Text of the body rows of the group, see [`body_rows`].
+    fn body_text(logger: &mut Logger, group_id: group::Id, space: usize, width: usize)
+    -> Vec<String> {
+        let group = LineRange { data: &logger.groups[*group_id], next_line: None };
+        body_rows(&group, space, width, &mut logger.style).into_iter().map(|(_, row)| match row {
+            BodyRow::Log(content) => content.into_owned(),
+            BodyRow::Separator(run) => format!("--- {run} ---"),
+            BodyRow::Annotation(annotation) => annotation.text.clone(),
+            BodyRow::Ellipsis => "...".into(),
+        }).collect()
     }
-}
 
-fn index_to_group_char_opt(d: usize) -> char {
-    index_to_group_char(d).unwrap_or('?')
+    #[test]
+    fn wrapped_lines_take_multiple_rows() -> Result {
+        let logger = SharedLogger::default();
+        let group_id = logger.create_group(&["docs"][..])?;
+        logger.set_group_wrap(group_id, true)?;
+        logger.log(&["docs"][..], None, "Error:");
+        logger.log(&["docs"][..], None, "The quick brown fox jumps over the lazy dog");
+        logger.modify(|logger| {
+            let tail = ["brown fox", "jumps over", "the lazy", "dog"];
+            assert_eq!(body_text(logger, group_id, 4, 10), tail);
+            let all = ["Error:", "The quick", "brown fox", "jumps over", "the lazy", "dog", ""];
+            assert_eq!(body_text(logger, group_id, 7, 10), all);
+            logger.groups[*group_id].scroll = Some(LineId(0));
+            let head = ["Error:", "The quick", "brown fox", "..."];
+            assert_eq!(body_text(logger, group_id, 4, 10), head);
+        })
+    }
 }
@@ -0,0 +1,66 @@
+use crate::prelude::*;
+
+use std::path::PathBuf;
+
+// ================
+// === Location ===
+// ================
+
+/// Position in a source file referenced by a log line, e.g. `src/foo.rs:10:5`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: u32,
+}
+
+/// Finds the first `path:line` reference in the text. The path has to contain a `.` or a path
+/// separator, so that e.g. times of day are not mistaken for locations.
+pub fn find(text: &str) -> Option<Location> {
+    let is_punctuation =
+        |c: char| matches!(c, '(' | ')' | '[' | ']' | '<' | '>' | ',' | '\'' | '"');
+    text.split_whitespace().find_map(|word| {
+        let mut parts = word.trim_matches(is_punctuation).split(':');
+        let path = parts.next()?;
+        let line = parts.next()?.parse().ok()?;
+        let is_path = path.contains(['.', '/', '\\']);
+        is_path.then(|| Location { path: path.into(), line })
+    })
+}
+
+/// Finds the first location matched by the regex, which has to capture the `path` and `line`
+/// groups.
+#[cfg(feature = "regex")]
+pub fn find_with(regex: &regex::Regex, text: &str) -> Option<Location> {
+    regex.captures_iter(text).find_map(|captures| {
+        let path = captures.name("path")?.as_str().into();
+        let line = captures.name("line")?.as_str().parse().ok()?;
+        Some(Location { path, line })
+    })
+}
+
+// ==============
+// === Opener ===
+// ==============
+
+/// Callback opening a location of a log line, e.g. in an editor, see
+/// [`crate::SharedLogger::set_opener`].
+#[derive(Clone)]
+pub struct Opener {
+    f: Arc<dyn Fn(PathBuf, u32) + Send + Sync>,
+}
+
+impl Debug for Opener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Opener").finish()
+    }
+}
+
+impl Opener {
+    pub fn new(f: impl Fn(PathBuf, u32) + Send + Sync + 'static) -> Self {
+        Self { f: Arc::new(f) }
+    }
+
+    pub fn call(&self, location: Location) {
+        (self.f)(location.path, location.line)
+    }
+}
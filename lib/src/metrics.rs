@@ -0,0 +1,180 @@
+use crate::prelude::*;
+
+use crate::Logger;
+use crate::SharedLogger;
+use crate::group;
+use crate::logger;
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Time a client has to send its request before the connection is closed.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ==============
+// === Render ===
+// ==============
+
+/// Group path as a label value, with the quotes and backslashes escaped.
+fn group_label(group: &group::Group) -> String {
+    group.path.join(".").replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Statistics of all groups in the Prometheus text format. Counters include the lines dropped or
+/// cleared since, and the duration is measured over the current run, as in the group footer.
+pub fn render(logger: &Logger) -> String {
+    let now = SystemTime::now();
+    let mut lines_total = String::new();
+    let mut errors_total = String::new();
+    let mut finished = String::new();
+    let mut duration = String::new();
+    for group in logger.groups.iter() {
+        let label = group_label(group);
+        let group::WindowStats { lines: line_count, errors: error_count } =
+            group.line_counter.total();
+        let last_line = group.lines.last();
+        let is_finished = last_line.is_some_and(|line| line.log.status.is_finished());
+        let run_start = group.lines.iter().rposition(|line| line.is_run_start()).unwrap_or_default();
+        let run_duration = group.lines.get(run_start).zip(last_line).map(|(start, end)| {
+            let end = if is_finished { end.time } else { now };
+            end.duration_since(start.time).unwrap_or_default().as_secs_f64()
+        }).unwrap_or_default();
+        writeln!(lines_total, "lmux_group_lines_total{{group=\"{label}\"}} {line_count}").ok();
+        writeln!(errors_total, "lmux_group_errors_total{{group=\"{label}\"}} {error_count}").ok();
+        writeln!(finished, "lmux_group_finished{{group=\"{label}\"}} {}", u8::from(is_finished))
+            .ok();
+        writeln!(duration, "lmux_group_duration_seconds{{group=\"{label}\"}} {run_duration:.3}")
+            .ok();
+    }
+    let families = [
+        ("lmux_group_lines_total", "counter", "Number of logged lines.", lines_total),
+        ("lmux_group_errors_total", "counter", "Number of logged error lines.", errors_total),
+        ("lmux_group_finished", "gauge", "Whether the group finished.", finished),
+        ("lmux_group_duration_seconds", "gauge", "Duration of the current run.", duration),
+    ];
+    let mut out = String::new();
+    for (name, kind, help, samples) in families {
+        writeln!(out, "# HELP {name} {help}").ok();
+        writeln!(out, "# TYPE {name} {kind}").ok();
+        out.push_str(&samples);
+    }
+    out
+}
+
+// =============
+// === Serve ===
+// =============
+
+impl SharedLogger {
+    /// Serves the statistics of the groups over HTTP on the address, see [`render`]. Every request
+    /// is answered with the current statistics, regardless of its path.
+    pub fn serve_metrics(&self, addr: impl ToSocketAddrs) -> Result<thread::JoinHandle<Result>> {
+        let listener = TcpListener::bind(addr)?;
+        let logger = self.clone();
+        let thread = thread::Builder::new().name("lmux-metrics".into()).spawn(move || {
+            // Failed connections are skipped, so that they do not stop the server.
+            for stream in listener.incoming().flatten() {
+                logger.respond_metrics(stream).ok();
+            }
+            Ok(())
+        })?;
+        Ok(thread)
+    }
+
+    fn respond_metrics(&self, mut stream: TcpStream) -> Result {
+        // Requests are answered one by one, so an idle client must not block the later ones.
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        // The request is read up to its end, but its content is not used.
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && line != "\r\n" {
+            line.clear();
+        }
+        let body = self.modify(|l| render(l))?;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )?;
+        Ok(())
+    }
+}
+
+pub fn serve(addr: impl ToSocketAddrs) -> Result<thread::JoinHandle<Result>> {
+    logger().serve_metrics(addr)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Status;
+    use std::io::Read;
+
+    /// Value of the sample of the group in the rendered statistics.
+    fn sample(metrics: &str, name: &str, group: &str) -> Option<u64> {
+        let prefix = format!("{name}{{group=\"{group}\"}} ");
+        metrics.lines().find_map(|line| line.strip_prefix(&prefix)?.parse().ok())
+    }
+
+    fn scrape(addr: std::net::SocketAddr) -> Result<String> {
+        let mut stream = TcpStream::connect(addr)?;
+        write!(stream, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    #[test]
+    fn counters_include_dropped_and_cleared_lines() -> Result {
+        let logger = SharedLogger::default();
+        let group_id = logger.create_group(&["build"][..])?;
+        logger.modify(|l| l.groups[*group_id].max_lines = Some(4))?;
+        for i in 0..10 {
+            logger.log(&["build"][..], None, format!("line {i}"));
+        }
+        logger.log(&["build"][..], Status::error(), "failed");
+        logger.clear_group(group_id)?;
+        let metrics = logger.modify(|l| render(l))?;
+        assert_eq!(sample(&metrics, "lmux_group_lines_total", "build"), Some(11));
+        assert_eq!(sample(&metrics, "lmux_group_errors_total", "build"), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn endpoint_serves_the_counters_while_lines_are_logged() -> Result {
+        let logger = SharedLogger::default();
+        let addr = TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let _server = logger.serve_metrics(addr)?;
+        let writer = logger.clone();
+        let lines = thread::spawn(move || {
+            for i in 0..500 {
+                writer.log(&["build"][..], None, format!("line {i}"));
+                thread::sleep(Duration::from_micros(100));
+            }
+        });
+        let mut counts = Vec::new();
+        while !lines.is_finished() {
+            let response = scrape(addr)?;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            counts.extend(sample(&response, "lmux_group_lines_total", "build"));
+        }
+        lines.join().map_err(|_| anyhow!("Logging thread panicked"))?;
+        counts.extend(sample(&scrape(addr)?, "lmux_group_lines_total", "build"));
+        assert!(counts.is_sorted());
+        assert_eq!(counts.last(), Some(&500));
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::prelude::*;
+
+use crate::format;
+use crate::framebuffer::LineIndex;
+use crate::fuzzy;
+use crate::group;
+use crate::terminal;
+use crossterm::style::Stylize;
+use std::collections::HashMap;
+
+// ===============
+// === Palette ===
+// ===============
+
+/// Maximum number of listed matches.
+const MAX_MATCHES: usize = 10;
+
+/// Maximum width of the palette.
+const MAX_WIDTH: usize = 60;
+
+/// Quick-switch palette opened with `Ctrl+P`, selecting a group by its header. The query is
+/// matched fuzzily, see [`fuzzy::score`]. Drawn over the top rows of the screen.
+#[derive(Clone, Debug, Default)]
+pub struct Palette {
+    pub query: String,
+    /// Index of the highlighted match.
+    pub selected: usize,
+}
+
+impl Palette {
+    /// Groups with headers matching the query, the best matches first. Equally good matches keep
+    /// the given order.
+    pub fn matches<'t>(&self, groups: impl IntoIterator<Item = (group::Id, &'t str)>)
+    -> Vec<(group::Id, &'t str)> {
+        let mut matches = groups.into_iter()
+            .filter_map(|(id, header)| Some((fuzzy::score(&self.query, header)?, id, header)))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+        matches.into_iter().take(MAX_MATCHES).map(|(_, id, header)| (id, header)).collect()
+    }
+
+    pub fn push(&mut self, char: char) {
+        self.query.push(char);
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn shift_selection(&mut self, shift: isize, match_count: usize) {
+        let len = match_count.max(1) as isize;
+        self.selected = (self.selected as isize + shift).rem_euclid(len) as usize;
+    }
+
+    /// Index of the highlighted match. The matches change with the groups, so the index is
+    /// clamped to them.
+    fn selected_index(&self, match_count: usize) -> usize {
+        self.selected.min(match_count.saturating_sub(1))
+    }
+
+    pub fn selected_group(&self, matches: &[(group::Id, &str)]) -> Option<group::Id> {
+        matches.get(self.selected_index(matches.len())).map(|(id, _)| *id)
+    }
+
+    /// Rows of the palette by their screen rows, with the column they start at: the query
+    /// followed by the matches.
+    pub fn overlay(&self, matches: &[(group::Id, &str)], size: terminal::Size)
+    -> HashMap<LineIndex, (usize, String)> {
+        let width = MAX_WIDTH.min(size.cols);
+        let column = (size.cols - width) / 2;
+        let entry = |text: &str| {
+            let text = format::truncate(text, width);
+            let padding = width.saturating_sub(format::visible_width(&text));
+            format!("{text}{}", " ".repeat(padding))
+        };
+        let selected = self.selected_index(matches.len());
+        let mut rows = vec![entry(&format!(" > {}_", self.query)).black().on_white().to_string()];
+        if matches.is_empty() {
+            rows.push(entry(" No matching groups").dark_grey().on_black().to_string());
+        }
+        rows.extend(matches.iter().enumerate().map(|(index, (_, header))| {
+            let row = entry(&format!(" {header}"));
+            if index == selected {
+                row.black().on_green().to_string()
+            } else {
+                row.white().on_dark_grey().to_string()
+            }
+        }));
+        rows.into_iter().take(size.rows).enumerate()
+            .map(|(row, content)| (LineIndex(row), (column, content)))
+            .collect()
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(matches: &[(group::Id, &str)]) -> Vec<usize> {
+        matches.iter().map(|(id, _)| id.0).collect()
+    }
+
+    #[test]
+    fn best_matches_come_first() {
+        let headers = ["docs", "test-runner", "trace", "build"];
+        let groups = || headers.iter().enumerate().map(|(id, header)| (group::Id(id), *header));
+        let mut palette = Palette::default();
+        assert_eq!(ids(&palette.matches(groups())), [0, 1, 2, 3]);
+        palette.push('t');
+        palette.push('r');
+        assert_eq!(ids(&palette.matches(groups())), [2, 1]);
+        let many = (0..20).map(|id| (group::Id(id), "trace"));
+        assert_eq!(palette.matches(many).len(), MAX_MATCHES);
+    }
+
+    #[test]
+    fn selection_wraps_and_is_clamped_to_the_matches() {
+        let matches = [(group::Id(4), "a"), (group::Id(7), "b")];
+        let mut palette = Palette::default();
+        palette.shift_selection(-1, matches.len());
+        assert_eq!(palette.selected_group(&matches), Some(group::Id(7)));
+        assert_eq!(palette.selected_group(&matches[..1]), Some(group::Id(4)));
+        assert_eq!(palette.selected_group(&[]), None);
+        palette.push('x');
+        assert_eq!(palette.selected, 0);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::prelude::*;
+
+use crate::hash_tree::HashTree;
+
+// ===============
+// === Segment ===
+// ===============
+
+/// Single segment of a [`Pattern`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Segment {
+    Exact(String),
+    /// Any single segment, written as `*`.
+    Any,
+    /// Any number of segments, including none, written as `**`.
+    AnyDepth,
+}
+
+impl From<&str> for Segment {
+    fn from(segment: &str) -> Self {
+        match segment {
+            "*" => Self::Any,
+            "**" => Self::AnyDepth,
+            _ => Self::Exact(segment.to_string()),
+        }
+    }
+}
+
+// ===============
+// === Pattern ===
+// ===============
+
+/// Group selector path which may contain wildcard segments, e.g. `["build", "*"]` selecting all
+/// direct children of `build`, or `["**"]` selecting all groups.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Pattern {
+    pub segments: Vec<Segment>,
+}
+
+impl Pattern {
+    pub fn parse(path: &[String]) -> Self {
+        Self { segments: path.iter().map(|s| Segment::from(s.as_str())).collect() }
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.segments.iter().any(|s| !matches!(s, Segment::Exact(_)))
+    }
+
+    /// Leading exact segments of the pattern.
+    pub fn exact_prefix(&self) -> Vec<String> {
+        self.segments.iter().map_while(|s| match s {
+            Segment::Exact(segment) => Some(segment.clone()),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn matches<S: AsRef<str>>(&self, path: &[S]) -> bool {
+        Self::matches_segments(&self.segments, path)
+    }
+
+    fn matches_segments<S: AsRef<str>>(segments: &[Segment], path: &[S]) -> bool {
+        match segments.split_first() {
+            None => path.is_empty(),
+            Some((Segment::AnyDepth, rest)) =>
+                (0..=path.len()).any(|skip| Self::matches_segments(rest, &path[skip..])),
+            Some((segment, rest)) => path.split_first().is_some_and(|(head, tail)| {
+                let head_matches = match segment {
+                    Segment::Exact(exact) => exact == head.as_ref(),
+                    _ => true,
+                };
+                head_matches && Self::matches_segments(rest, tail)
+            }),
+        }
+    }
+
+    /// Values of all paths in the tree matching the pattern, in the tree iteration order. Only the
+    /// subtree under the exact prefix of the pattern is visited.
+    pub fn find<'t, V>(&self, tree: &'t HashTree<String, V>) -> Vec<&'t V> {
+        let prefix = self.exact_prefix();
+        let rest = &self.segments[prefix.len()..];
+        tree.subtree(&prefix).into_iter()
+            .flat_map(|subtree| subtree.iter())
+            .filter(|(path, _)| Self::matches_segments(rest, path))
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
@@ -0,0 +1,294 @@
+use crate::prelude::*;
+
+use crate::LineId;
+use crate::Logger;
+use crate::SharedLogger;
+use crate::group;
+use crate::logger;
+use crate::snapshot::LineSnapshot;
+use crate::snapshot::Snapshot;
+use serde::Deserialize;
+use serde::Serialize;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::thread;
+use std::time::Duration;
+
+/// Time between checks of the served logger for updates.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time between attempts to reconnect to the served logger.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum length of an encoded update. Longer updates are rejected instead of allocated, as the
+/// length comes from the network.
+const MAX_UPDATE_LEN: usize = 64 * 1024 * 1024;
+
+// ==============
+// === Update ===
+// ==============
+
+/// Change of the served logger state. Replaying an update more than once has no further effect.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Update {
+    /// Full state, sent first on every connection.
+    Snapshot(Snapshot),
+    Line { path: Vec<String>, line: LineSnapshot },
+    /// Repeat count of an already sent line, grown by deduplicated repetitions.
+    Repeat { path: Vec<String>, line: usize, repeat: u32 },
+    Header { path: Vec<String>, header: String },
+    Footer { path: Vec<String>, footer: String },
+}
+
+/// Writes the update as a big-endian `u32` length followed by its JSON encoding.
+fn write_update(stream: &mut impl Write, update: &Update) -> Result {
+    let bytes = serde_json::to_vec(update)?;
+    let len = u32::try_from(bytes.len())?;
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_update(stream: &mut impl Read) -> Result<Update> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_UPDATE_LEN {
+        return Err(anyhow!("Update of {len} bytes exceeds the limit of {MAX_UPDATE_LEN} bytes"));
+    }
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+// ==============
+// === Mirror ===
+// ==============
+
+/// Group state already sent to a client.
+#[derive(Debug, Default)]
+struct SentGroup {
+    header: String,
+    footer: String,
+    /// Id and repeat count of the last line.
+    last_line: Option<(usize, u32)>,
+}
+
+/// Logger state already sent to a client.
+#[derive(Debug, Default)]
+struct Mirror {
+    next_line_id: usize,
+    groups: Vec<SentGroup>,
+}
+
+impl Mirror {
+    fn new(snapshot: &Snapshot) -> Self {
+        let next_line_id = snapshot.next_line_id;
+        let groups = snapshot.groups.iter().map(|group| SentGroup {
+            header: group.header.clone(),
+            footer: group.footer.clone(),
+            last_line: group.lines.last().map(|line| (line.id, line.repeat)),
+        }).collect();
+        Self { next_line_id, groups }
+    }
+
+    /// Updates of the logger since the last call.
+    fn updates(&mut self, logger: &Logger) -> Vec<Update> {
+        let mut updates = Vec::new();
+        let mut lines = Vec::new();
+        for (index, group) in logger.groups.iter().enumerate() {
+            if index >= self.groups.len() {
+                self.groups.push(default());
+            }
+            let sent = &mut self.groups[index];
+            if sent.header != group.header {
+                sent.header.clone_from(&group.header);
+                let header = sent.header.clone();
+                updates.push(Update::Header { path: group.path.clone(), header });
+            }
+            if sent.footer != group.footer {
+                sent.footer.clone_from(&group.footer);
+                let footer = sent.footer.clone();
+                updates.push(Update::Footer { path: group.path.clone(), footer });
+            }
+            let last_line = group.lines.last().map(|line| (*line.timestamp, line.repeat));
+            let repeated = sent.last_line != last_line;
+            if let Some((line, repeat)) = last_line && repeated && line < self.next_line_id {
+                updates.push(Update::Repeat { path: group.path.clone(), line, repeat });
+            }
+            sent.last_line = last_line;
+            let new_lines = group.lines.iter().rev()
+                .take_while(|line| *line.timestamp >= self.next_line_id);
+            lines.extend(new_lines.map(|line| (group, line)));
+        }
+        lines.sort_by_key(|(_, line)| line.timestamp);
+        updates.extend(lines.into_iter().map(|(group, line)| {
+            Update::Line { path: group.path.clone(), line: LineSnapshot::new(line) }
+        }));
+        self.next_line_id = *logger.next_line_id;
+        updates
+    }
+}
+
+// =============
+// === Serve ===
+// =============
+
+impl SharedLogger {
+    /// Serves the logger state to the clients connecting to the address, see [`attach`]. Every
+    /// client receives a full snapshot, followed by the changes.
+    pub fn serve(&self, addr: impl ToSocketAddrs) -> Result<thread::JoinHandle<Result>> {
+        let listener = TcpListener::bind(addr)?;
+        let logger = self.clone();
+        let thread = thread::Builder::new().name("lmux-serve".into()).spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream?;
+                let logger = logger.clone();
+                thread::Builder::new().name("lmux-client".into())
+                    .spawn(move || logger.serve_client(stream).ok())?;
+            }
+            Ok(())
+        })?;
+        Ok(thread)
+    }
+
+    fn serve_client(&self, mut stream: TcpStream) -> Result {
+        let snapshot = self.modify(|l| l.snapshot())?;
+        let mut mirror = Mirror::new(&snapshot);
+        write_update(&mut stream, &Update::Snapshot(snapshot))?;
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            for update in self.modify(|l| mirror.updates(l))? {
+                write_update(&mut stream, &update)?;
+            }
+        }
+    }
+}
+
+pub fn serve(addr: impl ToSocketAddrs) -> Result<thread::JoinHandle<Result>> {
+    logger().serve(addr)
+}
+
+// ==============
+// === Attach ===
+// ==============
+
+impl Logger {
+    /// Applies an update received from a served logger.
+    fn apply_update(&mut self, update: Update) -> Result {
+        match update {
+            Update::Snapshot(snapshot) => {
+                self.groups.range.data.clear();
+                self.groups.zoomed = None;
+                self.path_to_group_id = default();
+                self.history.clear();
+                self.restore(snapshot)?;
+            }
+            Update::Line { path, line } => {
+                if line.id >= *self.next_line_id {
+                    let group_id = self.create_group(&path);
+                    let line = line.restore();
+                    self.next_line_id = line.timestamp;
+                    self.push_line_of_kind(group_id, line.kind, line.log);
+                    let group = &mut self.groups[*group_id];
+                    if let Some(last) = group.lines.last_mut() {
+                        last.time = line.time;
+                        last.repeat = line.repeat;
+                    }
+                    if let group::LineKind::RunStart(run) = line.kind {
+                        group.run = run;
+                    }
+                    self.next_line_id = LineId(line.timestamp.0 + 1);
+                }
+            }
+            Update::Repeat { path, line, repeat } => {
+                let group_id = self.create_group(&path);
+                let lines = &mut self.groups[*group_id].lines;
+                if let Some(last) = lines.last_mut() && *last.timestamp == line {
+                    last.repeat = repeat;
+                }
+            }
+            Update::Header { path, header } => {
+                let group_id = self.create_group(&path);
+                self.groups[*group_id].header = header;
+                self.groups[*group_id].header_pending = false;
+            }
+            Update::Footer { path, footer } => {
+                let group_id = self.create_group(&path);
+                self.groups[*group_id].footer = footer;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mirrors the logger served at the address in the terminal, reconnecting if the connection is
+/// lost. Runs until the user quits the UI.
+pub fn attach(addr: impl ToSocketAddrs + Send + 'static) -> Result {
+    let logger = SharedLogger::default();
+    logger.modify(|l| l.attached = true)?;
+    let receiver = logger.clone();
+    thread::Builder::new().name("lmux-attach".into()).spawn(move || loop {
+        if let Err(error) = receive_updates(&receiver, &addr) {
+            receiver.modify(|l| l.push_debug_line(format!("Connection lost: {error}"))).ok();
+        }
+        thread::sleep(RECONNECT_INTERVAL);
+    })?;
+    logger.main(true)
+}
+
+/// Applies the updates received from the address until the connection fails.
+fn receive_updates(logger: &SharedLogger, addr: &impl ToSocketAddrs) -> Result {
+    let mut stream = TcpStream::connect(addr)?;
+    loop {
+        let update = read_update(&mut stream)?;
+        logger.modify(|l| l.apply_update(update))??;
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_updates_are_rejected() {
+        let len = u32::try_from(MAX_UPDATE_LEN + 1).unwrap_or(u32::MAX);
+        assert!(read_update(&mut &len.to_be_bytes()[..]).is_err());
+    }
+
+    #[test]
+    fn repeats_reach_the_attached_view() -> Result {
+        let served = SharedLogger::default();
+        let group_id = served.create_group(&["net"][..])?;
+        served.set_group_dedup(group_id, true)?;
+        served.log(&["net"][..], None, "retrying");
+        let snapshot = served.modify(|l| l.snapshot())?;
+        let mut mirror = Mirror::new(&snapshot);
+        let attached = SharedLogger::default();
+        attached.modify(|l| l.apply_update(Update::Snapshot(snapshot)))??;
+        served.log(&["net"][..], None, "retrying");
+        served.log(&["net"][..], None, "retrying");
+        let mut bytes = Vec::new();
+        for update in served.modify(|l| mirror.updates(l))? {
+            write_update(&mut bytes, &update)?;
+        }
+        let mut stream = &bytes[..];
+        while !stream.is_empty() {
+            let update = read_update(&mut stream)?;
+            attached.modify(|l| l.apply_update(update))??;
+        }
+        let repeats = attached.modify(|l| {
+            l.groups[*group_id].lines.iter().map(|line| line.repeat).collect::<Vec<_>>()
+        })?;
+        assert_eq!(repeats, [3]);
+        assert!(served.modify(|l| mirror.updates(l))?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+
+use crate::LineId;
+use crate::group;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+use std::time::SystemTime;
+
+// ====================
+// === LineSnapshot ===
+// ====================
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LineSnapshot {
+    pub id: usize,
+    /// Time of the line in milliseconds since the Unix epoch.
+    pub time: u64,
+    pub kind: group::LineKind,
+    pub repeat: u32,
+    pub content: String,
+    pub status: group::Status,
+    pub level: group::Level,
+}
+
+impl LineSnapshot {
+    pub fn new(line: &group::Line) -> Self {
+        let since_epoch = line.time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        Self {
+            id: *line.timestamp,
+            time: since_epoch.as_millis() as u64,
+            kind: line.kind,
+            repeat: line.repeat,
+            content: line.log.content.clone(),
+            status: line.log.status,
+            level: line.log.level,
+        }
+    }
+
+    pub fn restore(self) -> group::Line {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(self.time);
+        let log = group::Log { content: self.content, status: self.status, level: self.level };
+        group::Line { log, timestamp: LineId(self.id), time, kind: self.kind, repeat: self.repeat }
+    }
+}
+
+// =====================
+// === GroupSnapshot ===
+// =====================
+
+/// State of a group. Settings configured by the application, like templates, limits, and
+/// highlighting rules, are not included and should be configured again.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GroupSnapshot {
+    pub path: Vec<String>,
+    pub header: String,
+    pub footer: String,
+    pub collapsed: Option<bool>,
+    pub selected: bool,
+    /// Id of the top displayed line, if scrolled back.
+    pub scroll: Option<usize>,
+    pub run: usize,
+    pub lines: Vec<LineSnapshot>,
+}
+
+impl GroupSnapshot {
+    pub fn new(group: &group::Group) -> Self {
+        Self {
+            path: group.path.clone(),
+            header: group.header.clone(),
+            footer: group.footer.clone(),
+            collapsed: group.collapsed,
+            selected: group.selected,
+            scroll: group.scroll.map(|t| *t),
+            run: group.run,
+            lines: group.lines.iter().map(LineSnapshot::new).collect(),
+        }
+    }
+
+    /// Overwrites the state of the group with the snapshot.
+    pub fn restore(self, group: &mut group::Group) {
+        group.header = self.header;
+        group.header_pending = false;
+        group.footer = self.footer;
+        group.collapsed = self.collapsed;
+        group.selected = self.selected;
+        group.scroll = self.scroll.map(LineId);
+        group.run = self.run;
+        group.lines = self.lines.into_iter().map(LineSnapshot::restore).collect();
+    }
+}
+
+// ================
+// === Snapshot ===
+// ================
+
+/// State of a logger which can be saved and restored in another process, see
+/// [`crate::Logger::snapshot`]. The style and the callbacks are not included.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub groups: Vec<GroupSnapshot>,
+    /// Group index and status tag of every line, in the push order.
+    pub history: Vec<(usize, group::StatusTag)>,
+    pub next_line_id: usize,
+}
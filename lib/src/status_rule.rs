@@ -0,0 +1,36 @@
+use crate::prelude::*;
+
+use crate::group::Status;
+use regex::Regex;
+
+// ==============
+// === RuleId ===
+// ==============
+
+/// Identifier of a registered status rule, used to remove it.
+#[derive(Clone, Copy, Debug, Default, Deref, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RuleId(pub usize);
+
+// ==================
+// === StatusRule ===
+// ==================
+
+/// Status given to the pushed log lines matching the regex, replacing their own status.
+#[derive(Clone, Debug)]
+pub struct StatusRule {
+    pub id: RuleId,
+    pub regex: Regex,
+    pub status: Status,
+}
+
+impl StatusRule {
+    pub fn new(id: RuleId, pattern: &str, status: Status) -> Result<Self> {
+        let regex = Regex::new(pattern)?;
+        Ok(Self { id, regex, status })
+    }
+}
+
+/// Status of the first rule matching the line.
+pub fn find<'a>(rules: impl IntoIterator<Item = &'a StatusRule>, line: &str) -> Option<Status> {
+    rules.into_iter().find(|rule| rule.regex.is_match(line)).map(|rule| rule.status)
+}
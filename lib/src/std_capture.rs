@@ -0,0 +1,218 @@
+//! Capturing of the standard output and error of the process into groups. Supported on Unix only,
+//! on other platforms [`SharedLogger::capture_std`] returns an error.
+
+use crate::prelude::*;
+
+use crate::GroupStringSelector;
+use crate::SharedLogger;
+use std::fs::File;
+
+#[cfg(unix)]
+use std::io::BufRead;
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+#[cfg(unix)]
+use std::os::fd::FromRawFd;
+#[cfg(unix)]
+use std::os::fd::OwnedFd;
+#[cfg(unix)]
+use std::os::fd::RawFd;
+#[cfg(unix)]
+use std::thread::JoinHandle;
+#[cfg(unix)]
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
+
+/// Time [`restore`] waits for the capturing threads to log the remaining output.
+#[cfg(unix)]
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Original targets of the standard output and error, saved while they are captured.
+#[cfg(unix)]
+static SAVED: Mutex<Option<[OwnedFd; 2]>> = Mutex::new(None);
+
+#[cfg(unix)]
+fn saved() -> std::sync::MutexGuard<'static, Option<[OwnedFd; 2]>> {
+    SAVED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Threads logging the captured output, see [`SharedLogger::capture_std`].
+#[cfg(unix)]
+static READERS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+#[cfg(unix)]
+fn check(result: libc::c_int) -> std::io::Result<libc::c_int> {
+    if result < 0 { Err(std::io::Error::last_os_error()) } else { Ok(result) }
+}
+
+/// Descriptor pointed to a pipe, pointed back to its original target when dropped, unless the
+/// redirection is kept.
+#[cfg(unix)]
+struct Redirection {
+    fd: RawFd,
+    saved: Option<OwnedFd>,
+}
+
+#[cfg(unix)]
+impl Redirection {
+    /// Keeps the descriptor redirected, returning its original target.
+    fn keep(mut self) -> Option<OwnedFd> {
+        self.saved.take()
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Redirection {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            // SAFETY: Both descriptors are valid.
+            unsafe { libc::dup2(saved.as_raw_fd(), self.fd) };
+        }
+    }
+}
+
+/// Points the descriptor to a new pipe. Returns the redirection and the reading end of the pipe.
+#[cfg(unix)]
+fn redirect(fd: RawFd) -> std::io::Result<(Redirection, File)> {
+    // SAFETY: The descriptors passed to libc are valid, and the ones it returns are owned by the
+    // created handles only.
+    unsafe {
+        let saved = OwnedFd::from_raw_fd(check(libc::dup(fd))?);
+        let mut pipe = [0; 2];
+        check(libc::pipe(pipe.as_mut_ptr()))?;
+        let reader = File::from_raw_fd(pipe[0]);
+        let writer = OwnedFd::from_raw_fd(pipe[1]);
+        check(libc::dup2(writer.as_raw_fd(), fd))?;
+        Ok((Redirection { fd, saved: Some(saved) }, reader))
+    }
+}
+
+/// Logs the lines read from the pipe until all its writers are closed. Invalid UTF-8 is replaced,
+/// and the last line is logged even if it is not terminated.
+#[cfg(unix)]
+fn forward(logger: &SharedLogger, path: &[String], reader: impl std::io::Read) {
+    let mut reader = std::io::BufReader::new(reader);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).is_ok_and(|len| len > 0) {
+        let text = String::from_utf8_lossy(&line);
+        logger.log(path, None, text.trim_end_matches(['\n', '\r']));
+        line.clear();
+    }
+}
+
+/// Whether the standard streams are captured.
+#[cfg(unix)]
+pub fn is_active() -> bool {
+    saved().is_some()
+}
+
+#[cfg(not(unix))]
+pub fn is_active() -> bool {
+    false
+}
+
+/// Original standard output, if the standard streams are captured.
+#[cfg(unix)]
+pub fn original_stdout() -> Option<File> {
+    saved().as_ref().and_then(|[out, _]| out.try_clone().ok()).map(File::from)
+}
+
+/// Original standard error, if the standard streams are captured.
+#[cfg(unix)]
+pub fn original_stderr() -> Option<File> {
+    saved().as_ref().and_then(|[_, err]| err.try_clone().ok()).map(File::from)
+}
+
+#[cfg(not(unix))]
+pub fn original_stdout() -> Option<File> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn original_stderr() -> Option<File> {
+    None
+}
+
+/// Points the standard output and error back to their original targets. The capturing threads
+/// stop after logging the remaining output, which is waited for up to [`DRAIN_TIMEOUT`], as
+/// child processes may keep the pipes open.
+#[cfg(unix)]
+pub fn restore() -> Result {
+    let Some(saved) = saved().take() else { return Ok(()) };
+    std::io::Write::flush(&mut std::io::stdout()).ok();
+    std::io::Write::flush(&mut std::io::stderr()).ok();
+    for (fd, saved) in [libc::STDOUT_FILENO, libc::STDERR_FILENO].into_iter().zip(saved) {
+        // SAFETY: Both descriptors are valid.
+        check(unsafe { libc::dup2(saved.as_raw_fd(), fd) })?;
+    }
+    let readers = std::mem::take(&mut *READERS.lock().unwrap_or_else(|e| e.into_inner()));
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while Instant::now() < deadline && !readers.iter().all(|reader| reader.is_finished()) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn restore() -> Result {
+    Ok(())
+}
+
+impl SharedLogger {
+    /// Redirects the standard output and error of the process to the groups, line by line, so
+    /// that printing does not break the UI, which is still drawn to the original standard output.
+    /// The streams are restored when the UI exits.
+    #[cfg(unix)]
+    pub fn capture_std(&self, out: impl GroupStringSelector, err: impl GroupStringSelector)
+    -> Result {
+        let mut saved = saved();
+        if saved.is_some() {
+            return Err(anyhow!("The standard streams are captured already."));
+        }
+        std::io::Write::flush(&mut std::io::stdout())?;
+        std::io::Write::flush(&mut std::io::stderr())?;
+        let out_path = out.with_selector(|t| t.to_vec());
+        let err_path = err.with_selector(|t| t.to_vec());
+        // If redirecting the standard error fails, dropping the redirection of the standard
+        // output points it back.
+        let (out_redirection, out_reader) = redirect(libc::STDOUT_FILENO)?;
+        let (err_redirection, err_reader) = redirect(libc::STDERR_FILENO)?;
+        let mut readers = READERS.lock().unwrap_or_else(|e| e.into_inner());
+        for (path, reader) in [(out_path, out_reader), (err_path, err_reader)] {
+            let logger = self.clone();
+            let thread = std::thread::Builder::new().name("lmux-capture".into())
+                .spawn(move || forward(&logger, &path, reader))?;
+            readers.push(thread);
+        }
+        *saved = out_redirection.keep().zip(err_redirection.keep()).map(Into::into);
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn capture_std(&self, _out: impl GroupStringSelector, _err: impl GroupStringSelector)
+    -> Result {
+        Err(anyhow!("Capturing the standard streams is supported on Unix only."))
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unterminated_last_line_is_logged() -> Result {
+        let logger = SharedLogger::default();
+        let path = ["out".to_owned()];
+        forward(&logger, &path, &b"first\r\nsecond\n\xffthird"[..]);
+        let group_id = logger.group_id(&path[..])?;
+        let lines = logger.read_lines(group_id, 0..usize::MAX)?;
+        let contents = lines.into_iter().map(|(_, _, content)| content).collect::<Vec<_>>();
+        assert_eq!(contents, ["first", "second", "\u{fffd}third"]);
+        Ok(())
+    }
+}
@@ -1,41 +1,297 @@
 use crate::prelude::*;
 
+use std::ops::Range;
+use std::time::Duration;
 use std::time::SystemTime;
+use crossterm::style::Color;
 use crossterm::style::Stylize;
 
+use crate::format;
 use crate::group;
+use crate::terminal;
 use crate::widget;
-use crate::index_to_group_char;
+use crate::index_to_group_label;
 use crate::group::Group;
 use crate::LineRange;
 
-// ================
-// === Duration ===
-// ================
-
-fn format_duration(total_ms: u128, show_ms: bool) -> String {
-    let total_seconds = total_ms / 1000;
-    let ms = total_ms % 1000;
-    let s = total_seconds % 60;
-    let m = (total_seconds / 60) % 60;
-    let h = (total_seconds / 3600) % 24;
-    let d = total_seconds / 86400;
-
-    let mut parts = Vec::new();
-    if d > 0 { parts.push(format!("{d}d")) }
-    if h > 0 { parts.push(format!("{h}h")) }
-    if m > 0 { parts.push(format!("{m}m")) }
-    parts.push(format!("{s}s"));
-    if show_ms && ms > 0 && d == 0 {
-        parts.push(format!("{ms}ms"));
-    }
-    parts.join(" ")
+// ============
+// === Rate ===
+// ============
+
+fn format_rate(rate: f64, uses_amount: bool) -> String {
+    if uses_amount {
+        format!("{}/s", format::bytes(rate.round() as u64))
+    } else {
+        format!("{} lines/s", format::count(rate.round() as u64))
+    }
+}
+
+// ===================
+// === MenuSegment ===
+// ===================
+
+/// Part of the bottom menu row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuSegment {
+    /// Key shortcut of an action. Actions which have no effect in the current state are disabled,
+    /// and shown dimmed instead of being hidden, so that the menu does not change its layout.
+    Button { label: String, shortcut: String, enabled: bool },
+    /// Name of the selected group, or the number of selected groups.
+    Selection(String),
+    /// Scroll position of the selected group, starting from 1, and its number of displayed lines.
+    Scroll { line: usize, total: usize },
+    /// Number of lines shown in the history view and the number of all lines.
+    History { line: usize, total: usize },
+    /// Minimum displayed level of the selected group, if it hides any levels.
+    LevelFilter(group::Level),
+    /// Cost of rendering the previous frame, if enabled, see
+    /// [`crate::SharedLogger::toggle_frame_stats`].
+    FrameStats(crate::FrameStats),
+    /// Full text of the hovered row, if the row is cut off by the terminal.
+    Tooltip(String),
 }
 
+impl MenuSegment {
+    pub fn button(label: &str, shortcut: &str) -> Self {
+        Self::button_if(label, shortcut, true)
+    }
+
+    pub fn button_if(label: &str, shortcut: &str, enabled: bool) -> Self {
+        let label = label.to_string();
+        let shortcut = shortcut.to_string();
+        Self::Button { label, shortcut, enabled }
+    }
+
+    /// Segments of lower importance are dropped first if the menu does not fit the terminal.
+    /// Disabled buttons are dropped before the enabled ones.
+    pub fn importance(&self) -> usize {
+        match self {
+            Self::Button { enabled: false, .. } => 0,
+            Self::Button { enabled: true, .. } => 1,
+            Self::LevelFilter(_) => 2,
+            Self::Scroll { .. } => 3,
+            Self::History { .. } => 4,
+            Self::Selection(_) => 5,
+            Self::FrameStats(_) => 6,
+            Self::Tooltip(_) => 7,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        match self {
+            Self::Button { label, shortcut, enabled: true } =>
+                format!(" {label}{}", format!(" {shortcut} ").green().bold()),
+            Self::Button { label, shortcut, enabled: false } =>
+                format!(" {label} {shortcut} ").dark_grey().dim().to_string(),
+            Self::Selection(name) => format!(" {}", name.clone().bold()),
+            Self::Scroll { line, total } => {
+                let position = format!("line {} / {}", format::count(*line as u64),
+                    format::count(*total as u64));
+                format!(" {}", position.dark_grey())
+            }
+            Self::History { line, total } => {
+                let position = format!("history: {} / {}", format::count(*line as u64),
+                    format::count(*total as u64));
+                format!(" {}", position.dark_grey())
+            }
+            Self::LevelFilter(level) => format!(" {}", format!("level ≥ {level:?}").yellow()),
+            Self::FrameStats(stats) => format!(" {}", stats.to_string().dark_grey()),
+            Self::Tooltip(text) => format!(" {text}"),
+        }
+    }
+}
+
+// =============
+// === Style ===
+// =============
+
 pub trait Style: Send + Sync {
+    /// Called before rendering every frame with the theme configured on the logger.
+    fn on_frame(&mut self, _theme: &Theme) {}
+
+    /// Called before rendering every frame with the focus state of the terminal. Terminals which
+    /// do not report focus changes are always considered focused.
+    fn set_focused(&mut self, _focused: bool) {}
+
+    /// Called before rendering every frame with the terminal size. The rows are as wide as the
+    /// terminal.
+    fn set_size(&mut self, _size: terminal::Size) {}
+
+    /// Renders the header row. The `group_index` of this and the other row hooks is the id of the
+    /// group, which its label is derived from.
     fn header(&mut self, group: &LineRange<&'_ Group>, group_index: group::Id, s: &str) -> String;
     fn log_line(&mut self, group: &LineRange<&'_ Group>, group_index: group::Id, s: &str) -> String;
     fn footer(&mut self, group: &LineRange<&'_ Group>, group_index: group::Id, s: &str) -> String;
+
+    /// Row of the log line focused in the line mode, see [`crate::Logger::toggle_line_mode`].
+    fn focused_log_line
+    (&mut self, group: &LineRange<&'_ Group>, group_index: group::Id, s: &str) -> String {
+        self.log_line(group, group_index, s).reverse().to_string()
+    }
+
+    /// Columns of the header row which toggle the collapse of the group when clicked.
+    fn collapse_toggle(&self, _group: &LineRange<&'_ Group>) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Number of columns taken by the decoration which [`Self::log_line`] puts before the content.
+    /// Used to compute the width of wrapped lines.
+    fn log_line_indent(&self) -> usize {
+        0
+    }
+
+    /// Content of a log line of a level other than [`group::Level::Info`].
+    fn level_content(&mut self, _group: &LineRange<&'_ Group>, level: group::Level, s: &str)
+    -> String {
+        match level {
+            group::Level::Trace | group::Level::Debug => s.dark_grey().to_string(),
+            group::Level::Info => s.to_string(),
+            group::Level::Warn => s.yellow().to_string(),
+            group::Level::Error => s.red().to_string(),
+        }
+    }
+
+    /// Part of a line which changed since the previous line, see
+    /// [`group::State::diff_highlight`]. The styles of the rest of the line have to be kept.
+    fn changed_span(&mut self, _group: &LineRange<&'_ Group>, s: &str) -> String {
+        use crossterm::style::Attribute;
+        let (bold, underlined) = (Attribute::Bold, Attribute::Underlined);
+        let (normal, no_underline) = (Attribute::NormalIntensity, Attribute::NoUnderline);
+        format!("{bold}{underlined}{s}{normal}{no_underline}")
+    }
+
+    /// Suffix of a line merged with its `count` consecutive occurrences, see
+    /// [`group::State::dedup`].
+    fn repeat_suffix(&mut self, _group: &LineRange<&'_ Group>, count: u32) -> String {
+        format!(" ×{count}").dark_grey().to_string()
+    }
+
+    /// Row at the top or bottom edge of the paged group list, showing the number of groups above
+    /// or below the drawn ones, see [`crate::GroupViewport`].
+    fn more_groups(&mut self, count: usize, above: bool) -> String {
+        let arrow = if above { "⌃" } else { "⌄" };
+        format!(" {arrow} {count} more").dark_grey().to_string()
+    }
+
+    /// Row above the groups showing the number of groups by status, see
+    /// [`crate::Settings::show_summary`].
+    fn summary(&mut self, summary: &group::Summary) -> String {
+        let running = format!("{} {} running", "▶".cyan(), summary.running);
+        let done = format!("{} {} done", "✓".green(), summary.done);
+        let failed = format!("{} {} failed", "✗".red(), summary.failed);
+        let cancelled = format!("{} {} cancelled", "⊘".grey(), summary.cancelled);
+        let pending = format!("{} {} pending", "◼".dark_grey(), summary.pending);
+        if summary.cancelled > 0 {
+            format!(" {running}   {done}   {failed}   {cancelled}   {pending}")
+        } else {
+            format!(" {running}   {done}   {failed}   {pending}")
+        }
+    }
+
+    /// Bottom menu row. Segments are dropped, starting from the last one of the lowest
+    /// importance, until the row fits the width.
+    fn menu(&mut self, segments: &[MenuSegment], width: usize) -> String {
+        let mut rendered =
+            segments.iter().map(|t| (t.importance(), t.render())).collect::<Vec<_>>();
+        let row_width = |rendered: &[(usize, String)]| -> usize {
+            rendered.iter().map(|t| format::visible_width(&t.1)).sum()
+        };
+        while rendered.len() > 1 && row_width(&rendered) > width {
+            let least_important = rendered.iter().enumerate().rev().min_by_key(|t| t.1.0);
+            if let Some((index, _)) = least_important {
+                rendered.remove(index);
+            }
+        }
+        rendered.into_iter().map(|t| t.1).collect()
+    }
+
+    /// Row below the header of a collapsed group showing its last line, which is an error, see
+    /// [`crate::Settings::show_collapsed_errors`].
+    fn collapsed_error_line
+    (&mut self, _group: &LineRange<&'_ Group>, _group_index: group::Id, s: &str) -> String {
+        format!("   {}", format!("↳ {s}").red().dim())
+    }
+
+    /// Line separating consecutive runs of the group.
+    fn separator(&mut self, _group: &LineRange<&'_ Group>, _group_index: group::Id, run: usize)
+    -> String {
+        format!("── run {run} ──").dark_grey().to_string()
+    }
+
+    /// Row of a note attached to the log line above it, see [`group::Annotation`].
+    fn annotation(
+        &mut self,
+        _group: &LineRange<&'_ Group>,
+        _group_index: group::Id,
+        annotation: &group::Annotation
+    ) -> String {
+        format!("  {}", annotation_note(annotation))
+    }
+}
+
+/// Text of an annotation row, colored by the severity of the annotation.
+fn annotation_note(annotation: &group::Annotation) -> String {
+    let note = format!("↳ {}", annotation.text).italic();
+    match annotation.severity {
+        group::Level::Trace | group::Level::Debug => note.dark_grey(),
+        group::Level::Info => note.cyan(),
+        group::Level::Warn => note.yellow(),
+        group::Level::Error => note.red(),
+    }.to_string()
+}
+
+// =============
+// === Theme ===
+// =============
+
+/// Presentation options, configured on the logger and passed to the style on every frame.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Show the estimated remaining time next to the progress bar.
+    pub show_eta: bool,
+    pub spinner: widget::Spinner,
+    /// Show the progress percentage inside progress bars wide enough to fit it.
+    pub progress_label: bool,
+    /// Length of the sparkline of lines per second shown in headers. Hidden if set to zero.
+    pub sparkline_len: usize,
+    /// Use Unicode symbols. If disabled, widgets fall back to their ASCII versions.
+    pub unicode: bool,
+    /// Dim the accent color while the terminal is not focused.
+    pub dim_unfocused: bool,
+    /// Precision of the group durations, unless set for the group, see
+    /// [`group::State::duration_precision`].
+    pub duration_precision: format::DurationPrecision,
+    /// Interval the duration and rate in footers of running groups are updated at, so that the
+    /// footer rows are not redrawn every frame. Zero updates them every frame.
+    pub footer_refresh: Duration,
+    /// Window of the line and error counts shown in footers, e.g. for long-running services.
+    /// Hidden if not set, see [`LineRange::window_stats`].
+    pub stats_window: Option<Duration>,
+    /// Highlighting rules of all log lines, applied after the rules of their groups.
+    #[cfg(feature = "regex")]
+    pub highlights: Vec<crate::highlight::Rule>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let show_eta = true;
+        let spinner = default();
+        let progress_label = true;
+        let sparkline_len = 0;
+        let unicode = true;
+        let dim_unfocused = true;
+        let duration_precision = default();
+        let footer_refresh = Duration::from_secs(1);
+        let stats_window = None;
+        #[cfg(feature = "regex")]
+        let highlights = default();
+        Self {
+            show_eta, spinner, progress_label, sparkline_len, unicode, dim_unfocused,
+            duration_precision, footer_refresh, stats_window,
+            #[cfg(feature = "regex")]
+            highlights,
+        }
+    }
 }
 
 // ===========
@@ -53,9 +309,15 @@ impl Debug for Any {
     }
 }
 
+impl Any {
+    pub fn new(style: impl Style + 'static) -> Self {
+        Self { style: Box::new(style) }
+    }
+}
+
 impl Default for Any {
     fn default() -> Self {
-        Self { style: Box::new(DefaultStyle) }
+        Self::new(DefaultStyle::default())
     }
 }
 
@@ -63,85 +325,396 @@ impl Default for Any {
 // === DefaultStyle ===
 // ====================
 
-#[derive(Clone, Copy, Debug)]
-pub struct DefaultStyle;
+/// Estimates longer than this are not displayed, as they are not meaningful.
+const MAX_ETA: Duration = Duration::from_secs(7 * 24 * 3600);
+
+#[derive(Clone, Debug, Default)]
+pub struct DefaultStyle {
+    pub theme: Theme,
+    /// Time the terminal lost focus at, if it is not focused. Animations are frozen at it.
+    unfocused_since: Option<SystemTime>,
+    /// Size of the terminal, if known. The progress bar length is derived from its width.
+    size: Option<terminal::Size>,
+}
 
 impl Style for DefaultStyle {
+    fn on_frame(&mut self, theme: &Theme) {
+        self.theme.clone_from(theme);
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused {
+            self.unfocused_since = None;
+        } else {
+            self.unfocused_since.get_or_insert_with(SystemTime::now);
+        }
+    }
+
+    fn set_size(&mut self, size: terminal::Size) {
+        self.size = Some(size);
+    }
+
     fn header(&mut self, group: &LineRange<&'_ Group>, group_index: group::Id, s: &str) -> String {
-        let progress_bar_len = 10;
+        let label = index_to_group_label(group_index.0).unwrap_or_else(|| "…".to_string());
+        let index = Self::border_style(group, &format!("[{label}]"));
+        let icon = group.icon.as_ref().filter(|_| self.theme.unicode)
+            .map(|icon| format!("{icon} ")).unwrap_or_default();
+        let border = self.border_top_left(group);
+        let prefix_width = format::visible_width(&format!("{border} {index} {icon}"));
+        let progress_bar_len = self.progress_bar_len(prefix_width);
+        let spinner = self.theme.spinner;
         let state = group.state();
         let last_line = state.view_lines().last();
-        let progress = last_line.and_then(|t| t.log.status.progress);
+        // The displayed progress never goes back within a run, so that a growing total number of
+        // steps does not make the bar jump backwards.
+        let progress = last_line.and_then(|t| t.log.status.progress).map(|progress| {
+            state.run_lines().iter().filter_map(|t| t.log.status.progress).fold(progress, f32::max)
+        });
         let finished = last_line.map(|t| t.log.status.is_finished()).unwrap_or_default();
+        let history_view = group.next_line.is_some();
+        // In the history view, animations are frozen at the phase of the last line.
+        let phase = Self::animation_phase(match last_line {
+            Some(line) if history_view => line.time,
+            _ => self.unfocused_since.unwrap_or_else(SystemTime::now),
+        });
         let progress_bar = match (progress, finished) {
-            (Some(progress), _) =>
-                Self::header_style(group, &widget::progress_bar(progress_bar_len, progress)),
-            (_, true) =>
-                Self::header_style(group, &widget::progress_bar(progress_bar_len, 1.0)),
-            _ => {
-                let time = group.next_line.map(|t| t.0 % progress_bar_len).unwrap_or_else(|| {
-                    let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default().as_millis();
-                    ((now / 100) % progress_bar_len as u128) as usize
-                });
-                widget::spinner(progress_bar_len, time)
-            }
+            _ if Self::is_blocked_cancelled(group) =>
+                self.progress_bar(group, progress_bar_len, 0.0),
+            _ if group.blocked.is_some() && self.theme.unicode =>
+                widget::waiting(spinner.width(progress_bar_len)),
+            _ if group.blocked.is_some() => widget::waiting_ascii(spinner.width(progress_bar_len)),
+            (Some(progress), _) => self.progress_bar(group, progress_bar_len, progress),
+            (_, true) => self.progress_bar(group, progress_bar_len, 1.0),
+            _ if last_line.is_none() && history_view =>
+                widget::not_started(spinner.width(progress_bar_len)),
+            _ if last_line.is_none() => widget::pending(spinner.width(progress_bar_len)),
+            _ if state.is_stale() && self.theme.unicode =>
+                widget::paused(spinner.width(progress_bar_len)),
+            _ if state.is_stale() => widget::paused_ascii(spinner.width(progress_bar_len)),
+            _ if last_line.is_some_and(|t| t.log.status.indeterminate) =>
+                widget::indeterminate_bar(progress_bar_len, phase),
+            _ => spinner.render(progress_bar_len, phase),
         };
-        let label = index_to_group_char(group_index.0).unwrap_or('…');
-        let index = Self::border_style(group, &format!("[{label}]"));
-        let border = Self::border_top_left(group);
-        let content = Self::header_style(group, s);
-        format!("{border} {index} {progress_bar} {content}")
+        let eta = self.theme.show_eta.then(|| Self::eta(group)).flatten().map(|eta| {
+            let eta = format::duration(eta, format::DurationPrecision::Seconds);
+            format!(" {}", Self::border_style(group, &format!("~{eta} left")))
+        }).unwrap_or_default();
+        let steps_width = self.steps_width(prefix_width, progress_bar_len);
+        let steps = last_line.and_then(|t| t.log.status.steps)
+            .and_then(|steps| Self::steps_text(steps, steps_width))
+            .map(|steps| format!(" {}", Self::border_style(group, &steps)))
+            .unwrap_or_default();
+        let sparkline = self.sparkline(group).map(|sparkline| {
+            format!(" {}", Self::border_style(group, &sparkline))
+        }).unwrap_or_default();
+        let content = self.header_style(group, s);
+        // Compact groups have no footer, so the duration is shown after the title instead.
+        let duration = if group.compact {
+            let (duration, _) = self.run_duration(group);
+            format!(" {}", self.duration_status(group, duration))
+        } else {
+            default()
+        };
+        let subtitle = group.subtitle.as_ref()
+            .map(|subtitle| format!(" {}", subtitle.clone().dark_grey())).unwrap_or_default();
+        let blocked = group.blocked.as_ref()
+            .map(|blocked| format!(" {}", blocked.subtitle().dark_grey())).unwrap_or_default();
+        let badges = group.badges.iter().map(|badge| {
+            format!(" {}", format!(" {} ", badge.text).black().on(badge.color))
+        }).collect::<String>();
+        let stalled = if group.stalled {
+            let text = if self.theme.unicode { " ⚠ stalled " } else { " ! stalled " };
+            format!(" {}", text.black().on_yellow())
+        } else {
+            default()
+        };
+        let widgets = if group.show_progress {
+            format!("{progress_bar}{sparkline}{steps}{eta} ")
+        } else {
+            default()
+        };
+        let suffix = format!("{duration}{subtitle}{blocked}{badges}{stalled}");
+        format!("{border} {index} {icon}{widgets}{content}{suffix}")
     }
 
     fn log_line(&mut self, group: &LineRange<&'_ Group>, _group_index: group::Id, s: &str) -> String {
-        let border = Self::border_left(group);
+        let border = self.border_left(group);
+        #[cfg(feature = "regex")]
+        let s = crate::highlight::apply(group.highlights.iter().chain(&self.theme.highlights), s);
         format!("{border} {s}")
     }
 
+    fn focused_log_line
+    (&mut self, _group: &LineRange<&'_ Group>, _group_index: group::Id, s: &str) -> String {
+        let marker = if self.theme.unicode { "▶" } else { ">" };
+        format!(" {} {}", marker.green().bold(), s.reverse())
+    }
+
+    fn collapse_toggle(&self, group: &LineRange<&'_ Group>) -> Option<Range<usize>> {
+        // The left padding, the arrow, and the space after it.
+        let arrow_end = format::visible_width(&self.border_top_left(group));
+        Some(0..arrow_end + 1)
+    }
+
+    fn log_line_indent(&self) -> usize {
+        // Left padding, border, and space.
+        3
+    }
+
     fn footer(&mut self, group: &LineRange<&'_ Group>, _group_index: group::Id, s: &str) -> String {
-        let state = group.state();
-        let lines = state.view_lines();
-        let border = lines.first().zip(lines.last());
-        let ms = if let Some((start, line_end)) = border.map(|(a, b)| (a.time, b.time)) {
-            let history_view = group.next_line.is_some();
-            let finished = lines.last().map(|t| t.log.status.is_finished()).unwrap_or_default();
-            let end = if history_view || finished { line_end } else { SystemTime::now() };
-            let duration = end.duration_since(start).unwrap_or_default();
-            duration.as_millis()
+        let (duration, now) = self.run_duration(group);
+        let status = self.duration_status(group, duration);
+        let border = self.border_bottom_left(group);
+        let rate = Self::rate(group, duration, now).map(|rate| {
+            format!(" {}", Self::border_style(group, &rate))
+        }).unwrap_or_default();
+        let stats = self.theme.stats_window.map(|window| {
+            let stats = group.state().window_stats(now, window);
+            let (lines, errors, secs) = (stats.lines, stats.errors, window.as_secs());
+            let text = format!("{lines} msgs / {errors} errors in last {secs}s");
+            format!(" {}", Self::border_style(group, &text))
+        }).unwrap_or_default();
+        format!("{border} {status}{rate}{stats} {s}")
+    }
+
+    fn separator(&mut self, group: &LineRange<&'_ Group>, _group_index: group::Id, run: usize)
+    -> String {
+        let border = self.border_left(group);
+        let separator = format!("── run {run} ──").dark_grey();
+        format!("{border} {separator}")
+    }
+
+    fn annotation(
+        &mut self,
+        group: &LineRange<&'_ Group>,
+        _group_index: group::Id,
+        annotation: &group::Annotation
+    ) -> String {
+        let border = self.border_left(group);
+        format!("{border}   {}", annotation_note(annotation))
+    }
+}
+
+/// Minimum progress bar length to render the percentage label inside.
+const MIN_LABELED_PROGRESS_BAR_LEN: usize = 8;
+
+/// Progress bar length used if the terminal size is not known.
+const DEFAULT_PROGRESS_BAR_LEN: usize = 10;
+
+/// Bounds of the progress bar length derived from the terminal width.
+const MIN_PROGRESS_BAR_LEN: usize = 4;
+const MAX_PROGRESS_BAR_LEN: usize = 40;
+
+/// Header columns kept for the title when deriving the progress bar length.
+const MIN_TITLE_WIDTH: usize = 20;
+
+/// The progress bar takes this fraction of the header columns left for the widgets and the title.
+const PROGRESS_BAR_WIDTH_DIVISOR: usize = 4;
+
+impl DefaultStyle {
+    /// Length of the progress bar, and of the spinner drawn in its place, in a header whose
+    /// border, label, and icon take `prefix_width` columns.
+    fn progress_bar_len(&self, prefix_width: usize) -> usize {
+        let Some(size) = self.size else { return DEFAULT_PROGRESS_BAR_LEN };
+        let free = size.cols.saturating_sub(prefix_width + MIN_TITLE_WIDTH);
+        (free / PROGRESS_BAR_WIDTH_DIVISOR).clamp(MIN_PROGRESS_BAR_LEN, MAX_PROGRESS_BAR_LEN)
+    }
+
+    /// Columns left for the steps after the progress bar, keeping [`MIN_TITLE_WIDTH`] columns
+    /// for the title. Not limited if the terminal size is not known.
+    fn steps_width(&self, prefix_width: usize, progress_bar_len: usize) -> usize {
+        let Some(size) = self.size else { return usize::MAX };
+        // The space before the steps.
+        size.cols.saturating_sub(prefix_width + progress_bar_len + MIN_TITLE_WIDTH + 1)
+    }
+
+    /// Steps as `done/total`, or only `done` if they do not fit the width, or `None` if neither
+    /// fits.
+    fn steps_text((done, total): (u64, u64), width: usize) -> Option<String> {
+        [format!("{done}/{total}"), done.to_string()].into_iter().find(|t| t.len() <= width)
+    }
+
+    fn progress_bar(&self, group: &LineRange<&'_ Group>, len: usize, progress: f32) -> String {
+        if self.theme.progress_label && len >= MIN_LABELED_PROGRESS_BAR_LEN {
+            let fill = if Self::is_error(group) {
+                Color::Red
+            } else if Self::is_cancelled(group) {
+                Color::DarkGrey
+            } else {
+                Color::Green
+            };
+            let label_style = widget::LabelStyle { fill, ..default() };
+            widget::progress_bar_labeled(len, progress, label_style)
         } else {
-            0
+            self.header_style(group, &widget::progress_bar(len, progress))
+        }
+    }
+
+    /// Sparkline of lines per second, ending at the last visible line in the history view.
+    fn sparkline(&self, group: &LineRange<&'_ Group>) -> Option<String> {
+        let len = self.theme.sparkline_len;
+        (len > 0).then(|| {
+            let state = group.state();
+            let now = if group.next_line.is_some() {
+                state.view_lines().last().map_or_else(SystemTime::now, |t| t.time)
+            } else {
+                SystemTime::now()
+            };
+            let counts = group.activity.counts(now, len);
+            if self.theme.unicode {
+                widget::sparkline(&counts, len)
+            } else {
+                widget::sparkline_ascii(&counts, len)
+            }
+        })
+    }
+
+    /// Animation frame index at the given time.
+    fn animation_phase(time: SystemTime) -> usize {
+        let ms = time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis();
+        (ms / 100) as usize
+    }
+
+    fn is_error(group: &LineRange<&'_ Group>) -> bool {
+        group.state().view_lines().last().map(|t| t.log.status.is_error()).unwrap_or_default()
+    }
+
+    fn is_cancelled(group: &LineRange<&'_ Group>) -> bool {
+        let last_line = group.state().view_lines().last();
+        last_line.is_some_and(|t| t.log.status.is_cancelled()) || Self::is_blocked_cancelled(group)
+    }
+
+    /// A dependency of the group failed, see [`group::Blocked`].
+    fn is_blocked_cancelled(group: &LineRange<&'_ Group>) -> bool {
+        matches!(group.blocked, Some(group::Blocked::Cancelled(_)))
+    }
+
+    /// Overrun of the time budget, frozen while the terminal is not focused.
+    fn overrun(&self, group: &LineRange<&'_ Group>) -> Option<group::Overrun> {
+        group.state().overrun(self.unfocused_since.unwrap_or_else(SystemTime::now))
+    }
+
+    fn overrun_style(overrun: group::Overrun, s: &str) -> String {
+        match overrun {
+            group::Overrun::Exceeded => s.yellow().bold().to_string(),
+            group::Overrun::Doubled => s.red().bold().to_string(),
+        }
+    }
+
+    /// Estimated remaining time of the current run, extrapolated from its progress so far. In the
+    /// history view, the estimate is frozen at the time of the last visible line.
+    fn eta(group: &LineRange<&'_ Group>) -> Option<Duration> {
+        let state = group.state();
+        let lines = state.run_lines();
+        let (first, last) = lines.first().zip(lines.last())?;
+        let progress = last.log.status.progress.filter(|p| *p > 0.0 && *p < 1.0)?;
+        let went_back = lines.iter().filter_map(|t| t.log.status.progress).any(|p| p > progress);
+        if went_back || last.log.status.is_finished() {
+            return None;
+        }
+        let end = if group.next_line.is_some() { last.time } else { SystemTime::now() };
+        let elapsed = end.duration_since(first.time).unwrap_or_default();
+        let eta = elapsed.as_secs_f32() * (1.0 - progress) / progress;
+        (eta <= MAX_ETA.as_secs_f32()).then(|| Duration::from_secs_f32(eta))
+    }
+
+    /// Duration of the current run, and the time it was measured at, see [`Self::footer_time`].
+    /// The duration is frozen at the last line if the run is finished or in the history view.
+    fn run_duration(&self, group: &LineRange<&'_ Group>) -> (Duration, SystemTime) {
+        let state = group.state();
+        let lines = state.run_lines();
+        let history_view = group.next_line.is_some();
+        let start = group.started_at.or_else(|| lines.first().map(|t| t.time));
+        let now = self.footer_time(start);
+        let end = match lines.last() {
+            Some(last) if history_view || last.log.status.is_finished() => Some(last.time),
+            _ if history_view => None,
+            _ => Some(now),
         };
+        let duration = start.zip(end)
+            .map(|(start, end)| end.duration_since(start).unwrap_or_default())
+            .unwrap_or_default();
+        (duration, now)
+    }
+
+    /// Formatted run duration, highlighted if the run exceeds its time budget.
+    fn duration_status(&self, group: &LineRange<&'_ Group>, duration: Duration) -> String {
         let is_finished = group.state().view_lines().last().map(|t| t.log.status.is_finished())
             .unwrap_or_default();
         let is_history_view = group.next_line.is_some();
-        let show_ms = is_finished || is_history_view;
+        let precision = group.duration_precision.unwrap_or(self.theme.duration_precision)
+            .for_running(!is_finished && !is_history_view);
+        let status = format::duration(duration, precision);
+        let overrun = group.time_budget.filter(|_| !is_finished)
+            .and_then(|budget| group::Overrun::new(duration, budget));
+        match overrun {
+            Some(overrun) => Self::overrun_style(overrun, &status),
+            None => Self::border_style(group, &status),
+        }
+    }
 
-        let status = format_duration(ms, show_ms);
-        let border = Self::border_bottom_left(group);
-        let status = Self::border_style(group, &status);
-        format!("{border} {status} {s}")
+    /// Current time used by the footer of a running group started at `start`. It is rounded down
+    /// to a multiple of [`Theme::footer_refresh`] since the start, so that the footer row does not
+    /// change every frame.
+    fn footer_time(&self, start: Option<SystemTime>) -> SystemTime {
+        let now = SystemTime::now();
+        let interval = self.theme.footer_refresh.as_nanos();
+        let Some(start) = start.filter(|_| interval > 0) else { return now };
+        let elapsed = now.duration_since(start).unwrap_or_default().as_nanos();
+        start + Duration::from_nanos((elapsed - elapsed % interval) as u64)
+    }
+
+    /// Current throughput of a running group, or the average one of a finished group. The
+    /// current throughput is not known in the history view, so the average is shown there too.
+    fn rate(group: &LineRange<&'_ Group>, run_duration: Duration, now: SystemTime)
+    -> Option<String> {
+        let state = group.state();
+        let lines = state.run_lines();
+        let throughput = &group.throughput;
+        let finished = lines.last().is_some_and(|t| t.log.status.is_finished());
+        if finished || group.next_line.is_some() {
+            let logs = lines.iter().filter(|t| !t.is_run_start());
+            let total: u64 = if throughput.uses_amount() {
+                logs.filter_map(|t| t.log.status.amount).sum()
+            } else {
+                logs.count() as u64
+            };
+            let secs = run_duration.as_secs_f64();
+            let rate = (secs > 0.0).then(|| total as f64 / secs)?;
+            Some(format!("avg {}", format_rate(rate, throughput.uses_amount())))
+        } else {
+            let rate = throughput.rate(now);
+            Some(format_rate(rate, throughput.uses_amount()))
+        }
     }
-}
 
-impl DefaultStyle {
     fn is_newest_output(group: &LineRange<&'_ Group>) -> bool {
         group.state().view_lines().last().zip(group.next_line).map(|(line, rage)| {
             line.timestamp.0 == rage.0 - 1
         }).unwrap_or_default()
     }
 
-    fn header_style(group: &LineRange<&'_ Group>, s: &str) -> String {
-        if group.state().view_lines().last().map(|t| t.log.status.is_error()).unwrap_or_default() {
+    fn header_style(&self, group: &LineRange<&'_ Group>, s: &str) -> String {
+        if Self::is_error(group) {
             s.red().bold().to_string()
+        } else if Self::is_cancelled(group) {
+            s.dark_grey().crossed_out().to_string()
+        } else if let Some(overrun) = self.overrun(group) {
+            Self::overrun_style(overrun, s)
+        } else if group.next_line.is_some() && group.is_pending() {
+            s.dark_grey().to_string()
+        } else if group.state().is_stale() {
+            s.dark_grey().bold().to_string()
+        } else if self.unfocused_since.is_some() && self.theme.dim_unfocused {
+            s.dark_green().to_string()
         } else {
             s.green().bold().to_string()
         }
     }
 
-    fn left_padding_style(group: &LineRange<&'_ Group>) -> String {
+    fn left_padding_style(&self, group: &LineRange<&'_ Group>) -> String {
         if Self::is_newest_output(group) {
-            "▍".green().to_string()
+            (if self.theme.unicode { "▍" } else { "*" }).green().to_string()
         } else {
             " ".to_string()
         }
@@ -150,29 +723,116 @@ impl DefaultStyle {
     fn border_style(group: &LineRange<&'_ Group>, border: &str) -> String {
         if group.selected {
             border.white().bold().to_string()
-        } else if group.state().view_lines().last().map(|t| t.log.status.is_error())
-            .unwrap_or_default() {
+        } else if Self::is_error(group) {
             border.red().bold().to_string()
+        } else if Self::is_cancelled(group) {
+            border.dark_grey().to_string()
         } else {
             border.grey().bold().to_string()
         }
     }
 
-    fn border_top_left(group: &LineRange<&'_ Group>) -> String {
-        let padding = Self::left_padding_style(group);
-        let border = Self::border_style(group, if group.is_collapsed() { "▶" } else { "▼" });
+    fn border_top_left(&self, group: &LineRange<&'_ Group>) -> String {
+        let padding = self.left_padding_style(group);
+        let arrow = match (self.theme.unicode, group.is_collapsed()) {
+            (true, true) => "▶",
+            (true, false) => "▼",
+            (false, true) => ">",
+            (false, false) => "v",
+        };
+        let border = Self::border_style(group, arrow);
         format!("{padding}{border}")
     }
 
-    fn border_left(group: &LineRange<&'_ Group>) -> String {
-        let padding = Self::left_padding_style(group);
-        let border = Self::border_style(group, "│");
+    fn border_left(&self, group: &LineRange<&'_ Group>) -> String {
+        let padding = self.left_padding_style(group);
+        let border = Self::border_style(group, if self.theme.unicode { "│" } else { "|" });
         format!("{padding}{border}")
     }
 
-    fn border_bottom_left(group: &LineRange<&'_ Group>) -> String {
-        let padding = Self::left_padding_style(group);
-        let border = Self::border_style(group, "╰");
+    fn border_bottom_left(&self, group: &LineRange<&'_ Group>) -> String {
+        let padding = self.left_padding_style(group);
+        let border = Self::border_style(group, if self.theme.unicode { "╰" } else { "`" });
         format!("{padding}{border}")
     }
 }
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_shortened_to_fit() {
+        let steps = |width| DefaultStyle::steps_text((12, 345), width);
+        assert_eq!(steps(usize::MAX).as_deref(), Some("12/345"));
+        assert_eq!(steps(6).as_deref(), Some("12/345"));
+        assert_eq!(steps(5).as_deref(), Some("12"));
+        assert_eq!(steps(1), None);
+    }
+
+    #[test]
+    fn progress_bar_scales_with_the_terminal_width() {
+        let mut style = DefaultStyle::default();
+        assert_eq!(style.progress_bar_len(10), DEFAULT_PROGRESS_BAR_LEN);
+        let lens = [0, 40, 80, 120, 1000].map(|cols| {
+            style.set_size(terminal::Size { cols, rows: 24 });
+            style.progress_bar_len(10)
+        });
+        let (min, max) = (MIN_PROGRESS_BAR_LEN, MAX_PROGRESS_BAR_LEN);
+        assert_eq!(lens, [min, min, 12, 22, max]);
+    }
+
+    #[test]
+    fn collapse_toggle_covers_the_rendered_arrow() {
+        let cases = [(true, false, false, 0), (true, true, true, 35), (false, true, false, 400)];
+        for (unicode, collapsed, selected, id) in cases {
+            let mut style = DefaultStyle::default();
+            style.theme.unicode = unicode;
+            let mut group = Group::new(group::Id(id));
+            group.collapsed = Some(collapsed);
+            group.selected = selected;
+            let group = LineRange { data: &group, next_line: None };
+            let header = format::strip_ansi(&style.header(&group, group.id, "build"));
+            let column = |pattern: &[char]| header.find(pattern).map(|t| {
+                format::visible_width(&header[..t])
+            });
+            let arrow = column(&['▶', '▼', '>', 'v']);
+            let label = column(&['[']);
+            let toggle = style.collapse_toggle(&group).unwrap_or_default();
+            assert!(arrow.is_some_and(|arrow| toggle.contains(&arrow)), "{header:?} {toggle:?}");
+            assert!(label.is_some_and(|label| toggle.end <= label), "{header:?} {toggle:?}");
+        }
+    }
+
+    #[test]
+    fn run_duration_starts_at_the_timer_and_ends_at_the_finish() {
+        let now = SystemTime::now();
+        let ago = |secs| now - Duration::from_secs(secs);
+        let mut style = DefaultStyle::default();
+        style.theme.footer_refresh = Duration::ZERO;
+        let cases = [
+            (None, true, 3..=3),
+            (Some(ago(10)), true, 8..=8),
+            (None, false, 5..=6),
+            (Some(ago(10)), false, 10..=11),
+        ];
+        for (started_at, finished, secs) in cases {
+            let mut group = Group::new(group::Id(0));
+            group.started_at = started_at;
+            for (id, time) in [ago(5), ago(2)].into_iter().enumerate() {
+                let status = if finished { group::Status::ok().finished() } else { default() };
+                let log = group::Log { content: default(), status, level: default() };
+                let (timestamp, kind) = (crate::LineId(id), default());
+                group.lines.push(group::Line { log, timestamp, time, kind, repeat: 1 });
+            }
+            let group = LineRange { data: &group, next_line: None };
+            let (duration, _) = style.run_duration(&group);
+            let case = (started_at.is_some(), finished, duration);
+            assert!(secs.contains(&duration.as_secs()), "{case:?}");
+        }
+    }
+}
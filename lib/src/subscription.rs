@@ -0,0 +1,141 @@
+use crate::prelude::*;
+
+use crate::LineId;
+use crate::group::Status;
+use std::collections::VecDeque;
+use std::sync::Condvar;
+use std::time::SystemTime;
+
+// =================
+// === LineEvent ===
+// =================
+
+/// Line pushed to a subscribed group: its id, time, status, and content.
+pub type LineEvent = (LineId, SystemTime, Status, String);
+
+/// Default number of events buffered by a [`LineReceiver`]. If it is exceeded, the oldest events
+/// are dropped.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+// ===============
+// === Channel ===
+// ===============
+
+#[derive(Debug, Default)]
+struct Queue {
+    events: VecDeque<LineEvent>,
+    /// Number of events dropped because the queue was full.
+    dropped: usize,
+    /// The logger side of the channel was dropped.
+    closed: bool,
+}
+
+#[derive(Debug, Default)]
+struct Channel {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+}
+
+impl Channel {
+    fn queue(&self) -> std::sync::MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ==================
+// === LineSender ===
+// ==================
+
+/// Logger side of a subscription.
+#[derive(Debug)]
+pub struct LineSender {
+    channel: Arc<Channel>,
+    capacity: usize,
+}
+
+impl LineSender {
+    /// Checks whether the receiver still exists.
+    pub fn is_connected(&self) -> bool {
+        Arc::strong_count(&self.channel) > 1
+    }
+
+    /// Queues the event, dropping the oldest one if the queue is full.
+    pub fn send(&self, event: LineEvent) {
+        let mut queue = self.channel.queue();
+        if queue.events.len() >= self.capacity {
+            queue.events.pop_front();
+            queue.dropped += 1;
+        }
+        queue.events.push_back(event);
+        self.channel.ready.notify_one();
+    }
+}
+
+impl Drop for LineSender {
+    fn drop(&mut self) {
+        self.channel.queue().closed = true;
+        self.channel.ready.notify_all();
+    }
+}
+
+// ====================
+// === LineReceiver ===
+// ====================
+
+/// Receiver of the lines pushed to the subscribed groups, see [`crate::subscribe`].
+#[derive(Debug)]
+pub struct LineReceiver {
+    channel: Arc<Channel>,
+}
+
+impl LineReceiver {
+    /// Blocks until a line is pushed. Returns `None` if the logger was dropped and all queued lines
+    /// were received.
+    pub fn recv(&self) -> Option<LineEvent> {
+        let mut queue = self.channel.queue();
+        loop {
+            if let Some(event) = queue.events.pop_front() {
+                return Some(event);
+            }
+            if queue.closed {
+                return None;
+            }
+            queue = self.channel.ready.wait(queue).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Returns the oldest queued line without blocking.
+    pub fn try_recv(&self) -> Option<LineEvent> {
+        self.channel.queue().events.pop_front()
+    }
+
+    /// Number of lines dropped because they were not received in time.
+    pub fn dropped_count(&self) -> usize {
+        self.channel.queue().dropped
+    }
+}
+
+impl Iterator for LineReceiver {
+    type Item = LineEvent;
+    fn next(&mut self) -> Option<LineEvent> {
+        self.recv()
+    }
+}
+
+/// Creates the two sides of a subscription queueing up to `capacity` lines.
+pub fn channel(capacity: usize) -> (LineSender, LineReceiver) {
+    let channel = Arc::new(Channel::default());
+    let capacity = capacity.max(1);
+    (LineSender { channel: channel.clone(), capacity }, LineReceiver { channel })
+}
+
+// ==================
+// === Subscriber ===
+// ==================
+
+/// Subscription to the groups at or under the path prefix.
+#[derive(Debug)]
+pub struct Subscriber {
+    pub prefix: Vec<String>,
+    pub sender: LineSender,
+}
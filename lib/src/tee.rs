@@ -0,0 +1,89 @@
+use crate::prelude::*;
+
+use crate::group::Log;
+use crate::group::Status;
+use crate::group;
+use crate::pattern::Pattern;
+
+// =============
+// === TeeId ===
+// =============
+
+/// Identifier of a registered tee, used to remove it.
+#[derive(Clone, Copy, Debug, Default, Deref, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TeeId(pub usize);
+
+// ===========
+// === Tee ===
+// ===========
+
+/// Mirrors the lines of the groups matching the pattern which pass the filter into the target
+/// group, see [`crate::SharedLogger::add_tee`]. The target is never its own source, even if it
+/// matches the pattern, so that e.g. `**` mirrors all other groups.
+#[derive(Clone)]
+pub struct Tee {
+    pub id: TeeId,
+    pub from: Pattern,
+    /// Path of the target group.
+    pub to: Vec<String>,
+    pub target: group::Id,
+    filter: Arc<dyn Fn(&Log) -> bool + Send + Sync>,
+}
+
+impl Debug for Tee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tee")
+            .field("id", &self.id)
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl Tee {
+    pub fn new(
+        id: TeeId,
+        from: Pattern,
+        to: Vec<String>,
+        target: group::Id,
+        filter: impl Fn(&Log) -> bool + Send + Sync + 'static
+    ) -> Self {
+        let filter = Arc::new(filter);
+        Self { id, from, to, target, filter }
+    }
+
+    pub fn is_source(&self, path: &[String]) -> bool {
+        self.from.matches(path) && self.to != path
+    }
+
+    /// Checks whether the line pushed to the group at the path is mirrored.
+    pub fn accepts(&self, path: &[String], log: &Log) -> bool {
+        self.is_source(path) && (self.filter)(log)
+    }
+}
+
+/// Checks whether a tee from the pattern to the path would mirror lines back into a group they
+/// come from, either directly or through the registered tees.
+pub fn creates_cycle(tees: &[Tee], from: &Pattern, to: &[String]) -> bool {
+    let mut visited = vec![to.to_vec()];
+    let mut pending = vec![to.to_vec()];
+    while let Some(path) = pending.pop() {
+        for tee in tees.iter().filter(|tee| tee.is_source(&path)) {
+            if !visited.contains(&tee.to) {
+                visited.push(tee.to.clone());
+                pending.push(tee.to.clone());
+            }
+        }
+    }
+    visited.iter().any(|path| from.matches(path) && path != to)
+}
+
+/// Copy of the line mirrored from the group with the given header, prefixed with it. The copy
+/// keeps the status tag and the level, but it does not report progress or finish the target.
+pub fn mirror(log: &Log, source: &str) -> Log {
+    let content = format!("[{source}] {}", log.content);
+    let status = Status { tag: log.status.tag, ..Status::ok() };
+    let level = log.level;
+    Log { content, status, level }
+}
@@ -1,5 +1,11 @@
 use crate::prelude::*;
 
+use crate::std_capture;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
 // ============
 // === Size ===
 // ============
@@ -17,28 +23,213 @@ impl Size {
     }
 }
 
+// ====================
+// === Capabilities ===
+// ====================
+
+/// Features supported by the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// ANSI escape sequences are interpreted. Legacy Windows consoles support them only if the
+    /// virtual terminal processing can be enabled.
+    pub ansi: bool,
+    /// Box-drawing and other symbols render well. Not the case in the legacy Windows console.
+    pub unicode: bool,
+}
+
+impl Capabilities {
+    #[cfg(windows)]
+    pub fn detect() -> Self {
+        let ansi = crossterm::ansi_support::supports_ansi();
+        let env_set = |name: &str| std::env::var_os(name).is_some();
+        // Set by Windows Terminal, terminals of editors, and terminal emulators, respectively.
+        let modern = env_set("WT_SESSION") || env_set("TERM_PROGRAM") || env_set("TERM");
+        Self { ansi, unicode: ansi && modern }
+    }
+
+    #[cfg(not(windows))]
+    pub fn detect() -> Self {
+        Self { ansi: true, unicode: true }
+    }
+}
+
+// ==============
+// === Resize ===
+// ==============
+
+/// How the next frame should be drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frame {
+    /// Redraw the changed lines only.
+    Incremental,
+    /// Clear the screen and redraw everything.
+    Full,
+    /// Do not draw, the terminal is being resized.
+    Skip,
+}
+
+/// Tracks the terminal size, delaying the redraw until the size stops changing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Resize {
+    size: Option<Size>,
+    changed_at: Option<SystemTime>,
+}
+
+impl Resize {
+    /// Records the size of the terminal at `now`. The first frame after the size was stable for
+    /// `debounce` is fully redrawn, the earlier ones are skipped. The very first frame is drawn
+    /// immediately.
+    pub fn update(&mut self, size: Size, now: SystemTime, debounce: Duration) -> Frame {
+        match self.size {
+            None => {
+                self.size = Some(size);
+                return Frame::Full
+            }
+            Some(prev_size) if prev_size != size => {
+                self.size = Some(size);
+                self.changed_at = Some(now);
+            }
+            _ => {}
+        }
+        let Some(changed_at) = self.changed_at else { return Frame::Incremental };
+        if now.duration_since(changed_at).unwrap_or_default() < debounce {
+            Frame::Skip
+        } else {
+            self.changed_at = None;
+            Frame::Full
+        }
+    }
+}
+
+// ==============
+// === Output ===
+// ==============
+
+/// Stream the UI is drawn to: the standard output, or its original target if the standard streams
+/// are captured, see [`crate::SharedLogger::capture_std`].
+#[derive(Debug)]
+pub enum Output {
+    Stdout(std::io::Stdout),
+    Stderr(std::io::Stderr),
+    File(std::fs::File),
+}
+
+impl Output {
+    pub fn current() -> Self {
+        std_capture::original_stdout().map_or_else(|| Self::Stdout(std::io::stdout()), Self::File)
+    }
+
+    /// The standard error, or its original target if the standard streams are captured.
+    pub fn current_err() -> Self {
+        std_capture::original_stderr().map_or_else(|| Self::Stderr(std::io::stderr()), Self::File)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Stdout(stdout) => stdout.is_terminal(),
+            Self::Stderr(stderr) => stderr.is_terminal(),
+            Self::File(file) => file.is_terminal(),
+        }
+    }
+}
+
+/// Environment variable disabling the UI if set to `1`.
+pub const HEADLESS_VAR: &str = "LMUX_HEADLESS";
+
+/// Checks whether the UI can be shown: the output is a terminal and [`HEADLESS_VAR`] is not set.
+pub fn is_interactive() -> bool {
+    let headless = std::env::var(HEADLESS_VAR).is_ok_and(|t| t == "1");
+    !headless && Output::current().is_terminal()
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+            Self::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+            Self::File(file) => file.flush(),
+        }
+    }
+}
+
 // =========================
 // === Capture / Cleanup ===
 // =========================
 
-pub fn capture() -> Result {
-    let mut stdout = std::io::stdout();
+/// Switches the terminal to the alternate screen in raw mode, reporting mouse events if
+/// `mouse_capture` is set. The main screen is used if `inline` is set.
+pub fn capture(mouse_capture: bool, inline: bool) -> Result {
+    let mut stdout = Output::current();
     crossterm::terminal::enable_raw_mode()?;
-    // Disable line wrap
-    crossterm::execute!(stdout, crossterm::style::Print("\x1B[?7l"))?;
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    crossterm::execute!(stdout, crossterm::terminal::DisableLineWrap)?;
+    if !inline {
+        crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    }
     crossterm::execute!(stdout, crossterm::cursor::Hide)?;
-    crossterm::execute!(stdout, crossterm::event::EnableMouseCapture)?;
+    crossterm::execute!(stdout, crossterm::event::EnableBracketedPaste)?;
+    crossterm::execute!(stdout, crossterm::event::EnableFocusChange)?;
+    if mouse_capture {
+        crossterm::execute!(stdout, crossterm::event::EnableMouseCapture)?;
+    }
     Ok(())
 }
 
-pub fn cleanup() -> Result {
-    let mut stdout = std::io::stdout();
-    // Enable line wrap
-    crossterm::execute!(stdout, crossterm::style::Print("\x1B[?7h"))?;
+/// Restores the terminal, and the standard streams if they are captured. The last frame is kept
+/// on the main screen if `inline` is set, see [`capture`].
+pub fn cleanup(inline: bool) -> Result {
+    let mut stdout = Output::current();
+    crossterm::execute!(stdout, crossterm::terminal::EnableLineWrap)?;
     crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(stdout, crossterm::terminal::LeaveAlternateScreen)?;
+    if inline {
+        let (_, rows) = crossterm::terminal::size()?;
+        crossterm::execute!(stdout, crossterm::cursor::MoveTo(0, rows.saturating_sub(1)))?;
+        crossterm::execute!(stdout, crossterm::style::Print("\r\n"))?;
+    } else {
+        crossterm::execute!(stdout, crossterm::terminal::LeaveAlternateScreen)?;
+    }
     crossterm::execute!(stdout, crossterm::cursor::Show)?;
+    crossterm::execute!(stdout, crossterm::event::DisableBracketedPaste)?;
+    crossterm::execute!(stdout, crossterm::event::DisableFocusChange)?;
     crossterm::execute!(stdout, crossterm::event::DisableMouseCapture)?;
-    Ok(())
-}
\ No newline at end of file
+    std_capture::restore()
+}
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames drawn for the sizes reported at the times, in milliseconds.
+    fn frames(debounce: Duration, events: &[(usize, u64)]) -> Vec<Frame> {
+        let mut resize = Resize::default();
+        events.iter().map(|&(cols, millis)| {
+            let now = SystemTime::UNIX_EPOCH + Duration::from_millis(millis);
+            resize.update(Size { cols, rows: 24 }, now, debounce)
+        }).collect()
+    }
+
+    #[test]
+    fn frames_are_skipped_until_the_size_is_stable() {
+        use Frame::*;
+        let events = [(80, 0), (80, 10), (90, 20), (100, 60), (100, 100), (100, 160), (100, 170)];
+        let frames = frames(Duration::from_millis(100), &events);
+        assert_eq!(frames, [Full, Incremental, Skip, Skip, Skip, Full, Incremental]);
+    }
+
+    #[test]
+    fn zero_debounce_redraws_every_resize() {
+        use Frame::*;
+        let frames = frames(Duration::ZERO, &[(80, 0), (90, 0), (90, 0), (100, 10)]);
+        assert_eq!(frames, [Full, Full, Incremental, Full]);
+    }
+}
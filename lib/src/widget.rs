@@ -1,4 +1,7 @@
 use crate::prelude::*;
+
+use crate::format;
+use crossterm::style::Color;
 use crossterm::style::Stylize;
 
 // ===============
@@ -12,12 +15,142 @@ pub fn spinner(n: usize, i: usize) -> String {
     format!("{prefix}{marker}{suffix}").bold().on_grey().to_string()
 }
 
+// ===============
+// === Spinner ===
+// ===============
+
+/// Animation shown for work in progress of unknown length.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Spinner {
+    /// Block sweeping across the whole bar.
+    #[default]
+    Sweep,
+    /// Single rotating Braille character.
+    Braille,
+    /// Dots filling and emptying three cells.
+    Dots,
+    /// Single rotating ASCII character, for terminals without Unicode support.
+    Ascii,
+}
+
+impl Spinner {
+    const BRAILLE: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    const DOTS: &[&str] = &["   ", ".  ", ".. ", "...", " ..", "  ."];
+    const ASCII: &[&str] = &["|", "/", "-", "\\"];
+
+    /// Animation frames, or `None` if the frames are drawn to fill the width reserved for
+    /// progress bars.
+    fn frames(self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Sweep => None,
+            Self::Braille => Some(Self::BRAILLE),
+            Self::Dots => Some(Self::DOTS),
+            Self::Ascii => Some(Self::ASCII),
+        }
+    }
+
+    /// Width of the spinner in cells, given the width reserved for progress bars. It is the width
+    /// of the widest frame, and every frame is padded to it.
+    pub fn width(self, len: usize) -> usize {
+        self.frames().map_or(len, |frames| {
+            frames.iter().map(|frame| format::visible_width(frame)).max().unwrap_or_default()
+        })
+    }
+
+    /// Renders the animation frame for the given phase. The phase grows with time, and is wrapped
+    /// around the number of the spinner frames.
+    pub fn render(self, len: usize, phase: usize) -> String {
+        let Some(frames) = self.frames() else { return spinner(len, phase % len.max(1)) };
+        let frame = frames[phase % frames.len()];
+        let padding = self.width(len).saturating_sub(format::visible_width(frame));
+        let frame = format!("{frame}{}", " ".repeat(padding));
+        match self {
+            Self::Ascii => frame,
+            _ => frame.green().bold().to_string(),
+        }
+    }
+}
+
+// =========================
+// === indeterminate_bar ===
+// =========================
+
+/// Segment bouncing between the ends of the bar, for work of unknown length.
+pub fn indeterminate_bar(len: usize, phase: usize) -> String {
+    let segment = (len / 4).max(1).min(len);
+    let travel = len - segment;
+    let period = (2 * travel).max(1);
+    let step = phase % period;
+    let position = if step <= travel { step } else { period - step };
+    let prefix = " ".repeat(position);
+    let marker = "█".repeat(segment).green();
+    let suffix = " ".repeat(travel - position);
+    format!("{prefix}{marker}{suffix}").on_grey().to_string()
+}
+
+// ==============
+// === paused ===
+// ==============
+
+/// Static replacement of the spinner for work that stopped making progress.
+pub fn paused(n: usize) -> String {
+    let suffix = " ".repeat(n.saturating_sub(1));
+    format!("⏸{suffix}").dark_grey().on_grey().to_string()
+}
+
+/// Version of [`paused`] for terminals without Unicode support.
+pub fn paused_ascii(n: usize) -> String {
+    let label: String = "paused".chars().take(n).collect();
+    format!("{label:<n$}").dark_grey().to_string()
+}
+
+// ===============
+// === waiting ===
+// ===============
+
+/// Static replacement of the spinner for work waiting on other work, see [`crate::group::Blocked`].
+pub fn waiting(n: usize) -> String {
+    let suffix = " ".repeat(n.saturating_sub(1));
+    format!("⧗{suffix}").dark_grey().on_grey().to_string()
+}
+
+/// Version of [`waiting`] for terminals without Unicode support.
+pub fn waiting_ascii(n: usize) -> String {
+    let label: String = "waiting".chars().take(n).collect();
+    format!("{label:<n$}").dark_grey().to_string()
+}
+
+// ===============
+// === pending ===
+// ===============
+
+/// Static replacement of the spinner for work that did not start yet.
+pub fn pending(n: usize) -> String {
+    let label: String = "pending".chars().take(n).collect();
+    format!("{label:<n$}").dark_grey().to_string()
+}
+
+// ===================
+// === not_started ===
+// ===================
+
+/// Static replacement of the spinner for work that did not start yet at the time shown in the
+/// history view.
+pub fn not_started(n: usize) -> String {
+    let label: String = "not yet".chars().take(n).collect();
+    format!("{label:<n$}").dark_grey().to_string()
+}
+
 // ====================
 // === progress_bar ===
 // ====================
 
+const PROGRESS_SYMBOL: &[char] = &[' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
 pub fn progress_bar(len: usize, progress: f32) -> String {
-    const SYMBOL: &[char] = &[' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+    const SYMBOL: &[char] = PROGRESS_SYMBOL;
     let fill_f = (len as f32) * progress;
     let fill_full = fill_f.floor() as usize;
     let fill_partial = fill_f.fract();
@@ -31,3 +164,148 @@ pub fn progress_bar(len: usize, progress: f32) -> String {
     let suffix = " ".repeat(len.saturating_sub(fill_f.ceil() as usize));
     format!("{fill_full_str}{fill_partial_str}{suffix}").on_grey().to_string()
 }
+
+// ============================
+// === progress_bar_labeled ===
+// ============================
+
+/// Styling of [`progress_bar_labeled`].
+#[derive(Clone, Copy, Debug)]
+pub struct LabelStyle {
+    /// Color of the filled part of the bar.
+    pub fill: Color,
+    /// Show `✓` instead of `done` when the progress is complete.
+    pub check_when_done: bool,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        let fill = Color::Reset;
+        let check_when_done = false;
+        Self { fill, check_when_done }
+    }
+}
+
+/// Progress bar with the percentage label centered inside. The label colors are inverted where
+/// it overlaps the filled part of the bar. The result is always exactly `len` cells wide, the
+/// label is skipped if it does not fit.
+pub fn progress_bar_labeled(len: usize, progress: f32, style: LabelStyle) -> String {
+    let progress = progress.clamp(0.0, 1.0);
+    let label = if progress >= 1.0 {
+        if style.check_when_done { "✓".to_string() } else { "done".to_string() }
+    } else {
+        format!("{}%", (progress * 100.0).floor())
+    };
+    let label: Vec<char> = label.chars().collect();
+    let label_start = (label.len() <= len).then(|| (len - label.len()) / 2);
+    let fill_f = (len as f32) * progress;
+    let fill_full = fill_f.floor() as usize;
+    let fill_partial = fill_f.fract();
+    (0..len).map(|i| {
+        let label_char = label_start.and_then(|start| label.get(i.checked_sub(start)?));
+        let is_filled = i < fill_full || (i == fill_full && fill_partial >= 0.5);
+        match label_char {
+            Some(char) if is_filled => char.grey().on(style.fill).to_string(),
+            Some(char) => char.with(style.fill).on_grey().to_string(),
+            None if i < fill_full => '█'.with(style.fill).on_grey().to_string(),
+            None if i == fill_full && fill_partial != 0.0 => {
+                let symbol_index =
+                    (fill_partial * (PROGRESS_SYMBOL.len() - 1) as f32).round() as usize;
+                PROGRESS_SYMBOL[symbol_index].with(style.fill).on_grey().to_string()
+            }
+            None => ' '.on_grey().to_string(),
+        }
+    }).collect()
+}
+
+// =================
+// === sparkline ===
+// =================
+
+const SPARKLINE_SYMBOL: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARKLINE_SYMBOL_ASCII: &[char] = &['_', '.', ':', '-', '=', '+', '*', '#'];
+
+/// Sparkline of the last `len` values, normalized to the maximum of them. Missing values are
+/// rendered as zeros, and non-zero values are always visibly higher than zero.
+pub fn sparkline(values: &[f32], len: usize) -> String {
+    sparkline_with(SPARKLINE_SYMBOL, values, len)
+}
+
+/// Version of [`sparkline`] for terminals without Unicode support.
+pub fn sparkline_ascii(values: &[f32], len: usize) -> String {
+    sparkline_with(SPARKLINE_SYMBOL_ASCII, values, len)
+}
+
+fn sparkline_with(symbols: &[char], values: &[f32], len: usize) -> String {
+    let values = &values[values.len().saturating_sub(len)..];
+    let max = values.iter().copied().fold(0.0, f32::max);
+    let top = (symbols.len() - 1) as f32;
+    let padding = std::iter::repeat_n(symbols[0], len - values.len());
+    let line = values.iter().map(|value| {
+        let is_zero = max <= 0.0 || *value <= 0.0;
+        let level = if is_zero { 0.0 } else { (value / max * top).round().max(1.0) };
+        symbols[level as usize]
+    });
+    padding.chain(line).collect()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spinner_frames_have_the_spinner_width() {
+        let styles = [Spinner::Sweep, Spinner::Braille, Spinner::Dots, Spinner::Ascii];
+        for spinner in styles {
+            for len in [1, 4, 10] {
+                let width = spinner.width(len);
+                for phase in 0..20 {
+                    let frame = spinner.render(len, phase);
+                    assert_eq!(format::visible_width(&frame), width, "{spinner:?} {frame:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn labeled_progress_bars_have_the_exact_width() {
+        let styles = [
+            LabelStyle::default(),
+            LabelStyle { fill: Color::Red, check_when_done: true },
+        ];
+        for style in styles {
+            for len in [0, 1, 2, 3, 4, 5, 10, 80] {
+                for progress in [-1.0, 0.0, 0.005, 0.333, 0.5, 0.999, 1.0, 1.5] {
+                    let bar = progress_bar_labeled(len, progress, style);
+                    assert_eq!(format::visible_width(&bar), len, "{style:?} {len} {progress}");
+                }
+            }
+        }
+        let text = |len, progress, check_when_done| {
+            let style = LabelStyle { check_when_done, ..default() };
+            format::strip_ansi(&progress_bar_labeled(len, progress, style))
+        };
+        assert_eq!(text(10, 0.5, false), "███50%    ");
+        assert_eq!(text(3, 0.5, false), "50%");
+        assert_eq!(text(2, 0.5, false), "█ ");
+        assert_eq!(text(4, 1.0, false), "done");
+        assert_eq!(text(3, 1.0, false), "███");
+        assert_eq!(text(3, 1.0, true), "█✓█");
+        assert_eq!(text(5, 0.0, true), " 0%  ");
+    }
+
+    #[test]
+    fn sparklines_are_normalized_to_the_maximum() {
+        assert_eq!(sparkline(&[], 3), "▁▁▁");
+        assert_eq!(sparkline(&[0.0, 0.0, 0.0], 3), "▁▁▁");
+        assert_eq!(sparkline(&[0.0, 0.0, 10.0, 0.0], 4), "▁▁█▁");
+        assert_eq!(sparkline(&[1.0, 50.0, 100.0], 3), "▂▅█");
+        assert_eq!(sparkline(&[100.0, 0.0, 7.0], 2), "▁█");
+        assert_eq!(sparkline(&[3.0], 3), "▁▁█");
+        assert_eq!(sparkline_ascii(&[0.0, 1.0, 100.0], 3), "_.#");
+    }
+}